@@ -1,53 +1,182 @@
 #![allow(clippy::missing_const_for_fn)]
 
-use criterion::criterion_main;
-
-macro_rules! gen_benchmarks {
-    ( $($day_index:expr,)* ) => {
-        $(
-            paste::paste! {
-                #[cfg(feature = "day" $day_index)]
-                pub fn [<day_ $day_index _benches>](c: &mut criterion::Criterion) {
-                    use criterion::black_box;
-                    use aoc2022::*;
-
-                    c.bench_function(&format!("Day {} silver (sample)", stringify!($day_index)), |b| {
-                        use [<day $day_index>]::Day;
-                        b.iter(|| Day::calculate_silver(black_box(Day::INPUT_SAMPLE)))
-                    });
-                    c.bench_function(&format!("Day {} silver (real)", stringify!($day_index)), |b| {
-                        use [<day $day_index>]::Day;
-                        b.iter(|| Day::calculate_silver(black_box(Day::INPUT_REAL)))
-                    });
-                    c.bench_function(&format!("Day {} gold (sample)", stringify!($day_index)), |b| {
-                        use [<day $day_index>]::Day;
-                        b.iter(|| Day::calculate_gold(black_box(Day::INPUT_SAMPLE)))
-                    });
-                    c.bench_function(&format!("Day {} gold (real)", stringify!($day_index)), |b| {
-                        use [<day $day_index>]::Day;
-                        b.iter(|| Day::calculate_gold(black_box(Day::INPUT_REAL)))
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    path::PathBuf,
+    time::{Duration, Instant},
+};
+
+use aoc2022::{
+    memory::{self, Usage},
+    registry, InputKind, Params, Part,
+};
+use criterion::{black_box, BenchmarkId, Criterion, Throughput};
+
+/// Benchmarks every day in the registry in its own group, so each day can be selected and compared
+/// on its own. The throughput is the length of the input, to compare inputs of different sizes.
+pub fn benchmarks(c: &mut Criterion, usages: &mut Usages) {
+    for entry in registry::days() {
+        let params = |kind| Params::parse(entry.params(kind)).unwrap();
+        let group_name = format!("Day {:02}", entry.day);
+        let mut group = c.benchmark_group(&group_name);
+
+        for kind in InputKind::ALL {
+            let (input, params) = (entry.input(kind), params(kind));
+            group.throughput(Throughput::Bytes(input.len() as u64));
+            let name = format!("parse ({kind})");
+            group.bench_function(&name, |b| {
+                usages.measure(&group_name, &name, || (entry.parse)(input, &params));
+                b.iter(|| (entry.parse)(black_box(input), &params))
+            });
+        }
+
+        for part in Part::ALL {
+            let (Some(solve), Some(with_parsed)) = (entry.part(part), entry.with_parsed(part)) else {
+                continue;
+            };
+
+            for kind in InputKind::ALL {
+                let (input, params) = (entry.input(kind), params(kind));
+                group.throughput(Throughput::Bytes(input.len() as u64));
+                let name = format!("{part} ({kind})");
+                group.bench_function(&name, |b| {
+                    usages.measure(&group_name, &name, || solve(input, &params));
+                    b.iter(|| solve(black_box(input), &params))
+                });
+
+                with_parsed(input, &params, &mut |solve| {
+                    let name = format!("{part} solve ({kind})");
+                    group.bench_function(&name, |b| {
+                        usages.measure(&group_name, &name, &mut *solve);
+                        b.iter(&mut *solve)
                     });
-                }
+                })
+                .unwrap();
+            }
+        }
+
+        group.finish();
+    }
+}
+
+/// The sizes of the generated inputs in the scaling benchmarks.
+const SCALING_SIZES: [usize; 6] = [4, 16, 64, 256, 1024, 4096];
+/// The scaling benchmarks of a part stop before inputs longer than this.
+const SCALING_MAX_BYTES: usize = 1 << 20;
+/// The scaling benchmarks of a part stop after the first input that takes longer than this to
+/// solve, so days that scale badly still finish.
+const SCALING_MAX_TIME: Duration = Duration::from_secs(1);
+
+/// Benchmarks every day on generated inputs of growing sizes, in a `Day 01 scaling` group per day.
+/// The throughput is the length of the input, so a solution that takes linear time in it has a
+/// flat throughput curve.
+pub fn scaling_benchmarks(c: &mut Criterion, usages: &mut Usages) {
+    let params = Params::default();
+    for entry in registry::days() {
+        let group_name = format!("Day {:02} scaling", entry.day);
+        let mut group = c.benchmark_group(&group_name);
+        group.sample_size(10);
+
+        for part in Part::ALL {
+            let Some(solve) = entry.part(part) else {
+                continue;
+            };
 
-                cfg_if::cfg_if! {
-                    if #[cfg(feature = "day" $day_index)] {
-                        criterion::criterion_group!([<day_ $day_index>], [<day_ $day_index _benches>]);
-                    } else {
-                        fn [<day_ $day_index>]() {}
-                    }
+            for size in SCALING_SIZES {
+                let Some(input) = entry.generate(size, 0) else {
+                    break;
+                };
+                if input.len() > SCALING_MAX_BYTES {
+                    break;
                 }
 
-            }
-        )*
+                let mut elapsed = None;
+                group.throughput(Throughput::Bytes(input.len() as u64));
+                let id = BenchmarkId::new(part.to_string(), size);
+                group.bench_with_input(id, input.as_str(), |b, input| {
+                    let name = format!("{part}/{size}");
+                    usages.measure(&group_name, &name, || solve(input, &params));
+                    elapsed.get_or_insert_with(|| {
+                        let start = Instant::now();
+                        let _ = solve(input, &params);
+                        start.elapsed()
+                    });
+                    b.iter(|| solve(black_box(input), &params))
+                });
 
-        paste::paste!{
-            criterion_main! {
-                $([<day_ $day_index>],)*
+                // benchmarks that are filtered out never run, so they do not stop larger inputs
+                if matches!(elapsed, Some(elapsed) if elapsed > SCALING_MAX_TIME) {
+                    break;
+                }
             }
         }
+
+        group.finish();
+    }
+}
+
+/// The heap usage of every benchmark that was run, which is stored next to the Criterion results
+/// when memory tracking is enabled.
+#[derive(Default)]
+pub struct Usages(BTreeMap<String, Usage>);
+
+impl Usages {
+    /// Measures a single run of a benchmark, unless it was already measured. Criterion calls the
+    /// benchmark function for every sample, and only for benchmarks that match its filter.
+    fn measure<T>(&mut self, group: &str, name: &str, fun: impl FnOnce() -> T) {
+        let id = format!("{group}/{name}");
+        if !memory::ENABLED || self.0.contains_key(&id) {
+            return;
+        }
+
+        if let (_, Some(usage)) = memory::measure(fun) {
+            self.0.insert(id, usage);
+        }
+    }
+
+    /// Writes the measured usages to `memory.json` in the Criterion output directory.
+    fn save(&self) {
+        if self.0.is_empty() {
+            return;
+        }
+
+        let mut json = String::from("{\n");
+        for (i, (name, usage)) in self.0.iter().enumerate() {
+            let separator = if i + 1 < self.0.len() { "," } else { "" };
+            writeln!(
+                json,
+                "  \"{name}\": {{\"peak_bytes\":{},\"allocations\":{}}}{separator}",
+                usage.peak_bytes, usage.allocations
+            )
+            .unwrap();
+        }
+        json.push_str("}\n");
+
+        // the same directory that Criterion uses
+        let directory = std::env::var_os("CRITERION_HOME").map_or_else(
+            || {
+                std::env::var_os("CARGO_TARGET_DIR")
+                    .map_or_else(|| PathBuf::from("target"), PathBuf::from)
+                    .join("criterion")
+            },
+            PathBuf::from,
+        );
+        let path = directory.join("memory.json");
+        match std::fs::create_dir_all(&directory).and_then(|()| std::fs::write(&path, json)) {
+            Ok(()) => println!("Heap usage written to {}", path.display()),
+            Err(e) => eprintln!("Could not write {}: {e}", path.display()),
+        }
     }
 }
 
-gen_benchmarks! {
-    01, 02, 03, 04, 05, 06, 07, 08, 09, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+// Based on criterion's `criterion_group` and `criterion_main` macros, which cannot pass the usages
+// along.
+fn main() {
+    let mut usages = Usages::default();
+    let mut criterion = Criterion::default().configure_from_args();
+    benchmarks(&mut criterion, &mut usages);
+    scaling_benchmarks(&mut criterion, &mut usages);
+    criterion.final_summary();
+    usages.save();
 }
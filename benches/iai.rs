@@ -1,56 +1,48 @@
-// Based on iai 0.1.1's `main` macro.
-macro_rules! gen_benchmarks {
-    ( $($day_index:expr,)* ) => {
-        paste::paste! {
-            mod iai_wrappers {
-                $(
-                    #[cfg(feature = "day" $day_index)]
-                    pub fn [< day_ $day_index _silver_sample >]() {
-                        use aoc2022::*;
-                        use iai::black_box;
-                        let _ = black_box([<day $day_index>]::Day::calculate_silver(black_box([<day $day_index>]::Day::INPUT_SAMPLE)));
-                    }
-
-                    #[cfg(feature = "day" $day_index)]
-                    pub fn [< day_ $day_index _silver_real >]() {
-                        use aoc2022::*;
-                        use iai::black_box;
-                        let _ = black_box([<day $day_index>]::Day::calculate_silver(black_box([<day $day_index>]::Day::INPUT_REAL)));
-                    }
+use aoc2022::{registry, InputKind, Part};
+use iai::black_box;
 
-                    #[cfg(feature = "day" $day_index)]
-                    pub fn [< day_ $day_index _gold_sample >]() {
-                        use aoc2022::*;
-                        use iai::black_box;
-                        let _ = black_box([<day $day_index>]::Day::calculate_gold(black_box([<day $day_index>]::Day::INPUT_SAMPLE)));
-                    }
-
-                    #[cfg(feature = "day" $day_index)]
-                    pub fn [< day_ $day_index _gold_real >]() {
-                        use aoc2022::*;
-                        use iai::black_box;
-                        let _ = black_box([<day $day_index>]::Day::calculate_gold(black_box([<day $day_index>]::Day::INPUT_REAL)));
-                    }
-                )*
-            }
-        }
+/// Runs a part of a day against one of its embedded inputs. iai needs a plain function for every
+/// benchmark, so the day, part and input are passed as const parameters.
+fn run<const DAY: u32, const GOLD: bool, const REAL: bool>() {
+    let entry = registry::get(DAY).expect("day should be registered");
+    let part = if GOLD { Part::Gold } else { Part::Silver };
+    let kind = if REAL {
+        InputKind::Real
+    } else {
+        InputKind::Sample
+    };
+    let _ = black_box(entry.solve_embedded(part, black_box(kind)));
+}
 
-        fn main() {
-            let benchmarks : &[&(&'static str, fn())]= &[
-                $(
-                    paste::paste!(#[cfg(feature = "day" $day_index)] &(stringify!([< day_ $day_index _silver_sample >]), iai_wrappers::[< day_ $day_index _silver_sample >])),
-                    paste::paste!(#[cfg(feature = "day" $day_index)] &(stringify!([< day_ $day_index _silver_real >]), iai_wrappers::[< day_ $day_index _silver_real >])),
-                    paste::paste!(#[cfg(feature = "day" $day_index)] &(stringify!([< day_ $day_index _gold_sample >]), iai_wrappers::[< day_ $day_index _gold_sample >])),
-                    paste::paste!(#[cfg(feature = "day" $day_index)] &(stringify!([< day_ $day_index _gold_real >]), iai_wrappers::[< day_ $day_index _gold_real >])),
-                )*
+macro_rules! gen_benchmarks {
+    ( $($day_index:literal $parts:ident,)* ) => {
+        /// A benchmark for every day, part and input, including the ones that are not in the
+        /// feature set or not implemented.
+        const BENCHMARKS: &[(u32, Part, InputKind, fn())] = &[
+            $(
+                ($day_index, Part::Silver, InputKind::Sample, run::<$day_index, false, false>),
+                ($day_index, Part::Silver, InputKind::Real, run::<$day_index, false, true>),
+                ($day_index, Part::Gold, InputKind::Sample, run::<$day_index, true, false>),
+                ($day_index, Part::Gold, InputKind::Real, run::<$day_index, true, true>),
+            )*
+        ];
+    };
+}
 
-            ];
+aoc2022::for_each_day!(gen_benchmarks);
 
-            iai::runner(benchmarks);
-        }
-    }
-}
+// Does the same as iai 0.1.1's `main` macro, for benchmarks that are only known at runtime.
+fn main() {
+    // only keep the parts that are implemented by the days in the feature set
+    let benchmarks: Vec<(&'static str, fn())> = BENCHMARKS
+        .iter()
+        .filter(|(day, part, ..)| registry::get(*day).and_then(|e| e.part(*part)).is_some())
+        .map(|&(day, part, kind, fun)| {
+            let name = format!("day_{day:02}_{part}_{kind}");
+            (&*Box::leak(name.into_boxed_str()), fun)
+        })
+        .collect();
+    let benchmarks: Vec<_> = benchmarks.iter().collect();
 
-gen_benchmarks! {
-    01, 02, 03, 04, 05, 06, 07, 08, 09, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25,
+    iai::runner(&benchmarks);
 }
@@ -0,0 +1,42 @@
+#![allow(dead_code)]
+
+use std::fmt::Display;
+
+use aoc2022::{SolutionGold, SolutionSilver};
+
+/// Runs the silver part on `data`. Malformed input should be reported as an error, so any panic,
+/// hang or overflow is a bug.
+pub fn fuzz_silver<D, TSilver>(data: &[u8])
+where
+    D: SolutionSilver<TSilver>,
+    TSilver: Display,
+{
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    // the runner normalizes every input that is not embedded, so the parsers never see anything
+    // else
+    let input = D::INPUT_FORMAT.normalize(input);
+    if let Ok(answer) = D::try_calculate_silver(&input) {
+        let _ = answer.to_string();
+    }
+}
+
+/// Runs both parts on `data`, like [`fuzz_silver`].
+pub fn fuzz_gold<D, TSilver, TGold>(data: &[u8])
+where
+    D: SolutionGold<TSilver, TGold>,
+    TSilver: Display,
+    TGold: Display,
+{
+    fuzz_silver::<D, TSilver>(data);
+
+    let Ok(input) = std::str::from_utf8(data) else {
+        return;
+    };
+    let input = D::INPUT_FORMAT.normalize(input);
+    if let Ok(answer) = D::try_calculate_gold(&input) {
+        let _ = answer.to_string();
+    }
+}
@@ -0,0 +1,5 @@
+#![no_main]
+
+mod common;
+
+libfuzzer_sys::fuzz_target!(|data: &[u8]| common::fuzz_gold::<aoc2022::day03::Day, _, _>(data));
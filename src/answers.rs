@@ -0,0 +1,252 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use crate::input::InputError;
+
+use super::*;
+
+/// The file name that answers are loaded from when it is present in an input directory.
+pub const ANSWERS_FILE_NAME: &str = "answers.txt";
+
+/// The expected answers for the embedded inputs.
+const EMBEDDED_ANSWERS: &str = include_str!("../answers.txt");
+
+/// Expected answers for each day, part and input.
+///
+/// Answers are stored in a text file with one answer per line, in the form `day part input answer`
+/// (for example `01 silver real 67633`). Newlines and backslashes in answers are escaped as `\n`
+/// and `\\`. Empty lines and lines starting with `#` are ignored.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers {
+    answers: HashMap<(u32, Part, InputKind), String>,
+}
+
+impl Answers {
+    /// The answers for the inputs that are embedded in the binary.
+    pub fn embedded() -> Self {
+        Self::parse(EMBEDDED_ANSWERS).expect("embedded answers should be valid")
+    }
+
+    /// The answers that apply to the inputs of `inputs`. Embedded answers are only kept for the
+    /// inputs that are not replaced by the provider. If the provider reads from a directory that
+    /// contains an [`ANSWERS_FILE_NAME`], its answers are added as well.
+    pub fn for_inputs(inputs: &InputProvider) -> Result<Self, AnswersError> {
+        let mut answers = Self::embedded();
+        answers
+            .answers
+            .retain(|&(day, _, kind), _| !inputs.replaces(day, kind));
+
+        if let InputSource::Directory(dir) = inputs.source() {
+            let path = dir.join(ANSWERS_FILE_NAME);
+            if path.is_file() {
+                answers.extend(Self::load(&path)?);
+            }
+        }
+
+        Ok(answers)
+    }
+
+    /// Loads answers from a file.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, AnswersError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| AnswersError::Read(InputError::new(path, e)))?;
+
+        Self::parse(&text).map_err(|e| match e {
+            AnswersError::Parse { line, message, .. } => AnswersError::Parse {
+                path: Some(path.to_path_buf()),
+                line,
+                message,
+            },
+            e => e,
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<Self, AnswersError> {
+        let mut answers = Self::default();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: String| AnswersError::Parse {
+                path: None,
+                line: index + 1,
+                message,
+            };
+
+            let mut rest = line;
+            let mut next_field = |name: &str| {
+                let field = rest
+                    .split_whitespace()
+                    .next()
+                    .ok_or_else(|| error(format!("expected {name}")))?;
+                rest = rest.trim_start()[field.len()..].trim_start();
+                Ok(field)
+            };
+
+            let day = next_field("a day")?;
+            let day = day
+                .parse()
+                .map_err(|_| error(format!("invalid day `{day}`")))?;
+            let part = next_field("a part")?;
+            let part =
+                Part::from_name(part).ok_or_else(|| error(format!("invalid part `{part}`")))?;
+            let kind = next_field("an input")?;
+            let kind = InputKind::from_name(kind)
+                .ok_or_else(|| error(format!("invalid input `{kind}`")))?;
+
+            if rest.is_empty() {
+                return Err(error("expected an answer".to_string()));
+            }
+            answers.insert(day, part, kind, unescape(rest));
+        }
+
+        Ok(answers)
+    }
+
+    pub fn get(&self, day: u32, part: Part, kind: InputKind) -> Option<&str> {
+        self.answers.get(&(day, part, kind)).map(String::as_str)
+    }
+
+    pub fn insert(&mut self, day: u32, part: Part, kind: InputKind, answer: impl Into<String>) {
+        self.answers.insert((day, part, kind), answer.into());
+    }
+
+    /// Adds all answers of `other`, replacing existing answers for the same day, part and input.
+    pub fn extend(&mut self, other: Answers) {
+        self.answers.extend(other.answers);
+    }
+
+    pub fn len(&self) -> usize {
+        self.answers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.answers.is_empty()
+    }
+}
+
+fn unescape(answer: &str) -> String {
+    let mut unescaped = String::with_capacity(answer.len());
+    let mut chars = answer.chars();
+    while let Some(char) = chars.next() {
+        match (char, chars.clone().next()) {
+            ('\\', Some('n')) => {
+                unescaped.push('\n');
+                chars.next();
+            }
+            ('\\', Some('\\')) => {
+                unescaped.push('\\');
+                chars.next();
+            }
+            (char, _) => unescaped.push(char),
+        }
+    }
+    unescaped
+}
+
+/// An answers file that could not be read or parsed.
+#[derive(Debug)]
+pub enum AnswersError {
+    Read(InputError),
+    Parse {
+        path: Option<PathBuf>,
+        line: usize,
+        message: String,
+    },
+}
+
+impl Display for AnswersError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AnswersError::Read(e) => e.fmt(f),
+            AnswersError::Parse {
+                path: Some(path),
+                line,
+                message,
+            } => write!(f, "{}:{line}: {message}", path.display()),
+            AnswersError::Parse {
+                path: None,
+                line,
+                message,
+            } => write!(f, "answers line {line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for AnswersError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            AnswersError::Read(e) => Some(e),
+            AnswersError::Parse { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse(
+            "# comment\n\n01 silver sample 24000\n1 gold real  a b\\nc\\\\n\n25 silver real 2=-1=0",
+        )
+        .unwrap();
+
+        assert_eq!(answers.len(), 3);
+        assert_eq!(
+            answers.get(1, Part::Silver, InputKind::Sample),
+            Some("24000")
+        );
+        assert_eq!(
+            answers.get(1, Part::Gold, InputKind::Real),
+            Some("a b\nc\\n")
+        );
+        assert_eq!(
+            answers.get(25, Part::Silver, InputKind::Real),
+            Some("2=-1=0")
+        );
+        assert_eq!(answers.get(25, Part::Gold, InputKind::Real), None);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Answers::parse("01 silver sample").is_err());
+        assert!(Answers::parse("01 platinum sample 5").is_err());
+        assert!(Answers::parse("01 silver example 5").is_err());
+        assert!(matches!(
+            Answers::parse("01 silver real 5\nday1 silver real 5"),
+            Err(AnswersError::Parse { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_embedded_answers_are_complete() {
+        let answers = Answers::embedded();
+        for entry in registry::days() {
+            for part in Part::ALL {
+                for kind in InputKind::ALL {
+                    assert_eq!(
+                        entry.part(part).is_some(),
+                        answers.get(entry.day, part, kind).is_some(),
+                        "day {} {part} {kind}",
+                        entry.day
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_replaced_inputs_have_no_embedded_answers() {
+        let answers = Answers::for_inputs(&InputProvider::file("custom.txt")).unwrap();
+        assert!(answers.get(1, Part::Silver, InputKind::Sample).is_some());
+        assert!(answers.get(1, Part::Silver, InputKind::Real).is_none());
+    }
+}
@@ -0,0 +1,502 @@
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use crate::{input::InputError, report::Report};
+
+use super::*;
+
+/// The file that measurements are stored in when no other file is given.
+pub const BASELINES_FILE_NAME: &str = "baselines.txt";
+
+/// The directory that iai writes its cachegrind results to.
+pub const IAI_DIR: &str = "target/iai";
+
+/// A value that is measured for every day, part and input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Metric {
+    /// The median time it took to parse the input, in nanoseconds.
+    Parse,
+    /// The median time it took to solve the part for the parsed input, in nanoseconds.
+    Solve,
+    /// The number of instructions iai counted for parsing and solving.
+    Instructions,
+}
+
+impl Metric {
+    pub const ALL: [Metric; 3] = [Metric::Parse, Metric::Solve, Metric::Instructions];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Metric::Parse => "parse_ns",
+            Metric::Solve => "solve_ns",
+            Metric::Instructions => "instructions",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|metric| metric.name() == name)
+    }
+}
+
+impl Display for Metric {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A single measured value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Measurement {
+    pub day: u32,
+    pub part: Part,
+    pub kind: InputKind,
+    pub metric: Metric,
+    pub value: u64,
+}
+
+impl Measurement {
+    const fn key(&self) -> (u32, Part, InputKind, Metric) {
+        (self.day, self.part, self.kind, self.metric)
+    }
+}
+
+/// Measurements that are stored per git revision, so performance can be compared across commits.
+///
+/// Measurements are stored in a text file with one value per line, in the form
+/// `revision day part input metric value` (for example `bd6ee8d 06 silver real solve_ns 4210`).
+/// Empty lines and lines starting with `#` are ignored. Revisions are kept in the order they were
+/// first recorded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Baselines {
+    revisions: Vec<(String, Vec<Measurement>)>,
+}
+
+impl Baselines {
+    /// Loads measurements from a file. A file that does not exist has no measurements.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BaselineError> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| BaselineError::Read(InputError::new(path, e)))?;
+
+        Self::parse(&text).map_err(|e| match e {
+            BaselineError::Parse { line, message, .. } => BaselineError::Parse {
+                path: Some(path.to_path_buf()),
+                line,
+                message,
+            },
+            e => e,
+        })
+    }
+
+    pub fn parse(text: &str) -> Result<Self, BaselineError> {
+        let mut baselines = Self::default();
+
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let error = |message: String| BaselineError::Parse {
+                path: None,
+                line: index + 1,
+                message,
+            };
+
+            let fields: Vec<_> = line.split_whitespace().collect();
+            let [revision, day, part, kind, metric, value] = fields[..] else {
+                return Err(error(format!("expected 6 fields, found {}", fields.len())));
+            };
+
+            let measurement = Measurement {
+                day: day
+                    .parse()
+                    .map_err(|_| error(format!("invalid day `{day}`")))?,
+                part: Part::from_name(part)
+                    .ok_or_else(|| error(format!("invalid part `{part}`")))?,
+                kind: InputKind::from_name(kind)
+                    .ok_or_else(|| error(format!("invalid input `{kind}`")))?,
+                metric: Metric::from_name(metric)
+                    .ok_or_else(|| error(format!("invalid metric `{metric}`")))?,
+                value: value
+                    .parse()
+                    .map_err(|_| error(format!("invalid value `{value}`")))?,
+            };
+            baselines.record(revision, [measurement]);
+        }
+
+        Ok(baselines)
+    }
+
+    /// Writes the measurements to a file in the format that [`Baselines::load`] reads.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), BaselineError> {
+        let path = path.as_ref();
+        std::fs::write(path, self.render()).map_err(|e| BaselineError::Write(path.to_path_buf(), e))
+    }
+
+    pub fn render(&self) -> String {
+        let mut text = String::from(
+            "# Performance measurements per git revision, see `aoc2022 help`.\n\
+             # revision day part input metric value\n",
+        );
+        for (revision, measurements) in &self.revisions {
+            for m in measurements {
+                writeln!(
+                    text,
+                    "{revision} {:02} {} {} {} {}",
+                    m.day, m.part, m.kind, m.metric, m.value
+                )
+                .unwrap();
+            }
+        }
+        text
+    }
+
+    /// Stores measurements for `revision`, replacing earlier values for the same day, part, input
+    /// and metric of that revision.
+    pub fn record(&mut self, revision: &str, measurements: impl IntoIterator<Item = Measurement>) {
+        let index = match self.revisions.iter().position(|(r, _)| r == revision) {
+            Some(index) => index,
+            None => {
+                self.revisions.push((revision.to_string(), vec![]));
+                self.revisions.len() - 1
+            }
+        };
+
+        let stored = &mut self.revisions[index].1;
+        for measurement in measurements {
+            match stored.iter_mut().find(|m| m.key() == measurement.key()) {
+                Some(existing) => *existing = measurement,
+                None => stored.push(measurement),
+            }
+        }
+    }
+
+    /// The revisions that have measurements, in the order they were first recorded.
+    pub fn revisions(&self) -> impl Iterator<Item = &str> {
+        self.revisions.iter().map(|(revision, _)| revision.as_str())
+    }
+
+    pub fn get(&self, revision: &str) -> Option<&[Measurement]> {
+        self.revisions
+            .iter()
+            .find(|(r, _)| r == revision)
+            .map(|(_, measurements)| measurements.as_slice())
+    }
+
+    /// The most recently recorded revision other than `revision`, which is what a new measurement
+    /// is compared against by default.
+    pub fn latest_except(&self, revision: &str) -> Option<&str> {
+        self.revisions().filter(|&r| r != revision).last()
+    }
+}
+
+/// How a measurement changed between two revisions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Change {
+    pub baseline: Measurement,
+    pub current: u64,
+}
+
+impl Change {
+    /// The change relative to the baseline, in percent.
+    pub fn percent(&self) -> f64 {
+        if self.baseline.value == 0 {
+            return if self.current == 0 { 0. } else { f64::INFINITY };
+        }
+
+        let baseline = self.baseline.value as f64;
+        (self.current as f64 - baseline) / baseline * 100.
+    }
+
+    /// Whether the value increased by more than `threshold` percent.
+    pub fn is_regression(&self, threshold: u32) -> bool {
+        self.percent() > f64::from(threshold)
+    }
+
+    /// Whether the value decreased by more than `threshold` percent.
+    pub fn is_improvement(&self, threshold: u32) -> bool {
+        self.percent() < -f64::from(threshold)
+    }
+}
+
+/// Compares the measurements that exist in both `baseline` and `current`, in the order of
+/// `current`.
+pub fn compare(baseline: &[Measurement], current: &[Measurement]) -> Vec<Change> {
+    current
+        .iter()
+        .filter_map(|current| {
+            let baseline = baseline.iter().find(|m| m.key() == current.key())?;
+            Some(Change {
+                baseline: *baseline,
+                current: current.value,
+            })
+        })
+        .collect()
+}
+
+/// Renders the changes as a table, marking values that changed by more than `threshold` percent.
+pub fn render_table(changes: &[Change], threshold: u32) -> String {
+    let mut table = format!(
+        "{:<4} {:<7} {:<7} {:<13} {:>14} {:>14} {:>9}\n",
+        "Day", "Part", "Input", "Metric", "Baseline", "Current", "Change"
+    );
+
+    for change in changes {
+        let Measurement {
+            day,
+            part,
+            kind,
+            metric,
+            value,
+        } = change.baseline;
+        let verdict = if change.is_regression(threshold) {
+            "  REGRESSION"
+        } else if change.is_improvement(threshold) {
+            "  improved"
+        } else {
+            ""
+        };
+
+        writeln!(
+            table,
+            "{day:<4} {:<7} {:<7} {:<13} {value:>14} {:>14} {:>+8.1}%{verdict}",
+            part.name(),
+            kind.name(),
+            metric.name(),
+            change.current,
+            change.percent()
+        )
+        .unwrap();
+    }
+
+    table
+}
+
+/// Collects the median parse and solve times of every part in `report` that was timed.
+pub fn timings(report: &Report) -> Vec<Measurement> {
+    let mut measurements = vec![];
+    for record in &report.records {
+        let Some(timing) = record.timing else {
+            continue;
+        };
+
+        for (metric, stats) in [(Metric::Parse, timing.parse), (Metric::Solve, timing.solve)] {
+            measurements.push(Measurement {
+                day: record.day,
+                part: record.part,
+                kind: record.kind,
+                metric,
+                value: stats.median.as_nanos() as u64,
+            });
+        }
+    }
+    measurements
+}
+
+/// Reads the instruction count of a part from the results of the iai benchmark in `dir`, minus
+/// the overhead iai measured for an empty benchmark. Returns `None` if the part was not
+/// benchmarked.
+pub fn iai_instructions(dir: &Path, day: u32, part: Part, kind: InputKind) -> Option<u64> {
+    let read = |name: &str| {
+        let text = std::fs::read_to_string(dir.join(format!("cachegrind.out.{name}"))).ok()?;
+        parse_cachegrind_instructions(&text)
+    };
+
+    let instructions = read(&format!("day_{day:02}_{part}_{kind}"))?;
+    let calibration = read("iai_calibration").unwrap_or(0);
+    Some(instructions.saturating_sub(calibration))
+}
+
+/// Gets the total instruction count (`Ir`) from the output of cachegrind.
+fn parse_cachegrind_instructions(text: &str) -> Option<u64> {
+    let events = text.lines().find_map(|line| line.strip_prefix("events:"))?;
+    let summary = text
+        .lines()
+        .find_map(|line| line.strip_prefix("summary:"))?;
+
+    let index = events.split_whitespace().position(|event| event == "Ir")?;
+    summary.split_whitespace().nth(index)?.parse().ok()
+}
+
+/// The git revision of the working directory, with a `-dirty` suffix if there are uncommitted
+/// changes.
+pub fn current_revision() -> Result<String, BaselineError> {
+    let output = Command::new("git")
+        .args(["describe", "--always", "--dirty"])
+        .output()
+        .map_err(|e| BaselineError::Revision(format!("could not run git: {e}")))?;
+
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(BaselineError::Revision(stderr.trim().to_string()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// A baselines file that could not be read, parsed or written, or a revision that could not be
+/// determined.
+#[derive(Debug)]
+pub enum BaselineError {
+    Read(InputError),
+    Write(PathBuf, std::io::Error),
+    Parse {
+        path: Option<PathBuf>,
+        line: usize,
+        message: String,
+    },
+    Revision(String),
+}
+
+impl Display for BaselineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BaselineError::Read(e) => e.fmt(f),
+            BaselineError::Write(path, e) => write!(f, "could not write {}: {e}", path.display()),
+            BaselineError::Parse {
+                path: Some(path),
+                line,
+                message,
+            } => write!(f, "{}:{line}: {message}", path.display()),
+            BaselineError::Parse {
+                path: None,
+                line,
+                message,
+            } => write!(f, "baselines line {line}: {message}"),
+            BaselineError::Revision(message) => {
+                write!(f, "could not determine the git revision: {message}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for BaselineError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            BaselineError::Read(e) => Some(e),
+            BaselineError::Write(_, e) => Some(e),
+            BaselineError::Parse { .. } | BaselineError::Revision(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn measurement(day: u32, metric: Metric, value: u64) -> Measurement {
+        Measurement {
+            day,
+            part: Part::Silver,
+            kind: InputKind::Real,
+            metric,
+            value,
+        }
+    }
+
+    #[test]
+    fn test_parse_and_render() {
+        let text =
+            "# comment\n\nabc 06 silver real solve_ns 4210\nabc 6 silver real instructions 90\n\
+                    def 06 gold sample parse_ns 12\n";
+        let baselines = Baselines::parse(text).unwrap();
+
+        assert_eq!(baselines.revisions().collect::<Vec<_>>(), ["abc", "def"]);
+        assert_eq!(
+            baselines.get("abc").unwrap(),
+            [
+                measurement(6, Metric::Solve, 4210),
+                measurement(6, Metric::Instructions, 90)
+            ]
+        );
+        assert_eq!(baselines.get("xyz"), None);
+        assert_eq!(Baselines::parse(&baselines.render()).unwrap(), baselines);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(Baselines::parse("abc 06 silver real solve_ns").is_err());
+        assert!(Baselines::parse("abc 06 silver real wall_ns 5").is_err());
+        assert!(Baselines::parse("abc 06 silver real solve_ns -5").is_err());
+        assert!(matches!(
+            Baselines::parse("abc 06 silver real solve_ns 5\nabc 06 silver real solve_ns 5 6"),
+            Err(BaselineError::Parse { line: 2, .. })
+        ));
+    }
+
+    #[test]
+    fn test_record() {
+        let mut baselines = Baselines::default();
+        baselines.record("abc", [measurement(1, Metric::Solve, 10)]);
+        baselines.record("def", [measurement(1, Metric::Solve, 20)]);
+        baselines.record(
+            "abc",
+            [
+                measurement(1, Metric::Solve, 15),
+                measurement(1, Metric::Parse, 5),
+            ],
+        );
+
+        assert_eq!(
+            baselines.get("abc").unwrap(),
+            [
+                measurement(1, Metric::Solve, 15),
+                measurement(1, Metric::Parse, 5)
+            ]
+        );
+        assert_eq!(baselines.latest_except("abc"), Some("def"));
+        assert_eq!(baselines.latest_except("def"), Some("abc"));
+        assert_eq!(baselines.latest_except("xyz"), Some("def"));
+    }
+
+    #[test]
+    fn test_compare() {
+        let baseline = [
+            measurement(1, Metric::Solve, 100),
+            measurement(2, Metric::Solve, 100),
+            measurement(3, Metric::Solve, 100),
+        ];
+        let current = [
+            measurement(3, Metric::Solve, 80),
+            measurement(1, Metric::Solve, 125),
+            measurement(2, Metric::Solve, 105),
+            measurement(4, Metric::Solve, 100),
+        ];
+
+        let changes = compare(&baseline, &current);
+        assert_eq!(changes.len(), 3);
+        assert_eq!(changes[0].percent(), -20.);
+        assert!(changes[0].is_improvement(10));
+        assert!(changes[1].is_regression(10));
+        assert!(!changes[2].is_regression(10));
+        assert!(changes[2].is_regression(4));
+
+        assert_eq!(
+            render_table(&changes, 10),
+            "Day  Part    Input   Metric              Baseline        Current    Change\n\
+             3    silver  real    solve_ns                 100             80    -20.0%  improved\n\
+             1    silver  real    solve_ns                 100            125    +25.0%  REGRESSION\n\
+             2    silver  real    solve_ns                 100            105     +5.0%\n"
+        );
+    }
+
+    #[test]
+    fn test_cachegrind_instructions() {
+        let text = "desc: I1 cache: 32768 B, 64 B, 8-way associative\ncmd: bench --iai-run 3\n\
+                    events: Ir I1mr ILmr Dr\nfl=???\n0 1 2 3 4\nsummary: 1234 5 6 7\n";
+        assert_eq!(parse_cachegrind_instructions(text), Some(1234));
+        assert_eq!(
+            parse_cachegrind_instructions("events: I1mr Ir\nsummary: 5 6\n"),
+            Some(6)
+        );
+        assert_eq!(parse_cachegrind_instructions("summary: 5\n"), None);
+    }
+}
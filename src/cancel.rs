@@ -0,0 +1,122 @@
+//! Cooperative cancellation of parts that run longer than their time budget.
+//!
+//! The runner gives every job that solves a part with a budget a [`Token`], and cancels it when
+//! the budget runs out. Solutions that can search for a long time call [`checkpoint`] regularly,
+//! which stops them soon after their token is cancelled. Solutions without checkpoints keep running
+//! in the background until they finish, but the runner does not wait for them.
+
+use std::{
+    cell::RefCell,
+    panic,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+/// A flag that is shared by the runner and the threads that solve a single part.
+#[derive(Debug, Clone, Default)]
+pub struct Token(Arc<AtomicBool>);
+
+impl Token {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+thread_local! {
+    static CURRENT: RefCell<Option<Token>> = const { RefCell::new(None) };
+}
+
+/// Sets the token that [`checkpoint`] checks on the current thread.
+pub fn install(token: Option<Token>) {
+    CURRENT.with(|current| *current.borrow_mut() = token);
+}
+
+/// The token of the current thread. Parts that split their work over other threads pass it on
+/// with [`with_token`].
+pub fn current() -> Option<Token> {
+    CURRENT.with(|current| current.borrow().clone())
+}
+
+/// Runs `fun` with `token` installed on the current thread, and puts back the previous token
+/// afterwards, also when `fun` unwinds.
+pub fn with_token<T>(token: Option<Token>, fun: impl FnOnce() -> T) -> T {
+    struct Restore(Option<Token>);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            install(self.0.take());
+        }
+    }
+
+    let _restore = Restore(CURRENT.with(|current| current.replace(token)));
+    fun()
+}
+
+/// The payload of the unwind that [`checkpoint`] starts, which [`catch_panic`] turns into
+/// [`SolveError::Cancelled`].
+///
+/// [`catch_panic`]: crate::error::catch_panic
+/// [`SolveError::Cancelled`]: crate::SolveError::Cancelled
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+/// Stops the current part by unwinding if the token of this thread was cancelled. Does nothing
+/// on threads without a token, such as in tests and benchmarks.
+pub fn checkpoint() {
+    let cancelled =
+        CURRENT.with(|current| matches!(&*current.borrow(), Some(token) if token.is_cancelled()));
+    if cancelled {
+        // unlike `panic!`, this does not print a message
+        panic::resume_unwind(Box::new(Cancelled));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint() {
+        let token = Token::new();
+        std::thread::spawn(move || {
+            checkpoint();
+            install(Some(token.clone()));
+            checkpoint();
+
+            token.cancel();
+            let payload = panic::catch_unwind(checkpoint).unwrap_err();
+            assert_eq!(payload.downcast_ref::<Cancelled>(), Some(&Cancelled));
+
+            install(None);
+            checkpoint();
+        })
+        .join()
+        .unwrap();
+    }
+
+    #[test]
+    fn test_with_token() {
+        let token = Token::new();
+        token.cancel();
+        std::thread::spawn(move || {
+            let payload = panic::catch_unwind(|| with_token(Some(token), checkpoint)).unwrap_err();
+            assert_eq!(payload.downcast_ref::<Cancelled>(), Some(&Cancelled));
+
+            // the token is removed again, even though `checkpoint` unwound
+            assert!(current().is_none());
+            checkpoint();
+        })
+        .join()
+        .unwrap();
+    }
+}
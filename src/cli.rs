@@ -0,0 +1,693 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use crate::{
+    answers::{Answers, AnswersError},
+    baseline::{self, BaselineError, Baselines},
+    report::{Format, Report},
+    runner::{Execution, Selection},
+    timing::Repetitions,
+};
+
+use super::*;
+
+const USAGE: &str = "\
+Usage: aoc2022 [COMMAND] [OPTIONS] [DAYS...]
+
+Commands:
+  run      Run the selected days and print their answers (default)
+  bench    Like `run`, but with warm-up runs and 100 measured runs by default
+  verify   Check that the answers of the selected days are known to be correct
+  track    Like `bench`, but store the timings for the current git revision and compare them with
+           an earlier revision. Instruction counts of the iai benchmark are included if they
+           exist in `target/iai`, so run `just cachegrind` first to track those as well.
+  list     List the available days
+  generate Print a random input for a single day, see `--size` and `--seed`
+
+Days can be given as single numbers (`1`, `01`) or as ranges (`1-5`). If no days are given, all
+available days are selected.
+
+Answers are compared with the expected answers in `answers.txt`, which are only used for embedded
+inputs, and with `answers.txt` in the input directory if it exists.
+
+Options:
+  -p, --part <silver|gold|all>   The parts to run [default: all]
+  -i, --input <sample|real|all>  The inputs to run against [default: all, or real if --file is used]
+  -f, --file <PATH>              Use PATH as the real input, or read it from stdin if PATH is `-`
+  -d, --input-dir <DIR>          Look for inputs in DIR instead of `inputs`
+  -n, --iterations <N>           The number of measured runs [default: 1, or 100 for `bench`]
+  -w, --warmup <N>               The number of runs before measuring [default: 0, or 10 for `bench`]
+  -a, --answers <PATH>           Also load expected answers from PATH
+  -e, --expect <ANSWER>          The expected answer for `verify`, instead of the answers files
+  -F, --format <pretty|json|csv> The output format of `run`, `bench` and `verify` [default: pretty]
+  -j, --parallel                 Run all selected days and parts at the same time. Results are
+                                 still printed in order, followed by the total and CPU time
+  -T, --timeout <SECONDS>        Report parts that take longer than this as a timeout instead of
+                                 waiting for them, counting all runs of a part together
+  -b, --baselines <PATH>         The file that `track` stores measurements in [default: baselines.txt]
+      --against <REVISION>       The revision `track` compares with [default: the latest other one]
+  -t, --threshold <PERCENT>      The increase that `track` reports as a regression [default: 10]
+  -s, --size <N>                 The size of the input that `generate` prints, such as the number
+                                 of lines or the width of a map depending on the day [default: 100]
+      --seed <N>                 The seed of the input that `generate` prints [default: 0]
+  -h, --help                     Print this help text";
+
+/// A subcommand of the command line interface.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    Run,
+    Bench,
+    /// Like [`Command::Run`], but answers that are not known to be correct are a failure.
+    Verify {
+        expected: Option<String>,
+    },
+    /// Like [`Command::Bench`], but the timings are stored and compared with an earlier revision.
+    Track {
+        /// The file that measurements are stored in.
+        baselines: PathBuf,
+        /// The revision to compare with, instead of the latest other revision in the file.
+        against: Option<String>,
+        /// The increase in percent that is reported as a regression.
+        threshold: u32,
+    },
+    List,
+    /// Prints a random input of a single day, see [`DayEntry::generate`](registry::DayEntry::generate).
+    Generate {
+        size: usize,
+        seed: u64,
+    },
+    Help,
+}
+
+/// Where the command line interface should load inputs from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InputArg {
+    /// Use [`InputProvider::from_env`].
+    Default,
+    Directory(PathBuf),
+    File(PathBuf),
+    Stdin,
+}
+
+impl InputArg {
+    pub fn to_provider(&self) -> Result<InputProvider, input::InputError> {
+        match self {
+            InputArg::Default => Ok(InputProvider::from_env()),
+            InputArg::Directory(dir) => Ok(InputProvider::directory(dir)),
+            InputArg::File(path) => Ok(InputProvider::file(path)),
+            InputArg::Stdin => InputProvider::stdin(),
+        }
+    }
+}
+
+/// Parsed command line arguments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Args {
+    pub command: Command,
+    pub selection: Selection,
+    pub inputs: InputArg,
+    /// An extra answers file.
+    pub answers: Option<PathBuf>,
+    pub repetitions: Repetitions,
+    pub format: Format,
+    pub execution: Execution,
+    /// The time each part may take, see [`runner::run`].
+    pub budget: Option<Duration>,
+}
+
+impl Args {
+    /// Parses the command line arguments, excluding the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, CliError> {
+        let mut args = args.into_iter().peekable();
+
+        let subcommand = match args.peek().map(String::as_str) {
+            Some("run") => Some(Command::Run),
+            Some("bench") => Some(Command::Bench),
+            Some("verify") => Some(Command::Verify { expected: None }),
+            Some("track") => Some(Command::Track {
+                baselines: baseline::BASELINES_FILE_NAME.into(),
+                against: None,
+                threshold: 10,
+            }),
+            Some("list") => Some(Command::List),
+            Some("generate") => Some(Command::Generate { size: 100, seed: 0 }),
+            Some("help") => Some(Command::Help),
+            _ => None,
+        };
+        let mut command = match subcommand {
+            Some(command) => {
+                args.next();
+                command
+            }
+            None => Command::Run,
+        };
+
+        let mut days = vec![];
+        let mut parts = None;
+        let mut kinds = None;
+        let mut inputs = InputArg::Default;
+        let mut iterations = None;
+        let mut warmup = None;
+        let mut expected = None;
+        let mut answers = None;
+        let mut format = Format::default();
+        let mut execution = Execution::default();
+        let mut baselines = None;
+        let mut against = None;
+        let mut threshold = None;
+        let mut budget = None;
+        let mut size = None;
+        let mut seed = None;
+
+        while let Some(arg) = args.next() {
+            let (name, inline_value) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with('-') => (name.to_string(), Some(value)),
+                _ => (arg.clone(), None),
+            };
+            let mut value = || match inline_value {
+                Some(value) => Ok(value.to_string()),
+                None => args
+                    .next()
+                    .ok_or_else(|| CliError(format!("missing value for `{name}`"))),
+            };
+
+            match name.as_str() {
+                "-h" | "--help" => command = Command::Help,
+                "-j" | "--parallel" => execution = Execution::Parallel,
+                "-T" | "--timeout" => budget = Some(parse_seconds(&value()?)?),
+                "-p" | "--part" => parts = Some(parse_parts(&value()?)?),
+                "-i" | "--input" => kinds = Some(parse_kinds(&value()?)?),
+                "-f" | "--file" => {
+                    inputs = match value()?.as_str() {
+                        "-" => InputArg::Stdin,
+                        path => InputArg::File(path.into()),
+                    }
+                }
+                "-d" | "--input-dir" => inputs = InputArg::Directory(value()?.into()),
+                "-n" | "--iterations" => iterations = Some(parse_count(&value()?, "iterations")?),
+                "-w" | "--warmup" => warmup = Some(parse_count(&value()?, "warm-up runs")?),
+                "-a" | "--answers" => answers = Some(value()?.into()),
+                "-e" | "--expect" => expected = Some(value()?),
+                "-F" | "--format" => {
+                    let value = value()?;
+                    format = Format::from_name(&value)
+                        .ok_or_else(|| CliError(format!("unknown format: {value}")))?;
+                }
+                "-b" | "--baselines" => baselines = Some(value()?.into()),
+                "--against" => against = Some(value()?),
+                "-t" | "--threshold" => threshold = Some(parse_count(&value()?, "percent")?),
+                "-s" | "--size" => size = Some(parse_count(&value()?, "size")?),
+                "--seed" => seed = Some(parse_count(&value()?, "seed")?),
+                _ if name.starts_with('-') && name.len() > 1 => {
+                    return Err(CliError(format!("unknown option `{name}`")));
+                }
+                _ => days.extend(parse_days(&arg)?),
+            }
+        }
+
+        match &mut command {
+            Command::Verify { expected: e } => *e = expected,
+            Command::Track {
+                baselines: b,
+                against: a,
+                threshold: t,
+            } => {
+                *b = baselines.unwrap_or_else(|| b.clone());
+                *a = against;
+                *t = threshold.unwrap_or(*t);
+            }
+            Command::Generate { size: s, seed: n } => {
+                if days.len() != 1 {
+                    return Err(CliError("`generate` needs exactly one day".to_string()));
+                }
+                *s = size.unwrap_or(*s);
+                *n = seed.unwrap_or(*n);
+            }
+            _ => (),
+        }
+
+        let default_repetitions = match command {
+            Command::Bench | Command::Track { .. } => Repetitions::new(10, 100),
+            _ => Repetitions::ONCE,
+        };
+        let repetitions = Repetitions::new(
+            warmup.unwrap_or(default_repetitions.warmup),
+            iterations.unwrap_or(default_repetitions.runs),
+        );
+
+        let kinds = kinds.unwrap_or_else(|| match inputs {
+            InputArg::File(_) | InputArg::Stdin => vec![InputKind::Real],
+            InputArg::Default | InputArg::Directory(_) => InputKind::ALL.to_vec(),
+        });
+
+        Ok(Self {
+            command,
+            selection: Selection {
+                days: if days.is_empty() {
+                    Selection::all().days
+                } else {
+                    days
+                },
+                parts: parts.unwrap_or_else(|| Part::ALL.to_vec()),
+                kinds,
+            },
+            inputs,
+            answers,
+            repetitions,
+            format,
+            execution,
+            budget,
+        })
+    }
+}
+
+fn parse_count<T: FromStr>(value: &str, name: &str) -> Result<T, CliError> {
+    value
+        .parse()
+        .map_err(|_| CliError(format!("invalid number of {name}: {value}")))
+}
+
+fn parse_seconds(value: &str) -> Result<Duration, CliError> {
+    value
+        .parse()
+        .ok()
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+        .ok_or_else(|| CliError(format!("invalid number of seconds: {value}")))
+}
+
+fn parse_parts(value: &str) -> Result<Vec<Part>, CliError> {
+    match value {
+        "silver" | "1" => Ok(vec![Part::Silver]),
+        "gold" | "2" => Ok(vec![Part::Gold]),
+        "all" | "both" => Ok(Part::ALL.to_vec()),
+        _ => Err(CliError(format!("unknown part: {value}"))),
+    }
+}
+
+fn parse_kinds(value: &str) -> Result<Vec<InputKind>, CliError> {
+    match value {
+        "sample" => Ok(vec![InputKind::Sample]),
+        "real" => Ok(vec![InputKind::Real]),
+        "all" | "both" => Ok(InputKind::ALL.to_vec()),
+        _ => Err(CliError(format!("unknown input: {value}"))),
+    }
+}
+
+fn parse_days(value: &str) -> Result<Vec<u32>, CliError> {
+    let parse_day = |day: &str| -> Result<u32, CliError> {
+        let day = day.strip_prefix("day").unwrap_or(day);
+        day.parse()
+            .map_err(|_| CliError(format!("invalid day: {value}")))
+    };
+
+    let (start, end) = match value.split_once('-') {
+        Some((start, end)) => (parse_day(start)?, parse_day(end)?),
+        None => (parse_day(value)?, parse_day(value)?),
+    };
+
+    let days: Vec<_> = registry::days()
+        .iter()
+        .map(|entry| entry.day)
+        .filter(|day| (start..=end).contains(day))
+        .collect();
+
+    if days.is_empty() {
+        Err(CliError(format!("day {value} is not available")))
+    } else {
+        Ok(days)
+    }
+}
+
+/// An invalid command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CliError(String);
+
+impl Display for CliError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for CliError {}
+
+/// Runs the command line interface and returns the exit code.
+pub fn main(args: impl IntoIterator<Item = String>) -> i32 {
+    let args = match Args::parse(args) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return 2;
+        }
+    };
+
+    let inputs = match args.inputs.to_provider() {
+        Ok(inputs) => inputs,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return 1;
+        }
+    };
+
+    let answers = match load_answers(&args, &inputs) {
+        Ok(answers) => answers,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return 1;
+        }
+    };
+
+    let selection = &args.selection;
+    let run = || {
+        runner::run(
+            selection,
+            &inputs,
+            &answers,
+            args.repetitions,
+            args.format,
+            args.execution,
+            args.budget,
+        )
+    };
+    let success = match &args.command {
+        Command::Run | Command::Bench => run().is_success(),
+        Command::Verify { .. } => run().is_verified(),
+        Command::Track {
+            baselines,
+            against,
+            threshold,
+        } => {
+            let report = run();
+            match track(
+                &report,
+                selection,
+                baselines,
+                against.as_deref(),
+                *threshold,
+            ) {
+                Ok(no_regressions) => report.is_success() && no_regressions,
+                Err(e) => {
+                    eprintln!("error: {e}");
+                    false
+                }
+            }
+        }
+        Command::List => {
+            for entry in selection.days.iter().filter_map(|&day| registry::get(day)) {
+                let parts = if entry.has_gold() {
+                    "silver, gold"
+                } else {
+                    "silver"
+                };
+                println!("Day {}: {parts}", entry.day);
+            }
+            true
+        }
+        Command::Generate { size, seed } => {
+            let entry = selection.days.first().and_then(|&day| registry::get(day));
+            match entry.and_then(|entry| entry.generate(*size, *seed)) {
+                Some(input) => {
+                    println!("{input}");
+                    true
+                }
+                None => {
+                    eprintln!("error: the day has no input generator");
+                    false
+                }
+            }
+        }
+        Command::Help => {
+            println!("{USAGE}");
+            true
+        }
+    };
+
+    i32::from(!success)
+}
+
+/// Stores the timings of `report` and the iai instruction counts of `selection` in the baselines
+/// file for the current revision, and prints how they changed compared to `against` or the latest
+/// other revision in the file. Returns whether there were no regressions.
+fn track(
+    report: &Report,
+    selection: &Selection,
+    path: &Path,
+    against: Option<&str>,
+    threshold: u32,
+) -> Result<bool, BaselineError> {
+    let revision = baseline::current_revision()?;
+    let mut baselines = Baselines::load(path)?;
+
+    let mut measurements = baseline::timings(report);
+    for &day in &selection.days {
+        for &part in &selection.parts {
+            for &kind in &selection.kinds {
+                let iai_dir = Path::new(baseline::IAI_DIR);
+                if let Some(value) = baseline::iai_instructions(iai_dir, day, part, kind) {
+                    measurements.push(baseline::Measurement {
+                        day,
+                        part,
+                        kind,
+                        metric: baseline::Metric::Instructions,
+                        value,
+                    });
+                }
+            }
+        }
+    }
+
+    baselines.record(&revision, measurements.iter().copied());
+    baselines.save(path)?;
+    println!(
+        "Stored {} measurements for {revision} in {}",
+        measurements.len(),
+        path.display()
+    );
+
+    let Some(against) = against.or_else(|| baselines.latest_except(&revision)) else {
+        println!("There is no other revision to compare with yet");
+        return Ok(true);
+    };
+    let Some(stored) = baselines.get(against) else {
+        eprintln!("error: there are no measurements for {against}");
+        return Ok(false);
+    };
+
+    let changes = baseline::compare(stored, &measurements);
+    println!("\nCompared with {against} (threshold {threshold}%):");
+    print!("{}", baseline::render_table(&changes, threshold));
+
+    let regressions = changes
+        .iter()
+        .filter(|change| change.is_regression(threshold))
+        .count();
+    if regressions > 0 {
+        println!("{regressions} regressions");
+    }
+    Ok(regressions == 0)
+}
+
+/// Loads the answers that apply to `inputs`, together with the answers given on the command line.
+fn load_answers(args: &Args, inputs: &InputProvider) -> Result<Answers, AnswersError> {
+    let mut answers = Answers::for_inputs(inputs)?;
+
+    if let Some(path) = &args.answers {
+        answers.extend(Answers::load(path)?);
+    }
+
+    if let Command::Verify {
+        expected: Some(expected),
+    } = &args.command
+    {
+        for &day in &args.selection.days {
+            for &part in &args.selection.parts {
+                for &kind in &args.selection.kinds {
+                    answers.insert(day, part, kind, expected);
+                }
+            }
+        }
+    }
+
+    Ok(answers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<Args, CliError> {
+        Args::parse(args.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn test_parse_defaults() {
+        let args = parse("").unwrap();
+        assert_eq!(args.command, Command::Run);
+        assert_eq!(args.selection, Selection::all());
+        assert_eq!(args.inputs, InputArg::Default);
+        assert_eq!(args.answers, None);
+        assert_eq!(args.execution, Execution::Sequential);
+    }
+
+    #[test]
+    fn test_parse_parallel() {
+        assert_eq!(parse("-j").unwrap().execution, Execution::Parallel);
+        assert_eq!(
+            parse("bench --parallel").unwrap().execution,
+            Execution::Parallel
+        );
+    }
+
+    #[test]
+    fn test_parse_subcommands() {
+        assert_eq!(parse("run").unwrap().command, Command::Run);
+        assert_eq!(parse("list").unwrap().command, Command::List);
+        assert_eq!(parse("bench").unwrap().command, Command::Bench);
+        assert_eq!(
+            parse("verify --expect=123").unwrap().command,
+            Command::Verify {
+                expected: Some("123".to_string())
+            }
+        );
+        assert_eq!(
+            parse("verify").unwrap().command,
+            Command::Verify { expected: None }
+        );
+    }
+
+    #[test]
+    fn test_parse_selection() {
+        let Some(day) = registry::days().first().map(|entry| entry.day) else {
+            return;
+        };
+
+        let args = parse(&format!("run {day} --part gold --input sample")).unwrap();
+        assert_eq!(args.selection.days, vec![day]);
+        assert_eq!(args.selection.parts, vec![Part::Gold]);
+        assert_eq!(args.selection.kinds, vec![InputKind::Sample]);
+
+        let args = parse(&format!("{day:02} -p silver")).unwrap();
+        assert_eq!(args.command, Command::Run);
+        assert_eq!(args.selection.days, vec![day]);
+        assert_eq!(args.selection.parts, vec![Part::Silver]);
+    }
+
+    #[test]
+    fn test_parse_day_range() {
+        if !registry::days().is_empty() {
+            let args = parse("1-25").unwrap();
+            assert_eq!(args.selection, Selection::all());
+        }
+        assert!(parse("26").is_err());
+        assert!(parse("abc").is_err());
+    }
+
+    #[test]
+    fn test_parse_file() {
+        let args = parse("--file my_input.txt").unwrap();
+        assert_eq!(args.inputs, InputArg::File("my_input.txt".into()));
+        assert_eq!(args.selection.kinds, vec![InputKind::Real]);
+
+        let args = parse("-f - -i all").unwrap();
+        assert_eq!(args.inputs, InputArg::Stdin);
+        assert_eq!(args.selection.kinds, InputKind::ALL.to_vec());
+    }
+
+    #[test]
+    fn test_parse_repetitions() {
+        assert_eq!(parse("").unwrap().repetitions, Repetitions::ONCE);
+        assert_eq!(
+            parse("bench").unwrap().repetitions,
+            Repetitions::new(10, 100)
+        );
+        assert_eq!(
+            parse("bench -n 5").unwrap().repetitions,
+            Repetitions::new(10, 5)
+        );
+        assert_eq!(
+            parse("run -w 3 --iterations=20").unwrap().repetitions,
+            Repetitions::new(3, 20)
+        );
+        assert!(parse("-n many").is_err());
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(parse("").unwrap().format, Format::Pretty);
+        assert_eq!(parse("--format json").unwrap().format, Format::Json);
+        assert_eq!(parse("bench -F=csv").unwrap().format, Format::Csv);
+        assert!(parse("--format xml").is_err());
+    }
+
+    #[test]
+    fn test_parse_track() {
+        let args = parse("track").unwrap();
+        assert_eq!(
+            args.command,
+            Command::Track {
+                baselines: "baselines.txt".into(),
+                against: None,
+                threshold: 10
+            }
+        );
+        assert_eq!(args.repetitions, Repetitions::new(10, 100));
+
+        assert_eq!(
+            parse("track -b perf.txt --against=abc123 -t 5")
+                .unwrap()
+                .command,
+            Command::Track {
+                baselines: "perf.txt".into(),
+                against: Some("abc123".to_string()),
+                threshold: 5
+            }
+        );
+        assert!(parse("track --threshold high").is_err());
+    }
+
+    #[test]
+    fn test_parse_generate() {
+        let Some(day) = registry::days().first().map(|entry| entry.day) else {
+            return;
+        };
+
+        let args = parse(&format!("generate {day}")).unwrap();
+        assert_eq!(args.command, Command::Generate { size: 100, seed: 0 });
+        assert_eq!(args.selection.days, vec![day]);
+
+        assert_eq!(
+            parse(&format!("generate {day} -s 5 --seed=42"))
+                .unwrap()
+                .command,
+            Command::Generate { size: 5, seed: 42 }
+        );
+        assert!(parse("generate").is_err());
+        assert!(parse("generate 1-25").is_err());
+        assert!(parse(&format!("generate {day} --size big")).is_err());
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse("").unwrap().budget, None);
+        assert_eq!(
+            parse("--timeout 5").unwrap().budget,
+            Some(Duration::from_secs(5))
+        );
+        assert_eq!(
+            parse("bench -T=0.25").unwrap().budget,
+            Some(Duration::from_millis(250))
+        );
+        assert!(parse("--timeout -1").is_err());
+        assert!(parse("--timeout soon").is_err());
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse("--part platinum").is_err());
+        assert!(parse("--unknown").is_err());
+        assert!(parse("--input").is_err());
+    }
+}
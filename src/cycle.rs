@@ -0,0 +1,239 @@
+use std::hash::Hash;
+
+use ahash::AHashMap;
+
+/// Where a sequence of states starts repeating.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// The index of the first state that is part of the cycle.
+    pub start: usize,
+    /// The number of states in the cycle.
+    pub period: usize,
+}
+
+impl Cycle {
+    /// The index of the state before the cycle repeated for the first time that is equal to state
+    /// `n`.
+    pub const fn reduce(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.period
+        }
+    }
+
+    /// How many full cycles have passed before state `n`.
+    pub const fn cycles_before(&self, n: usize) -> usize {
+        if n < self.start {
+            0
+        } else {
+            (n - self.start) / self.period
+        }
+    }
+}
+
+/// Finds the cycle in the sequence `initial, next(initial), next(next(initial)), ...` using
+/// Floyd's tortoise and hare. Only a few states are kept in memory, but `next` is called about 3
+/// times as often as there are states before the cycle repeats.
+///
+/// The sequence must be infinite and every state must only depend on the one before it, so it
+/// is guaranteed to cycle if there are finitely many states.
+pub fn floyd<S: Clone + PartialEq>(initial: S, mut next: impl FnMut(&S) -> S) -> Cycle {
+    // find a state in the cycle, the hare is twice as far as the tortoise
+    let mut tortoise = next(&initial);
+    let mut hare = next(&tortoise);
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+        hare = next(&hare);
+    }
+
+    // the distance between the start and the cycle equals the distance between the meeting point
+    // and the start of the cycle
+    let mut start = 0;
+    tortoise = initial;
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+        start += 1;
+    }
+
+    let mut period = 1;
+    hare = next(&tortoise);
+    while tortoise != hare {
+        hare = next(&hare);
+        period += 1;
+    }
+
+    Cycle { start, period }
+}
+
+/// Like [`floyd`], but uses Brent's algorithm, which usually calls `next` fewer times.
+pub fn brent<S: Clone + PartialEq>(initial: S, mut next: impl FnMut(&S) -> S) -> Cycle {
+    // find the period by comparing against checkpoints at increasing powers of 2
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = next(&initial);
+    while tortoise != hare {
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = next(&hare);
+        period += 1;
+    }
+
+    // move the hare one period ahead, then advance both until they meet at the start
+    let mut start = 0;
+    tortoise = initial.clone();
+    hare = initial;
+    for _ in 0..period {
+        hare = next(&hare);
+    }
+    while tortoise != hare {
+        tortoise = next(&tortoise);
+        hare = next(&hare);
+        start += 1;
+    }
+
+    Cycle { start, period }
+}
+
+/// A cycle that was found with [`detect_by_key`], with the value that was tracked for every state
+/// before it repeated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackedCycle {
+    pub cycle: Cycle,
+    /// How much the value changes during one cycle.
+    pub delta: isize,
+    values: Vec<isize>,
+}
+
+impl TrackedCycle {
+    /// The value of state `n`, assuming the value keeps changing by the same amount every cycle.
+    pub fn value_at(&self, n: usize) -> isize {
+        let cycles = self.cycle.cycles_before(n) as isize;
+        self.values[self.cycle.reduce(n)] + cycles * self.delta
+    }
+}
+
+/// Detects a cycle in a simulation where the full state is too large to compare, or not available
+/// as a single value.
+///
+/// `step` is called with `0, 1, 2, ...` and advances the simulation to that state. It returns a key
+/// that identifies the state and a value that is tracked for it, such as the height of a tower.
+/// Two states with the same key must behave the same from then on. The cycle is found when a key
+/// repeats, and `None` is returned if `step` returns `None` before that.
+pub fn detect_by_key<K: Eq + Hash>(
+    mut step: impl FnMut(usize) -> Option<(K, isize)>,
+) -> Option<TrackedCycle> {
+    let mut seen = AHashMap::new();
+    let mut values = vec![];
+
+    for index in 0.. {
+        let (key, value) = step(index)?;
+        if let Some(&start) = seen.get(&key) {
+            return Some(TrackedCycle {
+                cycle: Cycle {
+                    start,
+                    period: index - start,
+                },
+                delta: value - values[start],
+                values,
+            });
+        }
+
+        seen.insert(key, index);
+        values.push(value);
+    }
+
+    unreachable!()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn next(x: &u32) -> u32 {
+        (x * x + 1) % 255
+    }
+
+    /// Finds the cycle by remembering every state.
+    fn brute_force(initial: u32, next: impl Fn(&u32) -> u32) -> Cycle {
+        let mut states = vec![initial];
+        loop {
+            let state = next(states.last().unwrap());
+            if let Some(start) = states.iter().position(|&s| s == state) {
+                return Cycle {
+                    start,
+                    period: states.len() - start,
+                };
+            }
+            states.push(state);
+        }
+    }
+
+    #[test]
+    fn test_floyd_and_brent() {
+        for initial in 0..255 {
+            let expected = brute_force(initial, next);
+            assert_eq!(floyd(initial, next), expected, "{initial}");
+            assert_eq!(brent(initial, next), expected, "{initial}");
+        }
+
+        let pure_cycle = Cycle {
+            start: 0,
+            period: 7,
+        };
+        assert_eq!(floyd(3, |x| (x + 1) % 7), pure_cycle);
+        assert_eq!(brent(3, |x| (x + 1) % 7), pure_cycle);
+        assert_eq!(
+            brent(0, |_| 0),
+            Cycle {
+                start: 0,
+                period: 1
+            }
+        );
+    }
+
+    #[test]
+    fn test_cycle() {
+        let cycle = Cycle {
+            start: 3,
+            period: 4,
+        };
+        assert_eq!(cycle.reduce(2), 2);
+        assert_eq!(cycle.reduce(3), 3);
+        assert_eq!(cycle.reduce(7), 3);
+        assert_eq!(cycle.reduce(12), 4);
+        assert_eq!(cycle.cycles_before(2), 0);
+        assert_eq!(cycle.cycles_before(12), 2);
+    }
+
+    #[test]
+    fn test_detect_by_key() {
+        // the key repeats every 3 states after the first 2, while the value keeps increasing
+        let keys = [10, 11, 0, 1, 2];
+        let tracked = detect_by_key(|i| {
+            let key = keys[i.min(2) + (i.max(2) - 2) % 3];
+            Some((key, i as isize * 2))
+        })
+        .unwrap();
+
+        assert_eq!(
+            tracked.cycle,
+            Cycle {
+                start: 2,
+                period: 3
+            }
+        );
+        assert_eq!(tracked.delta, 6);
+        for n in 0..20 {
+            assert_eq!(tracked.value_at(n), n as isize * 2);
+        }
+
+        assert_eq!(detect_by_key(|i| (i < 5).then_some((i, 0))), None);
+    }
+}
@@ -8,38 +8,55 @@ impl SolutionSilver<usize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> usize {
+    type Parsed<'a> = Vec<usize>;
+
+    /// Parses the input into the total calories carried by each elf.
+    fn parse(input: &str) -> Result<Vec<usize>, SolveError> {
         let mut lines = input.split('\n').peekable();
-        let mut max = 0;
+        let mut totals = vec![];
 
+        // the gold part adds up totals, so make sure the sum of all of them fits
+        let mut grand_total = 0usize;
         while lines.peek().is_some() {
-            let sum = lines
-                .by_ref()
-                .take_while(|&l| !l.is_empty())
-                .map(fast_parse_int)
-                .sum();
+            let mut sum = 0;
+            for line in lines.by_ref().take_while(|&l| !l.is_empty()) {
+                let calories = checked_parse_int(line.as_bytes()).map_err(|e| e.at(input, line))?;
+                grand_total = grand_total
+                    .checked_add(calories)
+                    .ok_or_else(|| SolveError::at(input, line, "total is too large"))?;
+                sum += calories;
+            }
 
-            max = max.max(sum);
+            totals.push(sum);
         }
 
-        max
+        Ok(totals)
+    }
+
+    fn solve_silver(totals: &Vec<usize>) -> usize {
+        totals.iter().copied().max().unwrap_or_default()
+    }
+
+    /// Generates `size` elves that carry 1 to 10 items each.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let elves: Vec<String> = (0..size.max(1))
+            .map(|_| {
+                let items: Vec<String> = (0..rng.usize(1..=10))
+                    .map(|_| rng.usize(1..=99999).to_string())
+                    .collect();
+                items.join("\n")
+            })
+            .collect();
+        Some(elves.join("\n\n"))
     }
 }
 
 impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
-        let mut lines = input.split('\n').peekable();
-
+    fn solve_gold(totals: &Vec<usize>) -> usize {
         // note: order is from least to most
         let mut max = [0, 0, 0];
 
-        while lines.peek().is_some() {
-            let sum = lines
-                .by_ref()
-                .take_while(|&l| !l.is_empty())
-                .map(fast_parse_int)
-                .sum();
-
+        for &sum in totals {
             if sum <= max[0] {
                 continue;
             }
@@ -87,3 +104,39 @@ fn test_gold_real() {
     let output = Day::calculate_gold(Day::INPUT_REAL);
     assert_eq!(199628, output);
 }
+
+#[test]
+fn test_malformed_input() {
+    assert_eq!(
+        Day::try_calculate_silver("1000\n)001000").unwrap_err(),
+        SolveError::Parse {
+            line: 2,
+            column: 1,
+            message: "expected a digit".to_string()
+        }
+    );
+
+    let input = format!("{}\n{}", usize::MAX, 1);
+    assert!(matches!(
+        Day::try_calculate_gold(&input),
+        Err(SolveError::Parse { line: 2, .. })
+    ));
+}
+
+#[cfg(test)]
+fn calculate_naive(input: &str) -> (usize, usize) {
+    let mut totals: Vec<usize> = input
+        .split("\n\n")
+        .map(|group| group.lines().map(|l| l.parse::<usize>().unwrap()).sum())
+        .collect();
+    totals.sort_unstable_by(|a, b| b.cmp(a));
+    (totals[0], totals.iter().take(3).sum())
+}
+
+#[test]
+fn test_differential() {
+    crate::property::check(2000, crate::property::input::<Day, _>, |input| {
+        let actual = (Day::calculate_silver(input), Day::calculate_gold(input));
+        assert_eq!(calculate_naive(input), actual);
+    });
+}
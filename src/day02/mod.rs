@@ -7,8 +7,22 @@ impl SolutionSilver<usize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> usize {
-        debug_assert!(input.as_bytes()[3] == b'\n');
+    type Parsed<'a> = &'a str;
+
+    /// Checks that every line is a single round, as the solutions read the rounds in chunks of 4
+    /// bytes without looking at them.
+    fn parse(input: &str) -> Result<&str, SolveError> {
+        for line in input.split('\n') {
+            if !matches!(line.as_bytes(), [b'A'..=b'C', b' ', b'X'..=b'Z']) {
+                return Err(SolveError::at(input, line, "expected a round like `A X`"));
+            }
+        }
+
+        Ok(input)
+    }
+
+    fn solve_silver(input: &&str) -> usize {
+        debug_assert_eq!(input.len() % 4, 3);
         debug_assert!(!input.ends_with('\n'));
 
         let bytes = input.as_bytes();
@@ -30,11 +44,25 @@ impl SolutionSilver<usize> for Day {
                 calculate_score(other, mine)
             }
     }
+
+    /// Generates a strategy guide of `size` rounds.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let rounds: Vec<String> = (0..size.max(1))
+            .map(|_| {
+                format!(
+                    "{} {}",
+                    rng.choose(&['A', 'B', 'C']),
+                    rng.choose(&['X', 'Y', 'Z'])
+                )
+            })
+            .collect();
+        Some(rounds.join("\n"))
+    }
 }
 
 impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
-        debug_assert!(input.as_bytes()[3] == b'\n');
+    fn solve_gold(input: &&str) -> usize {
+        debug_assert_eq!(input.len() % 4, 3);
         debug_assert!(!input.ends_with('\n'));
 
         let bytes = input.as_bytes();
@@ -103,3 +131,84 @@ fn test_gold_sample() {
 fn test_gold_real() {
     assert_eq!(13433, Day::calculate_gold(Day::INPUT_REAL));
 }
+
+#[test]
+fn test_single_round() {
+    assert_eq!(8, Day::calculate_silver("A Y"));
+    assert_eq!(4, Day::calculate_gold("A Y"));
+}
+
+#[test]
+fn test_malformed_input() {
+    assert_eq!(
+        Day::try_calculate_silver("A Y\nB XZ").unwrap_err(),
+        SolveError::Parse {
+            line: 2,
+            column: 1,
+            message: "expected a round like `A X`".to_string()
+        }
+    );
+
+    for input in ["", "A", "A Ya", "A Y\n\nB X", "A =\nB X", "A Y\n2"] {
+        assert!(matches!(
+            Day::try_calculate_gold(input),
+            Err(SolveError::Parse { .. })
+        ));
+    }
+}
+
+/// Scores the guide by spelling out the rules of the puzzle.
+#[cfg(test)]
+fn calculate_naive(input: &str) -> (usize, usize) {
+    let shape_score = |shape| match shape {
+        "rock" => 1,
+        "paper" => 2,
+        _ => 3,
+    };
+    let beats = |shape| match shape {
+        "rock" => "scissors",
+        "paper" => "rock",
+        _ => "paper",
+    };
+    let outcome_score = |mine, other| match () {
+        _ if mine == other => 3,
+        _ if beats(mine) == other => 6,
+        _ => 0,
+    };
+
+    let (mut silver, mut gold) = (0, 0);
+    for line in input.lines() {
+        let (other, mine) = line.split_once(' ').unwrap();
+        let other = match other {
+            "A" => "rock",
+            "B" => "paper",
+            _ => "scissors",
+        };
+
+        let mine_silver = match mine {
+            "X" => "rock",
+            "Y" => "paper",
+            _ => "scissors",
+        };
+        silver += shape_score(mine_silver) + outcome_score(mine_silver, other);
+
+        let mine_gold = ["rock", "paper", "scissors"]
+            .into_iter()
+            .find(|&shape| {
+                let score = outcome_score(shape, other);
+                matches!((mine, score), ("X", 0) | ("Y", 3) | ("Z", 6))
+            })
+            .unwrap();
+        gold += shape_score(mine_gold) + outcome_score(mine_gold, other);
+    }
+
+    (silver, gold)
+}
+
+#[test]
+fn test_differential() {
+    crate::property::check(2000, crate::property::input::<Day, _>, |input| {
+        let actual = (Day::calculate_silver(input), Day::calculate_gold(input));
+        assert_eq!(calculate_naive(input), actual);
+    });
+}
@@ -7,7 +7,29 @@ impl SolutionSilver<usize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> usize {
+    type Parsed<'a> = &'a str;
+
+    /// Checks that every rucksack can be split in two and only holds items, as the solutions work on
+    /// the raw lines.
+    fn parse(input: &str) -> Result<&str, SolveError> {
+        for line in input.split('\n') {
+            if let Some(offset) = line.bytes().position(|b| !b.is_ascii_alphabetic()) {
+                return Err(SolveError::at(
+                    input,
+                    &line[offset..],
+                    "expected an item (a letter)",
+                ));
+            }
+            if line.len() % 2 != 0 {
+                let message = "expected a rucksack with an even number of items";
+                return Err(SolveError::at(input, line, message));
+            }
+        }
+
+        Ok(input)
+    }
+
+    fn solve_silver(input: &&str) -> usize {
         input
             .split('\n')
             .map(|l| {
@@ -35,10 +57,44 @@ impl SolutionSilver<usize> for Day {
             })
             .sum()
     }
+
+    /// Generates `size` groups of three rucksacks. The halves of each rucksack share exactly one
+    /// item type, and the rucksacks in a group share exactly one badge.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let mut lines = vec![];
+        for _ in 0..size.max(1) {
+            // every rucksack in a group uses its own 17 item types, except for the badge
+            let mut items: Vec<char> = ('a'..='z').chain('A'..='Z').collect();
+            rng.shuffle(&mut items);
+            let badge = items[0];
+
+            for pool in items[1..].chunks(17) {
+                let shared = if rng.chance(1, 4) { badge } else { pool[0] };
+                let badge_left = rng.chance(1, 2);
+                let half_len = rng.usize(2..=12);
+
+                let mut half = |only: &[char], has_badge: bool| {
+                    let mut half = vec![shared];
+                    if has_badge && shared != badge {
+                        half.push(badge);
+                    }
+                    while half.len() < half_len {
+                        half.push(*rng.choose(only));
+                    }
+                    rng.shuffle(&mut half);
+                    half
+                };
+                let left = half(&pool[1..9], badge_left);
+                let right = half(&pool[9..], !badge_left);
+                lines.push(left.into_iter().chain(right).collect::<String>());
+            }
+        }
+        Some(lines.join("\n"))
+    }
 }
 
 impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
+    fn solve_gold(input: &&str) -> usize {
         input
             .split('\n')
             .array_chunks::<3>()
@@ -96,3 +152,74 @@ fn test_gold_sample() {
 fn test_gold_real() {
     assert_eq!(Day::calculate_gold(Day::INPUT_REAL), 2881);
 }
+
+#[test]
+fn test_malformed_input() {
+    let input =
+        Day::INPUT_SAMPLE.replacen("vJrwpWtwJgWrhcsFMMfFFhFp", "vJrwpWtwJgWrhcsFMMfFFhF", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 1,
+            column: 1,
+            message: "expected a rucksack with an even number of items".to_string()
+        }
+    );
+
+    let input = Day::INPUT_SAMPLE.replacen('W', "1", 1);
+    assert!(matches!(
+        Day::try_calculate_gold(&input),
+        Err(SolveError::Parse {
+            line: 1,
+            column: 6,
+            ..
+        })
+    ));
+}
+
+#[cfg(test)]
+fn calculate_naive(input: &str) -> (usize, usize) {
+    use std::collections::HashSet;
+
+    let priority = |c: char| match c {
+        'a'..='z' => c as usize - 'a' as usize + 1,
+        _ => c as usize - 'A' as usize + 27,
+    };
+    let only = |set: HashSet<char>| {
+        assert_eq!(set.len(), 1);
+        priority(set.into_iter().next().unwrap())
+    };
+
+    let lines: Vec<&str> = input.lines().collect();
+    let silver = lines
+        .iter()
+        .map(|line| {
+            let (left, right) = line.split_at(line.len() / 2);
+            let left: HashSet<char> = left.chars().collect();
+            only(right.chars().filter(|c| left.contains(c)).collect())
+        })
+        .sum();
+    let gold = lines
+        .chunks(3)
+        .map(|group| {
+            let sets: Vec<HashSet<char>> = group.iter().map(|l| l.chars().collect()).collect();
+            only(
+                sets[0]
+                    .iter()
+                    .copied()
+                    .filter(|c| sets[1].contains(c) && sets[2].contains(c))
+                    .collect(),
+            )
+        })
+        .sum();
+
+    (silver, gold)
+}
+
+#[test]
+fn test_differential() {
+    crate::property::check(2000, crate::property::input::<Day, _>, |input| {
+        let actual = (Day::calculate_silver(input), Day::calculate_gold(input));
+        assert_eq!(calculate_naive(input), actual);
+    });
+}
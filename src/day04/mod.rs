@@ -1,4 +1,4 @@
-use crate::utils::{fast_parse_int_from_bytes, split_once};
+use crate::utils::Cursor;
 
 use super::*;
 
@@ -11,53 +11,69 @@ impl SolutionSilver<usize> for Day {
 
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> usize {
-        let mut input = input.as_bytes();
-        let mut sum = 0;
-        while !input.is_empty() {
-            let (range_1_1, range_1_2, range_2_1, range_2_2);
+    type Parsed<'a> = Vec<[usize; 4]>;
 
-            (range_1_1, input) = split_once(input, b'-').unwrap();
-            let range_1_start = fast_parse_int_from_bytes(range_1_1);
+    /// Parses the input into the start and end of both ranges of each pair.
+    fn parse(input: &str) -> Result<Vec<[usize; 4]>, SolveError> {
+        let mut cursor = Cursor::new(input);
+        let mut pairs = vec![];
 
-            (range_1_2, input) = split_once(input, b',').unwrap();
-            let range_1_end = fast_parse_int_from_bytes(range_1_2);
+        while !cursor.is_empty() {
+            let range_1_start = cursor.uint()?;
+            cursor.expect(b"-")?;
+            let range_1_end = cursor.uint()?;
+            cursor.expect(b",")?;
+            let range_2_start = cursor.uint()?;
+            cursor.expect(b"-")?;
+            let range_2_end = cursor.uint()?;
 
-            (range_2_1, input) = split_once(input, b'-').unwrap();
-            let range_2_start = fast_parse_int_from_bytes(range_2_1);
+            if !cursor.is_empty() {
+                cursor.expect(b"\n")?;
+            }
 
-            (range_2_2, input) = split_once(input, b'\n').unwrap_or((input, &[]));
-            let range_2_end = fast_parse_int_from_bytes(range_2_2);
-
-            sum += usize::from(range_1_start >= range_2_start && range_1_end <= range_2_end)
-                | usize::from(range_2_start >= range_1_start && range_2_end <= range_1_end);
+            pairs.push([range_1_start, range_1_end, range_2_start, range_2_end]);
         }
-        sum
-    }
-}
-
-impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
-        let mut input = input.as_bytes();
-        let mut sum = 0;
-        while !input.is_empty() {
-            let (range_1_1, range_1_2, range_2_1, range_2_2);
-
-            (range_1_1, input) = split_once(input, b'-').unwrap();
-            let range_1_start = fast_parse_int_from_bytes(range_1_1);
 
-            (range_1_2, input) = split_once(input, b',').unwrap();
-            let range_1_end = fast_parse_int_from_bytes(range_1_2);
+        Ok(pairs)
+    }
 
-            (range_2_1, input) = split_once(input, b'-').unwrap();
-            let range_2_start = fast_parse_int_from_bytes(range_2_1);
+    fn solve_silver(pairs: &Vec<[usize; 4]>) -> usize {
+        pairs
+            .iter()
+            .filter(
+                |&&[range_1_start, range_1_end, range_2_start, range_2_end]| {
+                    (range_1_start >= range_2_start && range_1_end <= range_2_end)
+                        | (range_2_start >= range_1_start && range_2_end <= range_1_end)
+                },
+            )
+            .count()
+    }
 
-            (range_2_2, input) = split_once(input, b'\n').unwrap_or((input, &[]));
-            let range_2_end = fast_parse_int_from_bytes(range_2_2);
+    /// Generates `size` pairs of section ranges, with small numbers so ranges often touch or
+    /// overlap.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let max = rng.usize(1..=99);
+        let mut range = || {
+            let (a, b) = (rng.usize(1..=max), rng.usize(1..=max));
+            format!("{}-{}", a.min(b), a.max(b))
+        };
+        let pairs: Vec<String> = (0..size.max(1))
+            .map(|_| format!("{},{}", range(), range()))
+            .collect();
+        Some(pairs.join("\n"))
+    }
+}
 
-            sum += usize::from(!(range_2_end < range_1_start || range_2_start > range_1_end));
-        }
-        sum
+impl SolutionGold<usize, usize> for Day {
+    fn solve_gold(pairs: &Vec<[usize; 4]>) -> usize {
+        pairs
+            .iter()
+            .filter(
+                |&&[range_1_start, range_1_end, range_2_start, range_2_end]| {
+                    !(range_2_end < range_1_start || range_2_start > range_1_end)
+                },
+            )
+            .count()
     }
 }
 
@@ -80,3 +96,52 @@ fn test_gold_sample() {
 fn test_gold_real() {
     assert_eq!(Day::calculate_gold(Day::INPUT_REAL), 808);
 }
+
+#[test]
+fn test_malformed_input() {
+    assert_eq!(
+        Day::try_calculate_silver("2-4,6-8\n2-4,6-K").unwrap_err(),
+        SolveError::Parse {
+            line: 2,
+            column: 7,
+            message: "expected a number".to_string()
+        }
+    );
+
+    for input in ["_", "2-", "2-4,", "2-4,6-8\n-5"] {
+        assert!(matches!(
+            Day::try_calculate_gold(input),
+            Err(SolveError::Parse { .. })
+        ));
+    }
+}
+
+/// Compares the pairs section by section.
+#[cfg(test)]
+fn calculate_naive(input: &str) -> (usize, usize) {
+    use std::collections::HashSet;
+
+    let (mut silver, mut gold) = (0, 0);
+    for line in input.lines() {
+        let sections: Vec<HashSet<usize>> = line
+            .split(',')
+            .map(|range| {
+                let (start, end) = range.split_once('-').unwrap();
+                (start.parse().unwrap()..=end.parse().unwrap()).collect()
+            })
+            .collect();
+        let (first, second) = (&sections[0], &sections[1]);
+
+        silver += usize::from(first.is_subset(second) || second.is_subset(first));
+        gold += usize::from(!first.is_disjoint(second));
+    }
+    (silver, gold)
+}
+
+#[test]
+fn test_differential() {
+    crate::property::check(2000, crate::property::input::<Day, _>, |input| {
+        let actual = (Day::calculate_silver(input), Day::calculate_gold(input));
+        assert_eq!(calculate_naive(input), actual);
+    });
+}
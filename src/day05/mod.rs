@@ -1,6 +1,6 @@
 use tinyvec::ArrayVec;
 
-use crate::utils::{fast_parse_int_from_bytes, split_once_2};
+use crate::utils::Cursor;
 
 use super::*;
 
@@ -10,66 +10,115 @@ impl SolutionSilver<String> for Day {
     const DAY: u32 = 5;
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
+    // the crate drawing is aligned with spaces, only strip line breaks from the end
+    const INPUT_FORMAT: InputFormat = InputFormat::DEFAULT.trim_end(TrimEnd::Newlines);
 
-    fn calculate_silver(input: &str) -> String {
-        let mut bytes = input.as_bytes();
-        let mut header;
-        (header, bytes) = parse_header(bytes);
+    type Parsed<'a> = (Stacks, Vec<Move>);
 
-        while !bytes.is_empty() {
-            debug_assert_eq!(&bytes[.."move ".len()], b"move ");
-            bytes = &bytes["move ".len()..];
+    fn parse(input: &str) -> Result<(Stacks, Vec<Move>), SolveError> {
+        let (header, mut cursor) = parse_header(input)?;
 
-            let buf;
-            (buf, bytes) = split_once_2(bytes, b' ');
-            let count = fast_parse_int_from_bytes(buf);
-            let from = (bytes[6] - b'0') as usize;
-            let to = (bytes[11] - b'0') as usize;
+        // both parts move the same number of crates, so check up front that every stack has
+        // enough of them
+        let mut heights = header
+            .iter()
+            .map(|s| s.len())
+            .collect::<ArrayVec<[usize; 9]>>();
+        let mut moves = vec![];
+        while !cursor.is_empty() {
+            let line = cursor;
+            cursor.expect(b"move ")?;
+            let count = cursor.uint()?;
+            cursor.expect(b" from ")?;
+            let from = parse_stack_number(&mut cursor, heights.len())?;
+            cursor.expect(b" to ")?;
+            let to = parse_stack_number(&mut cursor, heights.len())?;
 
-            bytes = &bytes[" from x to y".len()..];
+            if !cursor.is_empty() {
+                cursor.expect(b"\n")?;
+            }
 
-            if !bytes.is_empty() {
-                bytes = &bytes["\n".len()..];
+            if count > heights[from - 1] {
+                let height = heights[from - 1];
+                let message = format!("cannot move {count} crates from stack {from} with {height}");
+                return Err(line.error(message));
             }
+            heights[from - 1] -= count;
+            heights[to - 1] += count;
+
+            moves.push(Move { count, from, to });
+        }
+
+        Ok((header, moves))
+    }
+
+    fn solve_silver((header, moves): &(Stacks, Vec<Move>)) -> String {
+        let mut header = *header;
 
+        for &Move { count, from, to } in moves {
             for _ in 0..count {
                 let item = header[from - 1].pop().unwrap();
                 header[to - 1].push(item);
             }
         }
 
-        let header_len = header.len();
-        header
-            .into_iter()
-            .fold(String::with_capacity(header_len), |mut acc, mut v| {
-                acc.push(v.pop().unwrap() as char);
-                acc
-            })
+        top_crates(header)
     }
-}
 
-impl SolutionGold<String, String> for Day {
-    fn calculate_gold(input: &str) -> String {
-        let mut bytes = input.as_bytes();
-        let mut header;
-        (header, bytes) = parse_header(bytes);
+    /// Generates a drawing of up to 9 stacks followed by `size` moves. Stacks never run empty, so
+    /// every stack has a crate on top at the end.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        use std::fmt::Write;
 
-        while !bytes.is_empty() {
-            debug_assert_eq!(&bytes[.."move ".len()], b"move ");
-            bytes = &bytes["move ".len()..];
+        let stack_count = rng.usize(2..=9);
+        let mut stacks: Vec<Vec<char>> = (0..stack_count)
+            .map(|_| {
+                (0..rng.usize(1..=7))
+                    .map(|_| rng.letter().to_ascii_uppercase())
+                    .collect()
+            })
+            .collect();
+        if stacks.iter().all(|s| s.len() < 2) {
+            stacks[0].push('X');
+        }
 
-            let buf;
-            (buf, bytes) = split_once_2(bytes, b' ');
-            let count = fast_parse_int_from_bytes(buf);
-            let from = (bytes[6] - b'0') as usize;
-            let to = (bytes[11] - b'0') as usize;
+        let height = stacks.iter().map(Vec::len).max().unwrap();
+        let mut input = String::new();
+        for row in (0..height).rev() {
+            let cells: Vec<String> = stacks
+                .iter()
+                .map(|s| {
+                    s.get(row)
+                        .map_or_else(|| "   ".to_string(), |c| format!("[{c}]"))
+                })
+                .collect();
+            writeln!(input, "{}", cells.join(" ")).unwrap();
+        }
+        let numbers: Vec<String> = (1..=stack_count).map(|i| format!(" {i} ")).collect();
+        writeln!(input, "{}\n", numbers.join(" ")).unwrap();
 
-            bytes = &bytes[" from x to y".len()..];
+        // there are more crates than stacks, so some stack always has a crate to spare
+        let mut moves = vec![];
+        for _ in 0..size.max(1) {
+            let sources: Vec<usize> = (0..stack_count).filter(|&i| stacks[i].len() >= 2).collect();
+            let from = *rng.choose(&sources);
+            let to = (from + rng.usize(1..=stack_count - 1)) % stack_count;
+            let count = rng.usize(1..=stacks[from].len() - 1);
 
-            if !bytes.is_empty() {
-                bytes = &bytes["\n".len()..];
-            }
+            let split = stacks[from].len() - count;
+            let moved = stacks[from].split_off(split);
+            stacks[to].extend(moved);
+            moves.push(format!("move {count} from {} to {}", from + 1, to + 1));
+        }
+        Some(input + &moves.join("\n"))
+    }
+}
+
+impl SolutionGold<String, String> for Day {
+    fn solve_gold((header, moves): &(Stacks, Vec<Move>)) -> String {
+        let mut header = *header;
 
+        for &Move { count, from, to } in moves {
             let from_bucket = &mut header[from - 1];
             let from_bucket_len = from_bucket.len();
             let from_slice = from_bucket
@@ -78,53 +127,104 @@ impl SolutionGold<String, String> for Day {
             header[to - 1].extend_from_slice(&from_slice);
         }
 
-        let header_len = header.len();
-        header
-            .into_iter()
-            .fold(String::with_capacity(header_len), |mut acc, mut v| {
-                acc.push(v.pop().unwrap() as char);
-                acc
-            })
+        top_crates(header)
     }
 }
 
-fn parse_header(bytes: &[u8]) -> (ArrayVec<[ArrayVec<[u8; 64]>; 9]>, &[u8]) {
-    let line_len = bytes.iter().position(|&x| x == b'\n').unwrap() + 1;
-    let bucket_count = (line_len) / 4;
+pub type Stacks = ArrayVec<[ArrayVec<[u8; 64]>; 9]>;
+
+/// Moves `count` crates from stack `from` to stack `to`. Stacks are 1-based.
+#[derive(Debug, Clone, Copy)]
+pub struct Move {
+    count: usize,
+    from: usize,
+    to: usize,
+}
+
+/// The crate on top of each stack. Stacks that ran empty are skipped.
+fn top_crates(header: Stacks) -> String {
+    let header_len = header.len();
+    header
+        .into_iter()
+        .fold(String::with_capacity(header_len), |mut acc, mut v| {
+            acc.extend(v.pop().map(char::from));
+            acc
+        })
+}
+
+/// The maximum number of crates, as every crate can end up on the same stack.
+const MAX_CRATES: usize = 64;
 
-    let bucket_capacity = bytes
-        .iter()
-        .step_by(line_len)
-        .position(|&c| c == b'\n')
-        .unwrap()
-        - 1;
+/// Parses the crate drawing and the stack numbers below it, and returns a cursor at the first move.
+fn parse_header(input: &str) -> Result<(Stacks, Cursor<'_>), SolveError> {
+    let mut cursor = Cursor::new(input);
+    let mut rows = vec![];
+    loop {
+        let line = cursor.until(b'\n')?;
+        if line.is_empty() {
+            break;
+        }
+        rows.push(line);
+    }
 
-    let total_len = (bucket_capacity + 1) * line_len + 1;
+    let Some(numbers) = rows.pop() else {
+        return Err(SolveError::at(input, input, "expected a drawing of the stacks"));
+    };
+    let bucket_count = (numbers.len() + 1) / 4;
+    if numbers.len() % 4 != 3 || !(1..=9).contains(&bucket_count) {
+        let message = "expected 1 to 9 stack numbers, each 3 characters wide";
+        return Err(SolveError::at(input, numbers, message));
+    }
+    for i in 0..bucket_count {
+        let cell = &numbers[i * 4..];
+        if cell[..3] != [b' ', b'1' + i as u8, b' '] || !matches!(cell.get(3), None | Some(b' ')) {
+            let message = format!("expected stack number {}", i + 1);
+            return Err(SolveError::at(input, cell, message));
+        }
+    }
 
-    let mut buckets = ArrayVec::<[ArrayVec<[u8; 64]>; 9]>::new();
+    let mut buckets = Stacks::new();
     for _ in 0..bucket_count {
         buckets.push(ArrayVec::<[u8; 64]>::new());
     }
 
-    for line_num in (0..bucket_capacity).rev() {
-        let offset = line_num * line_len;
-        for i in 0..bucket_count {
-            let index = offset + i * 4 + 1;
-            let char = bytes[index];
-
-            if char != b' ' {
-                buckets[i].push(char);
+    let mut crate_count = 0;
+    for &line in rows.iter().rev() {
+        if line.len() != numbers.len() {
+            let message = format!("expected a row of {} characters", numbers.len());
+            return Err(SolveError::at(input, line, message));
+        }
 
-                debug_assert_eq!(bytes[index - 1], b'[');
-                debug_assert_eq!(bytes[index + 1], b']');
-            } else {
-                debug_assert_eq!(bytes[index - 1], b' ');
-                debug_assert_eq!(bytes[index + 1], b' ');
+        for (i, bucket) in buckets.iter_mut().enumerate() {
+            let cell = &line[i * 4..];
+            match cell {
+                [b'[', char @ b'A'..=b'Z', b']', ..] => {
+                    crate_count += 1;
+                    if crate_count > MAX_CRATES {
+                        let message = format!("expected at most {MAX_CRATES} crates");
+                        return Err(SolveError::at(input, cell, message));
+                    }
+                    bucket.push(*char);
+                }
+                [b' ', b' ', b' ', ..] => {}
+                _ => return Err(SolveError::at(input, cell, "expected a crate or spaces")),
+            }
+            if !matches!(cell.get(3), None | Some(b' ')) {
+                return Err(SolveError::at(input, &cell[3..], "expected a space"));
             }
         }
     }
 
-    (buckets, &bytes[total_len..])
+    Ok((buckets, cursor))
+}
+
+/// Parses a stack number, which must be between 1 and `count`.
+fn parse_stack_number(cursor: &mut Cursor, count: usize) -> Result<usize, SolveError> {
+    let start = *cursor;
+    match cursor.uint()? {
+        number @ 1.. if number <= count => Ok(number),
+        _ => Err(start.error(format!("expected a stack from 1 to {count}"))),
+    }
 }
 
 #[test]
@@ -150,3 +250,98 @@ fn test_gold_real() {
     let output = Day::calculate_gold(Day::INPUT_REAL);
     assert_eq!("MGDMPSZTM", output);
 }
+
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen("from 1 to 3", "from 1 to 4", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 7,
+            column: 18,
+            message: "expected a stack from 1 to 3".to_string()
+        }
+    );
+
+    let input = Day::INPUT_SAMPLE.replacen("move 3 from 1", "move 4 from 1", 1);
+    assert_eq!(
+        Day::try_calculate_gold(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 7,
+            column: 1,
+            message: "cannot move 4 crates from stack 1 with 3".to_string()
+        }
+    );
+
+    // found by fuzzing, these used to index out of bounds or overflow
+    for input in [
+        "",
+        "  ",
+        "    [D]    \n[",
+        "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\nmove 1",
+        "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\nmove 1 from 2 to 1\n2",
+        "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\nmove 1 from 0 to 1",
+        "    [D]    \n[N] [C]    \n[Z] [M] [P]\n 1   2   3 \n\nmove 1 from 2 to 1\nmove 3 from 1 to [",
+    ] {
+        assert!(
+            matches!(Day::try_calculate_silver(input), Err(SolveError::Parse { .. })),
+            "{input:?}"
+        );
+    }
+}
+
+#[test]
+fn test_empty_stack() {
+    let input = "    [D]    \n[N] [C] [P]\n 1   2   3 \n\nmove 1 from 1 to 2";
+    assert_eq!("NP", Day::calculate_silver(input));
+    assert_eq!("NP", Day::calculate_gold(input));
+}
+
+/// Parses the drawing by columns and moves the crates one by one.
+#[cfg(test)]
+fn calculate_naive(input: &str) -> (String, String) {
+    let (drawing, moves) = input.split_once("\n\n").unwrap();
+    let mut rows: Vec<&str> = drawing.lines().collect();
+    let numbers = rows.pop().unwrap();
+    let stacks: Vec<Vec<char>> = numbers
+        .char_indices()
+        .filter(|(_, c)| c.is_ascii_digit())
+        .map(|(column, _)| {
+            rows.iter()
+                .rev()
+                .map(|row| row.as_bytes()[column] as char)
+                .take_while(|&c| c != ' ')
+                .collect()
+        })
+        .collect();
+
+    let (mut silver, mut gold) = (stacks.clone(), stacks);
+    for line in moves.lines() {
+        let words: Vec<usize> = line.split(' ').filter_map(|w| w.parse().ok()).collect();
+        let [count, from, to] = words[..] else {
+            panic!("invalid move {line}");
+        };
+
+        for _ in 0..count {
+            let item = silver[from - 1].pop().unwrap();
+            silver[to - 1].push(item);
+        }
+
+        let mut moved = vec![];
+        for _ in 0..count {
+            moved.insert(0, gold[from - 1].pop().unwrap());
+        }
+        gold[to - 1].extend(moved);
+    }
+
+    let tops = |stacks: Vec<Vec<char>>| stacks.iter().map(|s| *s.last().unwrap()).collect();
+    (tops(silver), tops(gold))
+}
+
+#[test]
+fn test_differential() {
+    crate::property::check(2000, crate::property::input::<Day, _>, |input| {
+        let actual = (Day::calculate_silver(input), Day::calculate_gold(input));
+        assert_eq!(calculate_naive(input), actual);
+    });
+}
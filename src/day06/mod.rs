@@ -11,19 +11,54 @@ impl SolutionSilver<usize> for Day {
 
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> usize {
-        calculate_bitwise_golf::<4>(input.as_bytes())
+    type Parsed<'a> = &'a str;
+
+    /// Checks that the datastream only contains lowercase letters and has a start-of-message
+    /// marker, which is always preceded by a start-of-packet marker.
+    fn parse(input: &str) -> Result<&str, SolveError> {
+        if let Some(offset) = input.bytes().position(|b| !b.is_ascii_lowercase()) {
+            return Err(SolveError::at(
+                input,
+                &input[offset..],
+                "expected a lowercase letter",
+            ));
+        }
+        if calculate_bitwise_skip::<14>(input.as_bytes()).is_none() {
+            return Err(SolveError::invalid(
+                "the datastream has no start-of-message marker",
+            ));
+        }
+
+        Ok(input)
+    }
+
+    fn solve_silver(input: &&str) -> usize {
+        calculate_bitwise_golf::<4>(input.as_bytes()).expect("checked while parsing")
+    }
+
+    /// Generates a datastream of `size` random characters followed by 14 distinct characters, so
+    /// it always contains both markers.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let alphabet_size = rng.usize(1..=26);
+        let mut input: String = (0..size)
+            .map(|_| (b'a' + rng.usize(0..=alphabet_size - 1) as u8) as char)
+            .collect();
+
+        let mut letters: Vec<char> = ('a'..='z').collect();
+        rng.shuffle(&mut letters);
+        input.extend(&letters[..14]);
+        Some(input)
     }
 }
 
 impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
-        calculate_bitwise_skip::<14>(input.as_bytes())
+    fn solve_gold(input: &&str) -> usize {
+        calculate_bitwise_skip::<14>(input.as_bytes()).expect("checked while parsing")
     }
 }
 
 #[allow(unused)]
-fn calculate_bitwise<const SIZE: usize>(bytes: &[u8]) -> usize {
+fn calculate_bitwise<const SIZE: usize>(bytes: &[u8]) -> Option<usize> {
     let mut bit_buffers = [0u32; SIZE];
     let mut bit_buffer_index = 0;
 
@@ -35,28 +70,27 @@ fn calculate_bitwise<const SIZE: usize>(bytes: &[u8]) -> usize {
 
         let sum = bit_buffers.iter().fold(0, |acc, v| acc | v);
         if sum.count_ones() == SIZE as u32 {
-            return bit_buffer_index;
+            return Some(bit_buffer_index);
         }
     }
 
-    unreachable!("no pattern found");
+    None
 }
 
 #[allow(unused)]
-fn calculate_bitwise_golf<const SIZE: usize>(bytes: &[u8]) -> usize {
+fn calculate_bitwise_golf<const SIZE: usize>(bytes: &[u8]) -> Option<usize> {
     bytes
         .array_windows::<SIZE>()
         .map(|w| w.iter().fold(0, |a, v| a | (1u32 << (v - b'a'))))
         .position(|v| v.count_ones() as usize == SIZE)
-        .unwrap()
-        + SIZE
+        .map(|position| position + SIZE)
 }
 
 #[allow(unused)]
-fn calculate_bitwise_skip<const SIZE: usize>(bytes: &[u8]) -> usize {
+fn calculate_bitwise_skip<const SIZE: usize>(bytes: &[u8]) -> Option<usize> {
     let mut index = SIZE;
 
-    while index < bytes.len() {
+    while index <= bytes.len() {
         let cflow = ((index - SIZE)..(index)).rev().try_fold(0, |bits, i| {
             let new_bit = 1u32 << (bytes[i] - b'a');
             let new_bits = bits | new_bit;
@@ -69,7 +103,7 @@ fn calculate_bitwise_skip<const SIZE: usize>(bytes: &[u8]) -> usize {
         });
 
         match cflow {
-            ControlFlow::Continue(x) => return index,
+            ControlFlow::Continue(x) => return Some(index),
             ControlFlow::Break(bits) => {
                 let count_to_skip = SIZE - (bits.count_ones() as usize);
                 index += count_to_skip;
@@ -77,7 +111,7 @@ fn calculate_bitwise_skip<const SIZE: usize>(bytes: &[u8]) -> usize {
         }
     }
 
-    unreachable!("no pattern found");
+    None
 }
 
 #[test]
@@ -125,3 +159,51 @@ fn test_gold_real() {
     let output = Day::calculate_gold(Day::INPUT_REAL);
     assert_eq!(3217, output);
 }
+
+#[cfg(test)]
+fn calculate_naive<const SIZE: usize>(bytes: &[u8]) -> Option<usize> {
+    bytes
+        .windows(SIZE)
+        .position(|w| w.iter().collect::<std::collections::HashSet<_>>().len() == SIZE)
+        .map(|position| position + SIZE)
+}
+
+#[test]
+fn test_differential() {
+    crate::property::check(2000, crate::property::input::<Day, _>, |input| {
+        let bytes = input.as_bytes();
+        let expected = (calculate_naive::<4>(bytes), calculate_naive::<14>(bytes));
+        assert_eq!(expected.0, calculate_bitwise::<4>(bytes));
+        assert_eq!(expected.0, calculate_bitwise_golf::<4>(bytes));
+        assert_eq!(expected.0, calculate_bitwise_skip::<4>(bytes));
+        assert_eq!(expected.1, calculate_bitwise::<14>(bytes));
+        assert_eq!(expected.1, calculate_bitwise_golf::<14>(bytes));
+        assert_eq!(expected.1, calculate_bitwise_skip::<14>(bytes));
+        let actual = (Day::calculate_silver(input), Day::calculate_gold(input));
+        assert_eq!(expected, (Some(actual.0), Some(actual.1)));
+    });
+}
+
+#[test]
+fn test_marker_at_end() {
+    // found by `test_differential`: the skipping implementation missed markers that end the input
+    assert_eq!(Some(4), calculate_bitwise_skip::<4>(b"abcd"));
+    assert_eq!(28, Day::calculate_gold("aaaaaaaaaaaaaaabcdefghijklmn"));
+}
+
+#[test]
+fn test_malformed_input() {
+    assert_eq!(
+        Day::try_calculate_silver("mjqjp^mgbljsph").unwrap_err(),
+        SolveError::Parse {
+            line: 1,
+            column: 6,
+            message: "expected a lowercase letter".to_string()
+        }
+    );
+
+    // found by fuzzing, these used to overflow or panic because there was no marker
+    for input in ["", "m:qv", "mjqjpqmgbljsph"] {
+        assert!(Day::try_calculate_gold(input).is_err(), "{input:?}");
+    }
+}
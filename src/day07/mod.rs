@@ -1,4 +1,4 @@
-use crate::utils::{fast_parse_int_from_bytes, split_once_2};
+use crate::utils::checked_parse_int;
 
 use super::*;
 
@@ -9,84 +9,191 @@ impl SolutionSilver<usize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> usize {
-        debug_assert_eq!(input.lines().next(), Some("$ cd /"));
-        let mut iterator = input.as_bytes().split(|&x| x == b'\n');
-        let (_, sum_under_100000) = walk_directory_silver(&mut iterator);
-        sum_under_100000
-    }
-}
+    type Parsed<'a> = Vec<usize>;
 
-impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
-        const DISK_SIZE_MAX_IN_USE: usize = 70_000_000 - 30_000_000;
+    /// Parses the terminal output into the sizes of all directories. The root directory comes
+    /// last.
+    fn parse(input: &str) -> Result<Vec<usize>, SolveError> {
+        let mut lines = input.as_bytes().split(|&x| x == b'\n');
+        if lines.next() != Some(b"$ cd /") {
+            return Err(SolveError::at_offset(input, 0, "expected `$ cd /`"));
+        }
+
+        // the sizes of the directories from `/` down to the current one, which are still being
+        // listed
+        let mut open = vec![0usize];
+        let mut sizes = vec![];
+        for line in lines {
+            if let Some(name) = line.strip_prefix(b"$ cd ") {
+                match name {
+                    b"" => return Err(SolveError::at(input, name, "expected a directory")),
+                    b".." if open.len() == 1 => {
+                        return Err(SolveError::at(input, line, "`/` has no parent directory"));
+                    }
+                    b".." => close_directory(&mut open, &mut sizes)?,
+                    _ => open.push(0),
+                }
+            } else if line.starts_with(b"$") {
+                if line != b"$ ls" {
+                    return Err(SolveError::at(input, line, "expected `$ cd` or `$ ls`"));
+                }
+            } else if !line.starts_with(b"dir ") {
+                // file
+                let size = line.split(|&x| x == b' ').next().unwrap_or_default();
+                let size = checked_parse_int(size)
+                    .map_err(|_| SolveError::at(input, line, "expected a file size or `dir`"))?;
+                let current = open.last_mut().expect("`/` is never closed");
+                *current = current
+                    .checked_add(size)
+                    .ok_or_else(|| SolveError::invalid("the files are too large"))?;
+            }
+        }
 
-        debug_assert_eq!(input.lines().next(), Some("$ cd /"));
-        let mut iterator = input.as_bytes().split(|&x| x == b'\n');
-        let mut vec = vec![];
-        let size_in_use = walk_directory_gold(&mut iterator, &mut vec);
-        debug_assert_eq!(*vec.iter().max().unwrap(), size_in_use);
+        while !open.is_empty() {
+            close_directory(&mut open, &mut sizes)?;
+        }
+        Ok(sizes)
+    }
 
-        let size_over_limit = size_in_use - DISK_SIZE_MAX_IN_USE;
-        *vec.iter().filter(|&&x| x > size_over_limit).min().unwrap()
+    fn solve_silver(sizes: &Vec<usize>) -> usize {
+        sizes.iter().filter(|&&size| size <= 100000).sum()
     }
-}
 
-fn walk_directory_silver<'a>(
-    remaining_input: &mut impl Iterator<Item = &'a [u8]>,
-) -> (usize, usize) {
-    let mut my_size = 0;
-    let mut sum_under_100000 = 0;
-    loop {
-        let Some(line) = remaining_input.next() else {
-            return (my_size, sum_under_100000);
-        };
-
-        if line.starts_with(b"$ cd") {
-            if line[5] == b'.' {
-                return (my_size, sum_under_100000);
+    /// Generates the transcript of exploring `size` directories, which form a random tree below
+    /// `/`. The files add up to more than 40M but at most 70M, so there is always a directory that
+    /// frees up enough space.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        #[derive(Default)]
+        struct Directory {
+            name: String,
+            children: Vec<usize>,
+            files: Vec<(usize, String)>,
+        }
+
+        /// Picks a name that is not used by anything in the directory at `index` yet. Only files
+        /// get an extension.
+        fn new_name(
+            rng: &mut generate::Rng,
+            directories: &[Directory],
+            index: usize,
+            is_file: bool,
+        ) -> String {
+            let directory = &directories[index];
+            loop {
+                let mut name = rng.word(1..=8);
+                if is_file && rng.chance(1, 2) {
+                    name = format!("{name}.{}", rng.word(1..=3));
+                }
+
+                let mut taken = (directory.children.iter())
+                    .map(|&child| &directories[child].name)
+                    .chain(directory.files.iter().map(|(_, name)| name));
+                if !taken.any(|taken| *taken == name) {
+                    return name;
+                }
             }
+        }
 
-            let (child_size, child_over_100000) = walk_directory_silver(remaining_input);
-            sum_under_100000 += child_over_100000;
-            if child_size <= 100000 {
-                sum_under_100000 += child_size;
+        fn explore(
+            rng: &mut generate::Rng,
+            directories: &[Directory],
+            index: usize,
+            lines: &mut Vec<String>,
+        ) {
+            let directory = &directories[index];
+            let mut listing: Vec<String> = (directory.children.iter())
+                .map(|&child| format!("dir {}", directories[child].name))
+                .chain(
+                    directory
+                        .files
+                        .iter()
+                        .map(|(size, name)| format!("{size} {name}")),
+                )
+                .collect();
+            rng.shuffle(&mut listing);
+            lines.push("$ ls".to_string());
+            lines.extend(listing);
+
+            for &child in &directory.children {
+                lines.push(format!("$ cd {}", directories[child].name));
+                explore(rng, directories, child, lines);
+                lines.push("$ cd ..".to_string());
             }
-            my_size += child_size;
-        } else if !line.starts_with(b"dir ") && !line.starts_with(b"$") {
-            // file
-            let (size, _) = split_once_2(line, b' ');
-            let size = fast_parse_int_from_bytes(size);
-            my_size += size;
         }
+
+        // every directory is inside one that comes before it
+        let count = size.max(1);
+        let mut directories: Vec<Directory> = (0..count).map(|_| Directory::default()).collect();
+        for index in 1..count {
+            let parent = rng.usize(0..=index - 1);
+            directories[index].name = new_name(rng, &directories, parent, false);
+            directories[parent].children.push(index);
+        }
+
+        // small files add up to at most 30M, and a large file in `/` fills the disk
+        let max_file_size = (30_000_000 / count / 3).clamp(1, 300_000);
+        let mut total_size = 0;
+        for index in 0..count {
+            for _ in 0..rng.usize(0..=3) {
+                let name = new_name(rng, &directories, index, true);
+                let size = rng.usize(1..=max_file_size);
+                total_size += size;
+                directories[index].files.push((size, name));
+            }
+        }
+        let name = new_name(rng, &directories, 0, true);
+        let size = rng.usize(40_000_001 - total_size..=70_000_000 - total_size);
+        directories[0].files.push((size, name));
+
+        let mut lines = vec!["$ cd /".to_string()];
+        explore(rng, &directories, 0, &mut lines);
+
+        // like the puzzle input, the transcript ends in the last directory that was explored
+        while lines.last().map(String::as_str) == Some("$ cd ..") {
+            lines.pop();
+        }
+        Some(lines.join("\n"))
     }
 }
 
-fn walk_directory_gold<'a>(
-    remaining_input: &mut impl Iterator<Item = &'a [u8]>,
-    vec: &mut Vec<usize>,
-) -> usize {
-    let mut my_size = 0;
-    loop {
-        let Some(line) = remaining_input.next() else {
-            vec.push(my_size);
-            return my_size;
-        };
-
-        if line.starts_with(b"$ cd") {
-            if line[5] == b'.' {
-                vec.push(my_size);
-                return my_size;
-            }
-            let child_size = walk_directory_gold(remaining_input, vec);
-            my_size += child_size;
-        } else if !line.starts_with(b"dir ") && !line.starts_with(b"$") {
-            // file
-            let (size, _) = split_once_2(line, b' ');
-            let size = fast_parse_int_from_bytes(size);
-            my_size += size;
+impl SolutionGold<usize, usize> for Day {
+    fn solve_gold(sizes: &Vec<usize>) -> usize {
+        Self::try_solve_gold(sizes).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Fails if the files do not fit on the disk, or if there is enough free space already.
+    fn try_solve_gold(sizes: &Vec<usize>) -> Result<usize, SolveError> {
+        const DISK_SIZE: usize = 70_000_000;
+        const DISK_SIZE_MAX_IN_USE: usize = DISK_SIZE - 30_000_000;
+
+        let size_in_use = *sizes.last().expect("there is always a root directory");
+        debug_assert_eq!(*sizes.iter().max().unwrap(), size_in_use);
+        if size_in_use > DISK_SIZE {
+            return Err(SolveError::invalid("the files do not fit on the disk"));
         }
+        if size_in_use <= DISK_SIZE_MAX_IN_USE {
+            return Err(SolveError::invalid("there is enough free space already"));
+        }
+
+        let size_over_limit = size_in_use - DISK_SIZE_MAX_IN_USE;
+        Ok(*sizes
+            .iter()
+            .filter(|&&x| x > size_over_limit)
+            .min()
+            .unwrap())
+    }
+}
+
+/// Finishes the listing of the current directory, and adds its size to its parent.
+fn close_directory(open: &mut Vec<usize>, sizes: &mut Vec<usize>) -> Result<(), SolveError> {
+    let size = open.pop().expect("there is a directory to close");
+    sizes.push(size);
+    if let Some(parent) = open.last_mut() {
+        *parent = parent
+            .checked_add(size)
+            .ok_or_else(|| SolveError::invalid("the files are too large"))?;
     }
+    Ok(())
 }
 
 #[test]
@@ -113,4 +220,48 @@ fn test_gold_real() {
     assert_eq!(5974547, output);
 }
 
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen("$ ls", "$ dir", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 2,
+            column: 1,
+            message: "expected `$ cd` or `$ ls`".to_string()
+        }
+    );
+
+    // these used to index out of bounds or overflow
+    for input in ["", "x", "$ cd /\n$ cd", "$ cd /\n$ cd ..", "$ cd /\nx.txt"] {
+        assert!(
+            matches!(
+                Day::try_calculate_silver(input),
+                Err(SolveError::Parse { .. })
+            ),
+            "{input:?}"
+        );
+    }
+
+    // the sizes of the directories used to overflow
+    let input = format!("$ cd /\n$ ls\n{0} a\n{0} b", usize::MAX);
+    assert!(matches!(
+        Day::try_calculate_silver(&input),
+        Err(SolveError::Invalid(_))
+    ));
+
+    // nesting deeply used to overflow the stack
+    let input = format!("$ cd /{}", "\n$ cd a".repeat(1_000_000));
+    assert_eq!(Day::try_calculate_silver(&input), Ok(0));
+
+    // gold has no answer if there is enough free space, or if the files do not fit
+    for size in [1, 80_000_000] {
+        let input = format!("$ cd /\n$ ls\n{size} a");
+        assert!(matches!(
+            Day::try_calculate_gold(&input),
+            Err(SolveError::Invalid(_))
+        ));
+    }
+}
+
 // 45349983 too high
@@ -1,3 +1,5 @@
+use crate::grid::Grid;
+
 use super::*;
 
 pub struct Day;
@@ -8,9 +10,20 @@ impl SolutionSilver<usize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> usize {
+    type Parsed<'a> = Grid<u8>;
+
+    fn parse(input: &str) -> Result<Grid<u8>, SolveError> {
         // TODO: could also take a transposed copy? probably slower though
-        let (trees, stride) = parse_input_contiguous(input);
+        let grid = Grid::parse(input, |b| (b as char).to_digit(10).map(|d| d as u8))?;
+        if grid.width() != grid.height() || grid.width() < 2 {
+            return Err(SolveError::invalid("expected a square patch of trees"));
+        }
+
+        Ok(grid)
+    }
+
+    fn solve_silver(grid: &Grid<u8>) -> usize {
+        let (trees, stride) = (grid.cells(), grid.width());
 
         let outer_ring_size = (stride - 1) * 4;
 
@@ -84,12 +97,24 @@ impl SolutionSilver<usize> for Day {
 
         visible_trees + outer_ring_size
     }
+
+    /// Generates a square patch of trees that is `size` trees wide (at least 2).
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let size = size.max(2);
+        let rows: Vec<String> = (0..size)
+            .map(|_| {
+                (0..size)
+                    .map(|_| (b'0' + rng.usize(0..=9) as u8) as char)
+                    .collect()
+            })
+            .collect();
+        Some(rows.join("\n"))
+    }
 }
 
 impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
-        // let (trees, width, height) = parse_input(input);
-        let (trees, stride) = parse_input_contiguous(input);
+    fn solve_gold(grid: &Grid<u8>) -> usize {
+        let (trees, stride) = (grid.cells(), grid.width());
 
         let mut highest_score = 0;
 
@@ -162,24 +187,6 @@ impl SolutionGold<usize, usize> for Day {
     }
 }
 
-fn parse_input_contiguous(input: &str) -> (Vec<u8>, usize) {
-    let bytes = input.as_bytes();
-    let stride = bytes.iter().position(|&b| b == b'\n').unwrap();
-    let mut trees = vec![0; stride * stride]; // TODO: assuming square patch here
-    let mut bytes_index = 0;
-    for line in 0..stride {
-        let start_line = line * stride;
-        for vec_index in 0..stride {
-            trees[start_line + vec_index] = bytes[bytes_index] - b'0';
-            bytes_index += 1;
-        }
-
-        bytes_index += 1; // skip newline
-    }
-
-    (trees, stride)
-}
-
 #[test]
 fn test_silver_sample() {
     let output = Day::calculate_silver(Day::INPUT_SAMPLE);
@@ -203,3 +210,21 @@ fn test_gold_real() {
     let output = Day::calculate_gold(Day::INPUT_REAL);
     assert_eq!(172224, output);
 }
+
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen("30373", "30x73", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 1,
+            column: 3,
+            message: "unexpected character `x`".to_string()
+        }
+    );
+
+    // found by fuzzing, these used to overflow while converting a character to a height
+    for input in ["50\n-1", "2569)"] {
+        assert!(Day::try_calculate_silver(input).is_err(), "{input:?}");
+    }
+}
@@ -1,6 +1,6 @@
 use ahash::AHashSet;
 
-use crate::utils::{fast_parse_int_from_bytes, split_once};
+use crate::utils::Cursor;
 
 use super::*;
 
@@ -11,22 +11,47 @@ impl SolutionSilver<usize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> usize {
+    type Parsed<'a> = Vec<(u8, usize)>;
+
+    /// Parses the input into the direction and length of each motion.
+    fn parse(input: &str) -> Result<Vec<(u8, usize)>, SolveError> {
+        let mut cursor = Cursor::new(input);
+        let mut motions = vec![];
+        let mut steps = 0usize;
+
+        while !cursor.is_empty() {
+            let direction = match cursor.peek() {
+                Some(direction @ (b'L' | b'R' | b'U' | b'D')) => direction,
+                _ => return Err(cursor.error("expected a direction (`L`, `R`, `U` or `D`)")),
+            };
+            cursor.expect(&[direction, b' '])?;
+            let length = cursor.uint()?;
+
+            if !cursor.is_empty() {
+                cursor.expect(b"\n")?;
+            }
+
+            steps = steps.saturating_add(length);
+            if steps > MAX_STEPS {
+                let message = format!("the head moves more than {MAX_STEPS} steps");
+                return Err(SolveError::invalid(message));
+            }
+
+            motions.push((direction, length));
+        }
+
+        Ok(motions)
+    }
+
+    fn solve_silver(motions: &Vec<(u8, usize)>) -> usize {
         let mut visited = AHashSet::default();
-        let mut bytes = input.as_bytes();
 
         let mut head_positions = (0, 0);
         let mut tail_position = (0, 0);
 
         visited.insert(tail_position);
 
-        while !bytes.is_empty() {
-            let line;
-            (line, bytes) = split_once(bytes, b'\n').unwrap_or((bytes, b""));
-
-            let direction = line[0];
-            let length = fast_parse_int_from_bytes(&line[2..]);
-
+        for &(direction, length) in motions {
             for _ in 0..length {
                 head_positions = match direction {
                     b'L' => (head_positions.0 - 1, head_positions.1),
@@ -46,24 +71,31 @@ impl SolutionSilver<usize> for Day {
 
         visited.len()
     }
+
+    /// Generates `size` motions of the head.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let motions: Vec<String> = (0..size.max(1))
+            .map(|_| {
+                format!(
+                    "{} {}",
+                    rng.choose(&['L', 'R', 'U', 'D']),
+                    rng.usize(1..=20)
+                )
+            })
+            .collect();
+        Some(motions.join("\n"))
+    }
 }
 
 impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
+    fn solve_gold(motions: &Vec<(u8, usize)>) -> usize {
         let mut visited = AHashSet::default();
-        let mut bytes = input.as_bytes();
 
         let mut snake_positions = [(0, 0); 10];
 
         visited.insert(snake_positions[9]);
 
-        while !bytes.is_empty() {
-            let line;
-            (line, bytes) = split_once(bytes, b'\n').unwrap_or((bytes, b""));
-
-            let direction = line[0];
-            let length = fast_parse_int_from_bytes(&line[2..]);
-
+        for &(direction, length) in motions {
             for _ in 0..length {
                 snake_positions[0] = match direction {
                     b'L' => (snake_positions[0].0 - 1, snake_positions[0].1),
@@ -89,6 +121,10 @@ impl SolutionGold<usize, usize> for Day {
     }
 }
 
+/// The most steps the head can take in total. The simulation takes a step at a time, so this
+/// rejects inputs that would take seconds to solve. Real inputs take about 11000 steps.
+const MAX_STEPS: usize = 1 << 20;
+
 fn calculate_tail_position(head: (isize, isize), tail: (isize, isize)) -> (isize, isize) {
     let tail_relative = (tail.0 - head.0, tail.1 - head.1);
 
@@ -138,3 +174,77 @@ fn test_gold_real() {
     let output = Day::calculate_gold(Day::INPUT_REAL);
     assert_eq!(2541, output);
 }
+
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen("U 4", "2 4", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 2,
+            column: 1,
+            message: "expected a direction (`L`, `R`, `U` or `D`)".to_string()
+        }
+    );
+
+    // found by fuzzing, these used to index out of bounds or hit `unreachable!`
+    for input in ["l", "RSS", "R 4\nUD", "R 4\nU 4\nL 3\n\n0 4"] {
+        assert!(
+            matches!(
+                Day::try_calculate_gold(input),
+                Err(SolveError::Parse { .. })
+            ),
+            "{input:?}"
+        );
+    }
+
+    // the second one was found by fuzzing, and used to take several seconds
+    for input in [format!("R {MAX_STEPS}\nL 1"), "D 10000001\nR 2".to_string()] {
+        assert!(
+            matches!(
+                Day::try_calculate_silver(&input),
+                Err(SolveError::Invalid(_))
+            ),
+            "{input:?}"
+        );
+    }
+}
+
+/// Moves every knot of a ten-knot rope towards the previous one until they touch.
+#[cfg(test)]
+fn calculate_naive(input: &str) -> (usize, usize) {
+    use std::collections::HashSet;
+
+    let mut knots = [(0isize, 0isize); 10];
+    let (mut silver, mut gold) = (HashSet::from([knots[1]]), HashSet::from([knots[9]]));
+    for line in input.lines() {
+        let (direction, length) = line.split_once(' ').unwrap();
+        let step = match direction {
+            "L" => (-1, 0),
+            "R" => (1, 0),
+            "U" => (0, -1),
+            _ => (0, 1),
+        };
+
+        for _ in 0..length.parse::<usize>().unwrap() {
+            knots[0] = (knots[0].0 + step.0, knots[0].1 + step.1);
+            for i in 1..knots.len() {
+                let (dx, dy) = (knots[i - 1].0 - knots[i].0, knots[i - 1].1 - knots[i].1);
+                if dx.abs() > 1 || dy.abs() > 1 {
+                    knots[i] = (knots[i].0 + dx.signum(), knots[i].1 + dy.signum());
+                }
+            }
+            silver.insert(knots[1]);
+            gold.insert(knots[9]);
+        }
+    }
+    (silver.len(), gold.len())
+}
+
+#[test]
+fn test_differential() {
+    crate::property::check(1000, crate::property::input::<Day, _>, |input| {
+        let actual = (Day::calculate_silver(input), Day::calculate_gold(input));
+        assert_eq!(calculate_naive(input), actual);
+    });
+}
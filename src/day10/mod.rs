@@ -1,4 +1,4 @@
-use crate::utils::{fast_parse_int_from_bytes, split_once};
+use crate::utils::Cursor;
 
 use super::*;
 
@@ -10,74 +10,90 @@ impl SolutionSilver<isize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> isize {
-        let mut cycle = 0;
-        let mut x_register = 1;
-        let mut signal_strength = 0;
+    type Parsed<'a> = Vec<Instruction>;
 
-        let mut bytes = input.as_bytes();
-        while !bytes.is_empty() {
-            let instruction;
-            (instruction, bytes) = bytes.split_at(4);
-
-            if instruction[0] == b'n' {
-                // noop
-                cycle += 1;
-
-                if ((cycle - 20) % 40) == 0 {
-                    signal_strength += cycle * x_register;
-                }
+    fn parse(input: &str) -> Result<Vec<Instruction>, SolveError> {
+        let mut instructions = vec![];
 
-                if !bytes.is_empty() {
-                    debug_assert_eq!(bytes[0], b'\n');
-                    bytes = &bytes[1..]; // skip newline
-                }
+        let mut cursor = Cursor::new(input);
+        while !cursor.is_empty() {
+            if cursor.eat(b"noop") {
+                instructions.push(Instruction::Noop);
             } else {
-                // addx
-                let operand;
-                bytes = &bytes[1..]; // skip space
-                (operand, bytes) = split_once(bytes, b'\n').unwrap_or((bytes, b""));
+                cursor.expect(b"addx ")?;
+                instructions.push(Instruction::AddX(cursor.int()?));
+            }
 
-                let is_negative = operand[0] == b'-';
+            if !cursor.is_empty() {
+                cursor.expect(b"\n")?;
+            }
+        }
 
-                let operand = if is_negative {
-                    -(fast_parse_int_from_bytes(&operand[1..]) as isize)
-                } else {
-                    fast_parse_int_from_bytes(operand) as isize
-                };
+        Ok(instructions)
+    }
 
-                cycle += 1;
+    fn solve_silver(instructions: &Vec<Instruction>) -> isize {
+        let mut cycle = 0;
+        let mut x_register = 1;
+        let mut signal_strength = 0;
+
+        for &instruction in instructions {
+            match instruction {
+                Instruction::Noop => {
+                    cycle += 1;
 
-                if ((cycle - 20) % 40) == 0 {
-                    signal_strength += cycle * x_register;
+                    if ((cycle - 20) % 40) == 0 {
+                        signal_strength += cycle * x_register;
+                    }
                 }
+                Instruction::AddX(operand) => {
+                    cycle += 1;
 
-                cycle += 1;
+                    if ((cycle - 20) % 40) == 0 {
+                        signal_strength += cycle * x_register;
+                    }
 
-                if ((cycle - 20) % 40) == 0 {
-                    signal_strength += cycle * x_register;
-                }
+                    cycle += 1;
 
-                x_register += operand;
+                    if ((cycle - 20) % 40) == 0 {
+                        signal_strength += cycle * x_register;
+                    }
 
-                debug_assert!(bytes.is_empty() || bytes[0] != b'\n');
+                    x_register += operand;
+                }
             }
         }
 
         signal_strength
     }
+
+    /// Generates a program of `size` instructions. Programs of more than 120 instructions can run
+    /// for more than 240 cycles, and draw over the display more than once.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let lines: Vec<String> = (0..size.max(1))
+            .map(|_| match rng.chance(1, 3) {
+                true => "noop".to_string(),
+                false => format!("addx {}", rng.isize(-20..=20)),
+            })
+            .collect();
+        Some(lines.join("\n"))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Instruction {
+    Noop,
+    AddX(isize),
 }
 
 const WIDTH: usize = 40;
 const HEIGHT: usize = 6;
 impl SolutionGold<isize, CrtDisplay<240, 40>> for Day {
-    fn calculate_gold(input: &str) -> CrtDisplay<240, 40> {
+    fn solve_gold(instructions: &Vec<Instruction>) -> CrtDisplay<240, 40> {
         let mut cycle = 0usize;
         let mut x_register = 1isize;
         let mut display = CrtDisplay::<{ WIDTH * HEIGHT }, WIDTH>([false; WIDTH * HEIGHT]);
 
-        let mut bytes = input.as_bytes();
-
         let draw = |display: &mut CrtDisplay<240, 40>, cycle: usize, x: isize| {
             let abs_diff = ((cycle % WIDTH) as isize).abs_diff(x);
             if abs_diff == 0 || abs_diff == 1 {
@@ -85,42 +101,21 @@ impl SolutionGold<isize, CrtDisplay<240, 40>> for Day {
             }
         };
 
-        while !bytes.is_empty() {
-            let instruction;
-            (instruction, bytes) = bytes.split_at(4);
-
-            if instruction[0] == b'n' {
-                // noop
-                draw(&mut display, cycle, x_register);
-                cycle += 1;
-
-                if !bytes.is_empty() {
-                    debug_assert_eq!(bytes[0], b'\n');
-                    bytes = &bytes[1..]; // skip newline
+        for &instruction in instructions {
+            match instruction {
+                Instruction::Noop => {
+                    draw(&mut display, cycle, x_register);
+                    cycle += 1;
                 }
-            } else {
-                // addx
-                let operand;
-                bytes = &bytes[1..]; // skip space
-                (operand, bytes) = split_once(bytes, b'\n').unwrap_or((bytes, b""));
-
-                let is_negative = operand[0] == b'-';
-
-                let operand = if is_negative {
-                    -(fast_parse_int_from_bytes(&operand[1..]) as isize)
-                } else {
-                    fast_parse_int_from_bytes(operand) as isize
-                };
-
-                draw(&mut display, cycle, x_register);
-                cycle += 1;
-
-                draw(&mut display, cycle, x_register);
-                cycle += 1;
+                Instruction::AddX(operand) => {
+                    draw(&mut display, cycle, x_register);
+                    cycle += 1;
 
-                x_register += operand;
+                    draw(&mut display, cycle, x_register);
+                    cycle += 1;
 
-                debug_assert!(bytes.is_empty() || bytes[0] != b'\n');
+                    x_register += operand;
+                }
             }
         }
 
@@ -258,3 +253,55 @@ fn test_crt_display() {
         )
     );
 }
+
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen("addx -11", "addx 11-", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 2,
+            column: 8,
+            message: "expected `\\n`".to_string()
+        }
+    );
+
+    let input = Day::INPUT_SAMPLE.replacen("noop", "nop", 1);
+    assert!(matches!(
+        Day::try_calculate_silver(&input),
+        Err(SolveError::Parse { .. })
+    ));
+}
+
+/// Lists the value of the register during every cycle and reads both answers from it.
+#[cfg(test)]
+fn calculate_naive(input: &str) -> (isize, Vec<bool>) {
+    let mut x_during = vec![];
+    let mut x_register = 1;
+    for line in input.lines() {
+        x_during.push(x_register);
+        if let Some(operand) = line.strip_prefix("addx ") {
+            x_during.push(x_register);
+            x_register += operand.parse::<isize>().unwrap();
+        }
+    }
+
+    let signal_strength = (20..=x_during.len())
+        .step_by(40)
+        .map(|cycle| cycle as isize * x_during[cycle - 1])
+        .sum();
+    let mut pixels = vec![false; WIDTH * HEIGHT];
+    for (i, x) in x_during.into_iter().enumerate() {
+        pixels[i] = ((i % WIDTH) as isize - x).abs() <= 1;
+    }
+    (signal_strength, pixels)
+}
+
+#[test]
+fn test_differential() {
+    crate::property::check(2000, crate::property::input::<Day, _>, |input| {
+        let (signal_strength, pixels) = calculate_naive(input);
+        assert_eq!(signal_strength, Day::calculate_silver(input));
+        assert_eq!(pixels, Day::calculate_gold(input).0);
+    });
+}
@@ -1,5 +1,3 @@
-use crate::utils::{fast_parse_int_from_bytes, split_once, split_once_2};
-
 use super::*;
 
 pub struct Day;
@@ -9,15 +7,26 @@ impl SolutionSilver<usize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> usize {
-        let mut monkeys = Monkey::parse_input(input.as_bytes());
+    type Parsed<'a> = Vec<Monkey>;
+
+    fn parse(input: &str) -> Result<Vec<Monkey>, SolveError> {
+        Monkey::parse_input(input)
+    }
+
+    fn solve_silver(monkeys: &Vec<Monkey>) -> usize {
+        Self::try_solve_silver(monkeys).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Fails if a worry level or the monkey business does not fit in a `usize`.
+    fn try_solve_silver(monkeys: &Vec<Monkey>) -> Result<usize, SolveError> {
+        let mut monkeys = monkeys.clone();
 
         for _ in 0..20 {
             for i in 0..monkeys.len() {
                 for item_idx in 0..monkeys[i].items.len() {
                     let monkey = &monkeys[i];
                     let item = monkey.items[item_idx];
-                    let evaluated = monkey.operation.evaluate(item);
+                    let evaluated = monkey.operation.evaluate(item)?;
                     let evaluated = evaluated / 3;
                     let divided = evaluated % monkey.division_check;
                     let target_monkey = if divided == 0 {
@@ -46,22 +55,66 @@ impl SolutionSilver<usize> for Day {
             (acc.0, acc.1)
         });
 
-        max1 * max2
+        monkey_business(max1, max2)
+    }
+
+    /// Generates 2 to 8 monkeys that hold `size` items together. Every monkey checks a different
+    /// prime, like in the puzzle input. Only one monkey multiplies and none of them squares, so the
+    /// worry levels of the silver part cannot overflow.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let count = rng.usize(2..=8);
+        let mut primes = [2, 3, 5, 7, 11, 13, 17, 19, 23];
+        rng.shuffle(&mut primes);
+
+        let mut items = vec![vec![]; count];
+        for _ in 0..size {
+            items[rng.usize(0..=count - 1)].push(rng.usize(50..=99).to_string());
+        }
+
+        let multiplying = rng.usize(0..=count - 1);
+        let monkeys: Vec<String> = (0..count)
+            .map(|index| {
+                let operation = match index == multiplying {
+                    true => format!("* {}", rng.usize(2..=19)),
+                    false => format!("+ {}", rng.usize(1..=8)),
+                };
+                let mut target = || (index + rng.usize(1..=count - 1)) % count;
+                let (if_true, if_false) = (target(), target());
+
+                format!(
+                    "Monkey {index}:\n  Starting items: {}\n  Operation: new = old {operation}\n  \
+                     Test: divisible by {}\n    If true: throw to monkey {if_true}\n    \
+                     If false: throw to monkey {if_false}",
+                    items[index].join(", "),
+                    primes[index]
+                )
+            })
+            .collect();
+        Some(monkeys.join("\n\n"))
     }
 }
 
 impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
-        let mut monkeys = Monkey::parse_input(input.as_bytes());
+    fn solve_gold(monkeys: &Vec<Monkey>) -> usize {
+        Self::try_solve_gold(monkeys).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Fails if the product of the divisors, a worry level or the monkey business does not fit in
+    /// a `usize`.
+    fn try_solve_gold(monkeys: &Vec<Monkey>) -> Result<usize, SolveError> {
+        let mut monkeys = monkeys.clone();
 
-        let gcd: usize = monkeys.iter().map(|m| m.division_check).product();
+        let gcd = monkeys
+            .iter()
+            .try_fold(1usize, |product, m| product.checked_mul(m.division_check))
+            .ok_or_else(|| SolveError::invalid("the product of the divisors overflows"))?;
 
         for _ in 0..10000 {
             for i in 0..monkeys.len() {
                 for item_idx in 0..monkeys[i].items.len() {
                     let monkey = &monkeys[i];
                     let item = monkey.items[item_idx];
-                    let evaluated = monkey.operation.evaluate(item);
+                    let evaluated = monkey.operation.evaluate(item)?;
                     let evaluated = evaluated % gcd;
                     let divided = evaluated % monkey.division_check;
                     let target_monkey = if divided == 0 {
@@ -89,11 +142,17 @@ impl SolutionGold<usize, usize> for Day {
 
             (acc.0, acc.1)
         });
-        max1 * max2
+        monkey_business(max1, max2)
     }
 }
 
-struct Monkey {
+fn monkey_business(max1: usize, max2: usize) -> Result<usize, SolveError> {
+    max1.checked_mul(max2)
+        .ok_or_else(|| SolveError::invalid("the monkey business overflows"))
+}
+
+#[derive(Clone)]
+pub struct Monkey {
     items: Vec<usize>,
     operation: Operation,
     division_check: usize,
@@ -103,75 +162,94 @@ struct Monkey {
 }
 
 impl Monkey {
-    pub fn parse_input(mut bytes: &[u8]) -> Vec<Monkey> {
-        let mut monkeys = vec![];
-
-        // parse all monkeys
-        loop {
-            debug_assert_eq!(&bytes[.."Monkey ".len()], b"Monkey ");
-            debug_assert_eq!((bytes["Monkey ".len()] - b'0') as usize, monkeys.len());
-            let monkey;
-            (monkey, bytes) = Monkey::parse(bytes);
-            monkeys.push(monkey);
-
-            if bytes.is_empty() {
-                return monkeys;
+    pub fn parse_input(input: &str) -> Result<Vec<Monkey>, SolveError> {
+        let monkeys = input
+            .trim_end()
+            .split("\n\n")
+            .enumerate()
+            .map(|(index, block)| Monkey::parse(input, block, index))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // make sure the simulation cannot index out of bounds or divide by zero
+        for (index, monkey) in monkeys.iter().enumerate() {
+            if monkey.division_check == 0 {
+                return Err(SolveError::invalid(format!(
+                    "monkey {index} checks divisibility by 0"
+                )));
+            }
+            for target in [monkey.target_true, monkey.target_false] {
+                if target >= monkeys.len() || target == index {
+                    return Err(SolveError::invalid(format!(
+                        "monkey {index} throws to invalid monkey {target}"
+                    )));
+                }
             }
-
-            bytes = &bytes[2..]; // skip 2 newlines
         }
-    }
 
-    pub fn parse(mut bytes: &[u8]) -> (Monkey, &[u8]) {
-        debug_assert!(&bytes[0..7] == b"Monkey ");
-        bytes = &bytes[(12 + "Starting items".len())..];
-        let mut nums;
-        (nums, bytes) = split_once(bytes, b'\n').unwrap();
+        Ok(monkeys)
+    }
 
-        let mut items = Vec::with_capacity(24);
-        let mut num_bytes;
-        while !nums.is_empty() {
-            nums = &nums[1..];
-            (num_bytes, nums) = split_once_2(nums, b',');
-            num_bytes = &num_bytes[1..];
+    /// Parses a single monkey from `block`, which is a subslice of `input`.
+    pub fn parse(input: &str, block: &str, index: usize) -> Result<Monkey, SolveError> {
+        let mut lines = block.lines();
+        let mut next_line = |prefix: &str| {
+            let line = lines.next().unwrap_or(&block[block.len()..]);
+            line.strip_prefix(prefix)
+                .ok_or_else(|| SolveError::at(input, line, format!("expected `{prefix}`")))
+        };
+
+        let header = next_line("Monkey ")?;
+        let number = header
+            .strip_suffix(':')
+            .ok_or_else(|| SolveError::at(input, header, "expected `N:`"))?;
+        if parse_number(input, number)? != index {
+            return Err(SolveError::at(
+                input,
+                number,
+                format!("expected monkey {index}"),
+            ));
+        }
 
-            items.push(fast_parse_int_from_bytes(num_bytes));
+        let items = next_line("  Starting items: ")?;
+        let items = if items.is_empty() {
+            vec![]
+        } else {
+            items
+                .split(", ")
+                .map(|item| parse_number(input, item))
+                .collect::<Result<_, _>>()?
+        };
+
+        let operation = Operation::parse(input, next_line("  Operation: new = old ")?)?;
+        let division_check = parse_number(input, next_line("  Test: divisible by ")?)?;
+        let target_true = parse_number(input, next_line("    If true: throw to monkey ")?)?;
+        let target_false = parse_number(input, next_line("    If false: throw to monkey ")?)?;
+
+        if let Some(line) = lines.next() {
+            return Err(SolveError::at(
+                input,
+                line,
+                "expected the end of the monkey",
+            ));
         }
 
-        debug_assert!(&bytes[0..13] == b"  Operation: ");
-        bytes = &bytes["  Operation: new = old ".len()..];
-        let op_bytes;
-        (op_bytes, bytes) = split_once(bytes, b'\n').unwrap();
-        let operation = Operation::parse(op_bytes);
-
-        debug_assert!(&bytes[0..6] == b"  Test");
-        bytes = &bytes["  Test: divisible by ".len()..];
-        let num_bytes;
-        (num_bytes, bytes) = split_once(bytes, b'\n').unwrap();
-        let division_check = fast_parse_int_from_bytes(num_bytes);
-
-        debug_assert!(&bytes[0..12] == b"    If true:");
-        let target_true = (bytes["    If true: throw to monkey ".len()] - b'0') as usize;
-        bytes = &bytes["    If true: throw to monkey N\n".len()..];
-
-        debug_assert!(&bytes[0..13] == b"    If false:");
-        let target_false = (bytes["    If false: throw to monkey ".len()] - b'0') as usize;
-        bytes = &bytes["    If false: throw to monkey N".len()..];
-
-        (
-            Monkey {
-                items,
-                operation,
-                division_check,
-                target_true,
-                target_false,
-                inspect_count: 0,
-            },
-            bytes,
-        )
+        Ok(Monkey {
+            items,
+            operation,
+            division_check,
+            target_true,
+            target_false,
+            inspect_count: 0,
+        })
     }
 }
 
+fn parse_number(input: &str, text: &str) -> Result<usize, SolveError> {
+    text.parse()
+        .map_err(|_| SolveError::at(input, text, format!("expected a number, found `{text}`")))
+}
+
+#[derive(Clone)]
 enum Operation {
     AddConstant(usize),
     MultiplyConstant(usize),
@@ -179,24 +257,32 @@ enum Operation {
 }
 
 impl Operation {
-    pub fn parse(bytes: &[u8]) -> Self {
-        let is_mult = bytes[0] == b'*';
-        let on_self = bytes[2] == b'o';
-
-        match (is_mult, on_self) {
-            (false, false) => Self::AddConstant(fast_parse_int_from_bytes(&bytes[2..])),
-            (true, false) => Self::MultiplyConstant(fast_parse_int_from_bytes(&bytes[2..])),
-            (false, true) => Self::MultiplyConstant(2),
-            (true, true) => Self::Square,
+    /// Parses the part after `new = old `, which is a subslice of `input`.
+    pub fn parse(input: &str, text: &str) -> Result<Self, SolveError> {
+        let Some((operator, operand)) = text.split_once(' ') else {
+            return Err(SolveError::at(input, text, "expected an operator and an operand"));
+        };
+
+        match (operator, operand) {
+            ("+", "old") => Ok(Self::MultiplyConstant(2)),
+            ("*", "old") => Ok(Self::Square),
+            ("+", _) => Ok(Self::AddConstant(parse_number(input, operand)?)),
+            ("*", _) => Ok(Self::MultiplyConstant(parse_number(input, operand)?)),
+            _ => Err(SolveError::at(
+                input,
+                operator,
+                format!("unknown operator `{operator}`"),
+            )),
         }
     }
 
-    fn evaluate(&self, item: usize) -> usize {
-        match self {
-            Operation::AddConstant(c) => item + c,
-            Operation::MultiplyConstant(c) => item * c,
-            Operation::Square => item * item,
-        }
+    fn evaluate(&self, item: usize) -> Result<usize, SolveError> {
+        let new = match self {
+            Operation::AddConstant(c) => item.checked_add(*c),
+            Operation::MultiplyConstant(c) => item.checked_mul(*c),
+            Operation::Square => item.checked_mul(item),
+        };
+        new.ok_or_else(|| SolveError::invalid("the worry level of an item overflows"))
     }
 }
 
@@ -223,3 +309,52 @@ fn test_gold_real() {
     let output = Day::calculate_gold(Day::INPUT_REAL);
     assert_eq!(17408399184, output);
 }
+
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen("Test: divisible by 23", "Test: divisible by x", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 4,
+            column: 22,
+            message: "expected a number, found `x`".to_string()
+        }
+    );
+
+    let input = Day::INPUT_SAMPLE.replacen("Operation", "Operator", 1);
+    assert!(matches!(
+        Day::try_calculate_silver(&input),
+        Err(SolveError::Parse {
+            line: 3,
+            column: 1,
+            ..
+        })
+    ));
+
+    let input = Day::INPUT_SAMPLE.replacen("throw to monkey 2", "throw to monkey 9", 1);
+    assert!(matches!(
+        Day::try_calculate_gold(&input),
+        Err(SolveError::Invalid(_))
+    ));
+}
+
+#[test]
+fn test_overflow() {
+    // found by fuzzing, this used to panic on overflow, or wrap around in release builds
+    let input = Day::INPUT_SAMPLE.replacen("79, 98", "42949672969", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input),
+        Err(SolveError::invalid("the worry level of an item overflows"))
+    );
+    // the gold part keeps the worry levels small
+    assert!(Day::try_calculate_gold(&input).is_ok());
+
+    let input =
+        Day::INPUT_SAMPLE.replacen("divisible by 23", "divisible by 1000000000000000000", 1);
+    assert!(Day::try_calculate_silver(&input).is_ok());
+    assert_eq!(
+        Day::try_calculate_gold(&input),
+        Err(SolveError::invalid("the product of the divisors overflows"))
+    );
+}
@@ -1,13 +1,7 @@
-use std::{
-    collections::VecDeque,
-    fmt::{self, Debug, Formatter},
-    rc::Rc,
-};
-
-use ahash::HashSet;
-use rayon::prelude::*;
 use tinyvec::ArrayVec;
 
+use crate::{grid::Grid, search};
+
 use super::*;
 
 pub struct Day;
@@ -18,162 +12,132 @@ impl SolutionSilver<usize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> usize {
+    type Parsed<'a> = Heightmap;
+
+    fn parse(input: &str) -> Result<Heightmap, SolveError> {
+        let heights = Grid::parse(input, |b| match b {
+            b'a'..=b'z' => Some((b - b'a') as i8),
+            b'S' => Some(-1),
+            b'E' => Some(26),
+            _ => None,
+        })?;
+
+        let find = |height, name| {
+            heights
+                .find(|&h| h == height)
+                .map(|position| heights.index_of(position))
+                .ok_or_else(|| SolveError::invalid(format!("the map has no {name}")))
+        };
+        let (start, end) = (find(-1, "start")?, find(26, "end")?);
+
+        Ok(Heightmap {
+            heights,
+            start,
+            end,
+        })
+    }
+
+    fn solve_silver(heightmap: &Heightmap) -> usize {
+        Self::try_solve_silver(heightmap).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Fails if the end cannot be reached from the start.
+    fn try_solve_silver(heightmap: &Heightmap) -> Result<usize, SolveError> {
         // TODO: can improve this massively, but I hate pathfinding
         // 1. use midpoints for unique chars
-        let stride = input.lines().next().unwrap().len();
-        let vec: Vec<_> = input
-            .as_bytes()
-            .iter()
-            .flat_map(|f| match f {
-                b'a'..=b'z' => Some((f - b'a') as i8),
-                b'S' => Some(-1),
-                b'E' => Some(26),
-                _ => None,
-            })
-            .collect();
-        let height = vec.len() / stride;
-        debug_assert!(vec.len() % stride == 0);
-
-        let start = vec.iter().position(|x| *x == -1).unwrap();
-        let end = vec.iter().position(|x| *x == (26)).unwrap();
-
-        let path = bfs(start, end, |i| {
-            let mut ret = ArrayVec::<[usize; 4]>::default();
-            let x = i % stride;
-            let y = i / stride;
-
-            if x > 0 && vec[i - 1] - vec[i] <= 1 {
-                ret.push(i - 1);
-            }
-            if x < (stride - 1) && vec[i + 1] - vec[i] <= 1 {
-                ret.push(i + 1);
-            }
-            if y > 0 && vec[i - stride] - vec[i] <= 1 {
-                ret.push(i - stride);
-            }
-            if y < (height - 1) && vec[i + stride] - vec[i] <= 1 {
-                ret.push(i + stride);
-            }
-
-            ret
-        });
-
-        let unwrapped = path.unwrap();
-        unwrapped.depth() - 1
+        let Heightmap {
+            heights,
+            start,
+            end,
+        } = heightmap;
+
+        search::bfs(
+            [*start],
+            |&i| climbable_neighbours(heights, i),
+            |&i| i == *end,
+        )
+        .map(|path| path.cost)
+        .ok_or_else(|| SolveError::invalid("there is no path to the end"))
     }
-}
 
-impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
-        // TODO: can improve this massively, but I hate pathfinding
-        // 1. search in reverse rather than brute forcing
-        // 2. early exit if a path is longer than shortest found path, if any (not needed if 1)
-        // 3. use midpoints for unique chars
-        let stride = input.lines().next().unwrap().len();
-        let vec: Vec<_> = input
-            .as_bytes()
-            .iter()
-            .flat_map(|f| match f {
-                b'a'..=b'z' => Some((f - b'a') as i8),
-                b'S' => Some(0), // same as 'a'
-                b'E' => Some(26),
-                _ => None,
-            })
+    /// Generates a square heightmap that is `size` squares wide (at least 6). A path that climbs
+    /// at most one step at a time snakes through the rows from the start to the end, and all other
+    /// squares have random elevations.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let size = size.max(6);
+        let mut rows: Vec<Vec<u8>> = (0..size)
+            .map(|_| (0..size).map(|_| rng.letter() as u8).collect())
             .collect();
-        let height = vec.len() / stride;
-        debug_assert!(vec.len() % stride == 0);
 
-        let starts: Vec<_> = vec
-            .iter()
-            .enumerate()
-            .filter(|(_, x)| **x == 0)
-            .map(|(i, _)| i)
-            .collect();
+        // climbing from `S` over `a` to `z` and then `E` takes at least 28 squares
+        let length = rng.usize(28..=size * size);
+        for i in 0..length {
+            let (y, x) = (i / size, i % size);
+            let x = if y % 2 == 1 { size - 1 - x } else { x };
+            rows[y][x] = match i {
+                0 => b'S',
+                _ if i == length - 1 => b'E',
+                _ => b'a' + ((i - 1) * 26 / (length - 2)) as u8,
+            };
+        }
 
-        let end = vec.iter().position(|x| *x == (26)).unwrap();
-
-        starts
-            .par_iter()
-            .map(|start| {
-                bfs(*start, end, |i| {
-                    let mut ret = ArrayVec::<[usize; 4]>::default();
-                    let x = i % stride;
-                    let y = i / stride;
-
-                    if x > 0 && vec[i - 1] - vec[i] <= 1 {
-                        ret.push(i - 1);
-                    }
-                    if x < (stride - 1) && vec[i + 1] - vec[i] <= 1 {
-                        ret.push(i + 1);
-                    }
-                    if y > 0 && vec[i - stride] - vec[i] <= 1 {
-                        ret.push(i - stride);
-                    }
-                    if y < (height - 1) && vec[i + stride] - vec[i] <= 1 {
-                        ret.push(i + stride);
-                    }
-
-                    ret
-                })
-                .map(|x| x.depth())
-                .unwrap_or(usize::MAX)
-            })
-            .min()
-            .unwrap()
-            - 1
+        let rows: Vec<String> = rows
+            .into_iter()
+            .map(|row| String::from_utf8(row).unwrap())
+            .collect();
+        Some(rows.join("\n"))
     }
 }
 
-// Locke @ https://stackoverflow.com/a/71190546, CC BY-SA 4.0
-fn bfs<F, R>(start: usize, end: usize, expand: F) -> Option<SearchPath>
-where
-    F: Fn(usize) -> R,
-    R: IntoIterator<Item = usize>,
-{
-    let mut visited = HashSet::default();
-    let mut queue = VecDeque::new();
-
-    queue.push_back(SearchPath(start, None));
-    visited.insert(start);
-
-    while let Some(SearchPath(node, path)) = queue.pop_front() {
-        if node == end {
-            return Some(SearchPath(node, path));
-        }
-
-        let path = Rc::new(SearchPath(node, path.clone()));
-
-        for edge in expand(node) {
-            if !visited.contains(&edge) {
-                visited.insert(edge);
-                queue.push_back(SearchPath(edge, Some(path.clone())));
-            }
-        }
+impl SolutionGold<usize, usize> for Day {
+    fn solve_gold(heightmap: &Heightmap) -> usize {
+        Self::try_solve_gold(heightmap).unwrap_or_else(|e| panic!("{e}"))
     }
 
-    None
-}
+    /// Fails if the end cannot be reached from any of the lowest squares.
+    fn try_solve_gold(heightmap: &Heightmap) -> Result<usize, SolveError> {
+        // TODO: can improve this massively, but I hate pathfinding
+        // 1. use midpoints for unique chars
+        let end = heightmap.end;
 
-#[derive(Clone, PartialEq, Eq)]
-pub struct SearchPath(usize, Option<Rc<SearchPath>>);
+        // the start has the same elevation as 'a'
+        let heights = heightmap.heights.map(|&h| h.max(0));
 
-impl SearchPath {
-    fn depth(&self) -> usize {
-        1 + match &self.1 {
-            Some(x) => x.depth(),
-            None => 0,
-        }
+        // search from every lowest point at once
+        let starts = heights
+            .cells()
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| **x == 0)
+            .map(|(i, _)| i);
+
+        search::bfs(
+            starts,
+            |&i| climbable_neighbours(&heights, i),
+            |&i| i == end,
+        )
+        .map(|path| path.cost)
+        .ok_or_else(|| SolveError::invalid("there is no path to the end"))
     }
 }
 
-impl Debug for SearchPath {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        match &self.1 {
-            Some(v) => write!(f, "{:?} -> {:?}", v, &self.0),
-            None => write!(f, "{:?}", &self.0),
-        }
-    }
+/// The indices of the neighbours of the cell at `index` that are at most one higher.
+fn climbable_neighbours(heights: &Grid<i8>, index: usize) -> ArrayVec<[usize; 4]> {
+    let height = heights.cells()[index];
+    heights
+        .neighbours4(heights.position_of(index))
+        .filter(|&position| heights[position] - height <= 1)
+        .map(|position| heights.index_of(position))
+        .collect()
+}
+
+/// The elevations of the map, where the start is `-1` and the end is `26`.
+pub struct Heightmap {
+    heights: Grid<i8>,
+    /// The index of the start in `heights`.
+    start: usize,
+    /// The index of the end in `heights`.
+    end: usize,
 }
 
 #[test]
@@ -199,3 +163,45 @@ fn test_gold_real() {
     let output = Day::calculate_gold(Day::INPUT_REAL);
     assert_eq!(363, output);
 }
+
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen('b', "B", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 1,
+            column: 3,
+            message: "unexpected character `B`".to_string()
+        }
+    );
+
+    assert!(matches!(
+        Day::try_calculate_silver("SE\nz"),
+        Err(SolveError::Parse { .. })
+    ));
+    for input in ["", "Sa"] {
+        assert!(
+            matches!(
+                Day::try_calculate_silver(input),
+                Err(SolveError::Invalid(_))
+            ),
+            "{input:?}"
+        );
+    }
+
+    // these used to unwrap a missing path
+    for input in ["SazE", "SzaE", "zSzE"] {
+        assert!(
+            matches!(
+                Day::try_calculate_silver(input),
+                Err(SolveError::Invalid(_))
+            ),
+            "{input:?}"
+        );
+        assert!(
+            matches!(Day::try_calculate_gold(input), Err(SolveError::Invalid(_))),
+            "{input:?}"
+        );
+    }
+}
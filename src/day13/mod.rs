@@ -1,4 +1,4 @@
-use crate::utils::{fast_parse_int_from_bytes, split_once_2};
+use crate::utils::Cursor;
 
 use super::*;
 
@@ -10,70 +10,71 @@ impl SolutionSilver<usize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> usize {
-        let mut bytes = input.as_bytes();
-        let mut val = 0;
-        let mut index = 1;
-        loop {
-            let (line1, line2);
-            (line1, bytes) = split_once_2(bytes, b'\n');
-            bytes = &bytes[1..];
-            (line2, bytes) = split_once_2(bytes, b'\n');
-
-            debug_assert_eq!(line1[0], b'[');
-            debug_assert_eq!(line2[0], b'[');
-            let (packet_1, remaining_1) = Packet::parse(line1);
-            let (packet_2, remaining_2) = Packet::parse(line2);
+    type Parsed<'a> = Vec<Packet>;
 
-            debug_assert_eq!(remaining_1, b"");
-            debug_assert_eq!(remaining_2, b"");
-
-            if packet_1 < packet_2 {
-                val += index;
-            }
+    /// Parses the input into a list of packets, where every two consecutive packets form a pair.
+    fn parse(input: &str) -> Result<Vec<Packet>, SolveError> {
+        let mut cursor = Cursor::new(input);
+        let mut packets = vec![];
+        loop {
+            packets.push(Packet::parse(&mut cursor, 0)?);
+            cursor.expect(b"\n")?;
+            packets.push(Packet::parse(&mut cursor, 0)?);
 
-            if bytes.is_empty() {
+            if cursor.is_empty() {
                 break;
             }
 
-            bytes = &bytes[2..];
-            index += 1;
+            cursor.expect(b"\n\n")?;
         }
 
-        val
+        Ok(packets)
     }
-}
-
-impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
-        let mut bytes = input.as_bytes();
 
-        let distress_1 = Packet::List(vec![Packet::List(vec![Packet::Number(2)])]);
-        let distress_2 = Packet::List(vec![Packet::List(vec![Packet::Number(6)])]);
-        let mut packets = vec![distress_1.clone(), distress_2.clone()];
-        loop {
-            let (line1, line2);
-            (line1, bytes) = split_once_2(bytes, b'\n');
-            bytes = &bytes[1..];
-            (line2, bytes) = split_once_2(bytes, b'\n');
-
-            debug_assert_eq!(line1[0], b'[');
-            debug_assert_eq!(line2[0], b'[');
-            let (packet_1, remaining_1) = Packet::parse(line1);
-            let (packet_2, remaining_2) = Packet::parse(line2);
+    fn solve_silver(packets: &Vec<Packet>) -> usize {
+        packets
+            .chunks_exact(2)
+            .enumerate()
+            .filter(|(_, pair)| pair[0] < pair[1])
+            .map(|(index, _)| index + 1)
+            .sum()
+    }
 
-            debug_assert_eq!(remaining_1, b"");
-            debug_assert_eq!(remaining_2, b"");
+    /// Generates `size` pairs of packets. Packets that compare equal to a divider packet are
+    /// skipped, because their order relative to it is not defined.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        fn packet(rng: &mut generate::Rng, depth: usize) -> String {
+            let items: Vec<String> = (0..rng.usize(0..=4))
+                .map(|_| match depth < 3 && rng.chance(1, 3) {
+                    true => packet(rng, depth + 1),
+                    false => rng.usize(0..=10).to_string(),
+                })
+                .collect();
+            format!("[{}]", items.join(","))
+        }
 
-            packets.push(packet_1);
-            packets.push(packet_2);
+        // only lists of a single item that end in a 2 or a 6 are equal to a divider
+        let is_divider =
+            |p: &str| !p.contains(',') && matches!(p.trim_matches(&['[', ']'][..]), "2" | "6");
 
-            if bytes.is_empty() {
-                break;
+        let mut pairs = vec![];
+        while pairs.len() < size.max(1) {
+            let (left, right) = (packet(rng, 0), packet(rng, 0));
+            if !is_divider(&left) && !is_divider(&right) {
+                pairs.push(format!("{left}\n{right}"));
             }
-
-            bytes = &bytes[2..];
         }
+        Some(pairs.join("\n\n"))
+    }
+}
+
+impl SolutionGold<usize, usize> for Day {
+    fn solve_gold(packets: &Vec<Packet>) -> usize {
+        let distress_1 = Packet::List(vec![Packet::List(vec![Packet::Number(2)])]);
+        let distress_2 = Packet::List(vec![Packet::List(vec![Packet::Number(6)])]);
+        let mut packets = packets.clone();
+        packets.push(distress_1.clone());
+        packets.push(distress_2.clone());
 
         packets.sort_unstable();
 
@@ -88,43 +89,34 @@ pub enum Packet {
     List(Vec<Packet>),
 }
 
-impl Packet {
-    pub fn parse(mut bytes: &[u8]) -> (Self, &[u8]) {
-        match &bytes[0] {
-            b'[' => {
-                let mut list = vec![];
+/// How deep lists can be nested. Parsing and comparing packets recurse into every list, so this
+/// keeps deeply nested input from overflowing the stack.
+const MAX_DEPTH: usize = 100;
 
-                if bytes[1] == b']' {
-                    return (Self::List(vec![]), &bytes[2..]);
-                }
+impl Packet {
+    /// Parses a packet at the cursor, which is nested in `depth` lists.
+    fn parse(cursor: &mut Cursor, depth: usize) -> Result<Self, SolveError> {
+        if cursor.peek() != Some(b'[') {
+            return Ok(Self::Number(cursor.uint()?));
+        }
+        if depth == MAX_DEPTH {
+            return Err(cursor.error(format!("lists are nested more than {MAX_DEPTH} deep")));
+        }
+        cursor.expect(b"[")?;
 
-                while bytes[0] != b']' {
-                    bytes = &bytes[1..];
-                    let packet;
-                    (packet, bytes) = Self::parse(bytes);
-                    list.push(packet);
-                    debug_assert!(matches!(bytes[0], b',' | b']'));
-                }
-                bytes = &bytes[1..];
+        let mut list = vec![];
+        if cursor.eat(b"]") {
+            return Ok(Self::List(list));
+        }
 
-                (Self::List(list), bytes)
-            }
-            _ => {
-                debug_assert!(bytes[0].is_ascii_digit());
-                let num;
-                (num, bytes) = Self::parse_number(bytes);
-                (Self::Number(num), bytes)
+        loop {
+            list.push(Self::parse(cursor, depth + 1)?);
+            if cursor.eat(b"]") {
+                return Ok(Self::List(list));
             }
+            cursor.expect(b",")?;
         }
     }
-
-    fn parse_number(bytes: &[u8]) -> (usize, &[u8]) {
-        let num_len = bytes.iter().take_while(|b| b.is_ascii_digit()).count();
-        let (num_bytes, rem_bytes) = bytes.split_at(num_len);
-        let num = fast_parse_int_from_bytes(num_bytes);
-
-        (num, rem_bytes)
-    }
 }
 
 impl PartialOrd for Packet {
@@ -175,3 +167,132 @@ fn test_gold_real() {
     let output = Day::calculate_gold(Day::INPUT_REAL);
     assert_eq!(21922, output);
 }
+
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen("[[4,4],4,4,4]", "[[4,4],4,4,4e", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 11,
+            column: 13,
+            message: "expected `,`".to_string()
+        }
+    );
+
+    // found by fuzzing, these used to index out of bounds or fail debug assertions
+    for input in [
+        "[",
+        "[1,1,\n[1,1,",
+        "[1,,1,1]\n[1]",
+        "[1]\n[2]\n2\n[9]",
+        "[1]\n[\u{6}1]",
+    ] {
+        assert!(
+            matches!(
+                Day::try_calculate_gold(input),
+                Err(SolveError::Parse { .. })
+            ),
+            "{input:?}"
+        );
+    }
+
+    let nested = format!("{}{}", "[".repeat(MAX_DEPTH + 1), "]".repeat(MAX_DEPTH + 1));
+    let input = format!("[]\n{nested}");
+    assert!(matches!(
+        Day::try_calculate_silver(&input),
+        Err(SolveError::Parse {
+            line: 2,
+            column: 101,
+            ..
+        })
+    ));
+}
+
+#[cfg(test)]
+const DIVIDERS: [&str; 2] = ["[[2]]", "[[6]]"];
+
+/// Compares two packets token by token, without building them. A number that is compared with a
+/// list is replaced by a list containing only that number.
+#[cfg(test)]
+fn compare_naive(left: &str, right: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    #[derive(Clone, Copy)]
+    enum Token {
+        Open,
+        Close,
+        Number(usize),
+    }
+
+    // the tokens in reverse order, so the next token can be popped
+    let tokenize = |packet: &str| {
+        let mut tokens = vec![];
+        let mut chars = packet.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '[' => tokens.push(Token::Open),
+                ']' => tokens.push(Token::Close),
+                ',' => {}
+                _ => {
+                    let mut number = c.to_digit(10).unwrap() as usize;
+                    while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                        number = number * 10 + digit as usize;
+                        chars.next();
+                    }
+                    tokens.push(Token::Number(number));
+                }
+            }
+        }
+        tokens.reverse();
+        tokens
+    };
+
+    let (mut left, mut right) = (tokenize(left), tokenize(right));
+    loop {
+        match (left.pop(), right.pop()) {
+            (Some(Token::Open), Some(Token::Open)) | (Some(Token::Close), Some(Token::Close)) => {}
+            (Some(Token::Number(a)), Some(Token::Number(b))) if a != b => return a.cmp(&b),
+            (Some(Token::Number(_)), Some(Token::Number(_))) => {}
+            (Some(Token::Close), Some(_)) => return Ordering::Less,
+            (Some(_), Some(Token::Close)) => return Ordering::Greater,
+            (Some(Token::Number(a)), Some(Token::Open)) => {
+                left.extend([Token::Close, Token::Number(a)]);
+            }
+            (Some(Token::Open), Some(Token::Number(b))) => {
+                right.extend([Token::Close, Token::Number(b)]);
+            }
+            _ => return Ordering::Equal,
+        }
+    }
+}
+
+#[cfg(test)]
+fn calculate_naive(input: &str) -> (usize, usize) {
+    let packets: Vec<&str> = input.lines().filter(|l| !l.is_empty()).collect();
+    let silver = packets
+        .chunks(2)
+        .enumerate()
+        .filter(|(_, pair)| compare_naive(pair[0], pair[1]).is_lt())
+        .map(|(index, _)| index + 1)
+        .sum();
+
+    // the position of a divider is the number of packets before it, including the other divider
+    let positions = DIVIDERS.map(|divider| {
+        packets
+            .iter()
+            .chain(&DIVIDERS)
+            .filter(|p| compare_naive(p, divider).is_lt())
+            .count()
+            + 1
+    });
+    (silver, positions[0] * positions[1])
+}
+
+#[test]
+fn test_differential() {
+    crate::property::check(2000, crate::property::input::<Day, _>, |input| {
+        let actual = (Day::calculate_silver(input), Day::calculate_gold(input));
+        assert_eq!(calculate_naive(input), actual);
+    });
+}
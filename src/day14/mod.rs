@@ -1,6 +1,4 @@
-use ahash::AHashSet;
-
-use crate::utils::fast_parse_int;
+use crate::{grid::Grid, utils::Cursor};
 
 use super::*;
 
@@ -11,15 +9,26 @@ impl SolutionSilver<usize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> usize {
-        let mut grid = parse_input(input);
-        let rock_bottom = *grid.iter().map(|(_x, y)| y).max().unwrap();
+    type Parsed<'a> = Cave;
+
+    fn parse(input: &str) -> Result<Cave, SolveError> {
+        parse_input(input)
+    }
+
+    fn solve_silver(cave: &Cave) -> usize {
+        let mut grid = cave.grid.clone();
+        let rock_bottom = cave.rock_bottom;
 
         // simulate the sand
         let mut iteration = 0;
         loop {
             let mut position = (500, 0);
 
+            // the rocks can form a cup around the source, then no more sand comes to rest
+            if grid[position] {
+                return iteration;
+            }
+
             // fall down
             loop {
                 // we fell off (the world)
@@ -27,41 +36,70 @@ impl SolutionSilver<usize> for Day {
                     return iteration;
                 }
 
-                if !grid.contains(&(position.0, position.1 + 1)) {
+                if !grid[(position.0, position.1 + 1)] {
                     position = (position.0, position.1 + 1);
                     continue;
                 }
 
-                if !grid.contains(&(position.0 - 1, position.1 + 1)) {
+                if !grid[(position.0 - 1, position.1 + 1)] {
                     position = (position.0 - 1, position.1 + 1);
                     continue;
                 }
 
-                if !grid.contains(&(position.0 + 1, position.1 + 1)) {
+                if !grid[(position.0 + 1, position.1 + 1)] {
                     position = (position.0 + 1, position.1 + 1);
                     continue;
                 }
 
                 // we cannot drop down further, place the sand here
-                grid.insert(position);
+                grid[position] = true;
                 break;
             }
 
             iteration += 1;
         }
     }
+
+    /// Generates `size` paths of rock below the source of the sand. The cave gets deeper with the
+    /// number of paths, up to 400 units, so the pile of sand in the gold part fits next to the
+    /// source.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let depth = (10 + size).min(400);
+        let paths: Vec<String> = (0..size.max(1))
+            .map(|_| {
+                let mut x = rng.usize(500 - depth / 2..=500 + depth / 2);
+                let mut y = rng.usize(1..=depth);
+                let mut points = vec![format!("{x},{y}")];
+
+                let mut horizontal = rng.chance(1, 2);
+                for _ in 0..rng.usize(1..=4) {
+                    let length = rng.usize(1..=8);
+                    match (horizontal, rng.chance(1, 2)) {
+                        (true, true) => x += length,
+                        (true, false) => x -= length,
+                        (false, true) => y = (y + length).min(depth),
+                        (false, false) => y = y.saturating_sub(length).max(1),
+                    }
+                    points.push(format!("{x},{y}"));
+                    horizontal = !horizontal;
+                }
+                points.join(" -> ")
+            })
+            .collect();
+        Some(paths.join("\n"))
+    }
 }
 impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
-        let mut grid = parse_input(input);
-        let rock_bottom = grid.iter().map(|(_x, y)| y).max().unwrap() + 2;
+    fn solve_gold(cave: &Cave) -> usize {
+        let mut grid = cave.grid.clone();
+        let rock_bottom = cave.rock_bottom + 2;
 
         // simulate the sand
         let mut iteration = 0;
         loop {
             let mut position = (500, 0);
 
-            if grid.contains(&position) {
+            if grid[position] {
                 return iteration;
             }
 
@@ -69,27 +107,27 @@ impl SolutionGold<usize, usize> for Day {
             loop {
                 // we hit rock bottom
                 if position.1 + 1 == rock_bottom {
-                    grid.insert(position);
+                    grid[position] = true;
                     break;
                 }
 
-                if !grid.contains(&(position.0, position.1 + 1)) {
+                if !grid[(position.0, position.1 + 1)] {
                     position = (position.0, position.1 + 1);
                     continue;
                 }
 
-                if !grid.contains(&(position.0 - 1, position.1 + 1)) {
+                if !grid[(position.0 - 1, position.1 + 1)] {
                     position = (position.0 - 1, position.1 + 1);
                     continue;
                 }
 
-                if !grid.contains(&(position.0 + 1, position.1 + 1)) {
+                if !grid[(position.0 + 1, position.1 + 1)] {
                     position = (position.0 + 1, position.1 + 1);
                     continue;
                 }
 
                 // we cannot drop down further, place the sand here
-                grid.insert(position);
+                grid[position] = true;
                 break;
             }
 
@@ -98,26 +136,67 @@ impl SolutionGold<usize, usize> for Day {
     }
 }
 
-fn parse_input(input: &str) -> AHashSet<(usize, usize)> {
+/// The rocks in the cave, and the lowest rock.
+pub struct Cave {
+    /// Whether a cell is blocked by rock or sand. The grid is wide enough to hold the pile of sand
+    /// that forms on the floor in the gold part.
+    grid: Grid<bool>,
+    rock_bottom: usize,
+}
+
+/// The deepest a rock can be. The pile of sand on the floor in the gold part spreads one cell to
+/// each side for every row, so it would not fit at non-negative `x` below this.
+const MAX_DEPTH: usize = 496;
+
+/// The furthest to the right a rock can be, which keeps the grid small.
+const MAX_X: usize = 1000;
+
+fn parse_input(input: &str) -> Result<Cave, SolveError> {
     // fuck performance all my homies hate performance
-    let iterator = input.lines().map(|l| {
-        l.split(" -> ")
-            .map(|p| p.split_once(',').unwrap())
-            .map(|(a, b)| (fast_parse_int(a), fast_parse_int(b)))
-    });
+    let mut cursor = Cursor::new(input);
+    let mut lines: Vec<Vec<_>> = vec![];
+    while !cursor.is_empty() {
+        let mut line = vec![parse_point(&mut cursor)?];
+        while !cursor.is_empty() && !cursor.eat(b"\n") {
+            cursor.expect(b" -> ")?;
+            let start = cursor;
+            let point = parse_point(&mut cursor)?;
+            let last = line[line.len() - 1];
+            if point.0 != last.0 && point.1 != last.1 {
+                return Err(start.error("expected a horizontal or vertical line"));
+            }
+            line.push(point);
+        }
+        lines.push(line);
+    }
 
-    let mut grid = AHashSet::new(); // TODO: oh no my perfomance!
-    for mut line in iterator {
-        let mut last = line.next().unwrap();
+    let Some(rock_bottom) = lines.iter().flatten().map(|&(_x, y)| y).max() else {
+        return Err(SolveError::invalid("the cave has no rocks"));
+    };
+    let right_edge = lines
+        .iter()
+        .flatten()
+        .map(|&(x, _y)| x)
+        .max()
+        .unwrap_or_default();
+
+    // sand piles up at most one cell to the side for every row it falls, and falls at most to the
+    // floor 2 rows below the lowest rock
+    let height = rock_bottom + 3;
+    let width = right_edge.max(500 + height) + 1;
+    let mut grid = Grid::new(width, height, false);
+    for line in lines {
+        let mut line = line.into_iter();
+        let mut last = line.next().expect("every line has a point");
 
         for curr in line {
             if curr.0 != last.0 {
                 for i in curr.0.min(last.0)..=curr.0.max(last.0) {
-                    grid.insert((i, curr.1));
+                    grid[(i, curr.1)] = true;
                 }
             } else {
                 for i in curr.1.min(last.1)..=curr.1.max(last.1) {
-                    grid.insert((curr.0, i));
+                    grid[(curr.0, i)] = true;
                 }
             }
 
@@ -125,7 +204,19 @@ fn parse_input(input: &str) -> AHashSet<(usize, usize)> {
         }
     }
 
-    grid
+    Ok(Cave { grid, rock_bottom })
+}
+
+/// Parses an `x,y` point of a rock path.
+fn parse_point(cursor: &mut Cursor) -> Result<(usize, usize), SolveError> {
+    let start = *cursor;
+    let x = cursor.uint()?;
+    cursor.expect(b",")?;
+    let y = cursor.uint()?;
+    if x > MAX_X || y > MAX_DEPTH {
+        return Err(start.error(format!("expected a point within {MAX_X},{MAX_DEPTH}")));
+    }
+    Ok((x, y))
 }
 
 #[test]
@@ -151,3 +242,36 @@ fn test_gold_real() {
     let output = Day::calculate_gold(Day::INPUT_REAL);
     assert_eq!(26625, output);
 }
+
+#[test]
+fn test_silver_cup() {
+    // the sand fills the cup up to the source and never falls off
+    let output = Day::calculate_silver("499,1 -> 499,2 -> 501,2 -> 501,1");
+    assert_eq!(2, output);
+}
+
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen("-> 496,6", "=> 496,6", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 1,
+            column: 15,
+            message: "expected ` -> `".to_string()
+        }
+    );
+
+    // found by fuzzing, these used to panic on a missing or diagonal line, or allocate a grid
+    // for a rock that is very far away
+    for input in [
+        "",
+        ">9",
+        "498,4 -> 498,",
+        "498,4 - -> 498,6",
+        "498,4 -> 696,6",
+        "5,9999999",
+    ] {
+        assert!(Day::try_calculate_gold(input).is_err(), "{input:?}");
+    }
+}
@@ -5,21 +5,64 @@ use std::ops::Range;
 
 use tinyvec::TinyVec;
 
+use crate::utils::Cursor;
+
 use super::*;
 
 pub struct Day;
 
+type Sensors = TinyVec<[((isize, isize), usize); 32]>;
+
+/// Coordinates are at most this far from 0, so the distances between them cannot overflow.
+const MAX_COORDINATE: isize = i32::MAX as isize;
+
+pub struct Puzzle {
+    /// The position of each sensor, with the distance to its beacon.
+    sensors: Sensors,
+    /// The position of each beacon, which can be the closest beacon of several sensors.
+    beacons: TinyVec<[(isize, isize); 32]>,
+    /// The row that is checked for positions that cannot contain a beacon.
+    row: isize,
+    /// The distress beacon is at most this far from 0 in both directions.
+    bound: usize,
+}
+
 impl SolutionSilver<usize> for Day {
     const DAY: u32 = 15;
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
+    const PARAMS_SAMPLE: &'static str = include_str!("params_sample.txt");
+
+    type Parsed<'a> = Puzzle;
+
+    fn parse(input: &str) -> Result<Puzzle, SolveError> {
+        Self::parse_with(input, &Params::default())
+    }
+
+    /// Reads the `row` to check for silver and the `bound` of the search area for gold, because
+    /// these differ between the sample and the real input.
+    fn parse_with(input: &str, params: &Params) -> Result<Puzzle, SolveError> {
+        let row = params.get_or("row", 2_000_000)?;
+        let bound = params.get_or("bound", 4_000_000)?;
+        if bound == 0 {
+            return Err(SolveError::invalid("the search area should not be empty"));
+        }
+        if bound > MAX_COORDINATE as usize {
+            let message = format!("the search area should be at most {MAX_COORDINATE} wide");
+            return Err(SolveError::invalid(message));
+        }
 
-    fn calculate_silver(input: &str) -> usize {
-        // ugly hack because AoC is weird
-        let is_sample = input == Self::INPUT_SAMPLE;
-        let line = if is_sample { 10 } else { 2_000_000 };
+        let (sensors, beacons) = parse_input(input)?;
+        Ok(Puzzle {
+            sensors,
+            beacons,
+            row,
+            bound,
+        })
+    }
 
-        let input = parse_input(input);
+    fn solve_silver(puzzle: &Puzzle) -> usize {
+        let (input, line) = (&puzzle.sensors, puzzle.row);
 
         // we need to calculate the range where we can check a beacon can be
         // we need to check the range of each sensor and add its range to it
@@ -58,20 +101,79 @@ impl SolutionSilver<usize> for Day {
             current_x = end;
         }
 
-        count - 1
+        // the beacons on the line are in range of their sensor, but their positions do contain a beacon
+        let mut beacons: Vec<_> = puzzle.beacons.iter().filter(|b| b.1 == line).collect();
+        beacons.sort_unstable();
+        beacons.dedup();
+
+        count - beacons.len()
+    }
+
+    /// Generates about `size` sensors (at least 9) that cover the whole search area of the real
+    /// input except for the distress beacon. Most sensors are on a grid, where each one reaches
+    /// past its neighbours. Sensors that would reach the distress beacon are left out, and four
+    /// sensors on its diagonals cover the gap that leaves.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        const BOUND: isize = 4_000_000;
+
+        let distress = (rng.isize(0..=BOUND - 1), rng.isize(0..=BOUND - 1));
+        let distance = |(x, y): (isize, isize)| x.abs_diff(distress.0) + y.abs_diff(distress.1);
+
+        // a point is never further than `spacing` from the nearest point of the grid
+        let per_side = ((size as f64).sqrt() as isize).max(1);
+        let spacing = BOUND / per_side + 1;
+        let offset = (rng.isize(0..=spacing - 1), rng.isize(0..=spacing - 1));
+
+        let mut sensors = vec![];
+        for grid_y in -1..=per_side {
+            for grid_x in -1..=per_side {
+                let sensor = (offset.0 + grid_x * spacing, offset.1 + grid_y * spacing);
+                let margin = spacing as usize / 4 + rng.usize(0..=spacing as usize / 4);
+                let reach = (spacing as usize + margin).min(distance(sensor).saturating_sub(1));
+                if reach >= spacing as usize {
+                    sensors.push((sensor, reach));
+                }
+            }
+        }
+
+        // each of these covers its quadrant up to 2 * spacing away, except for the beacon itself
+        for (dx, dy) in [(-1, -1), (-1, 1), (1, -1), (1, 1)] {
+            let sensor = (distress.0 + dx * 2 * spacing, distress.1 + dy * 2 * spacing);
+            sensors.push((sensor, 4 * spacing as usize - 1));
+        }
+        rng.shuffle(&mut sensors);
+
+        let lines: Vec<String> = sensors
+            .into_iter()
+            .map(|((x, y), reach)| {
+                let dx = rng.usize(0..=reach) as isize;
+                let dy = (reach as isize - dx) * *rng.choose(&[-1, 1]);
+                let dx = dx * *rng.choose(&[-1, 1]);
+                let beacon = (x + dx, y + dy);
+                format!(
+                    "Sensor at x={x}, y={y}: closest beacon is at x={}, y={}",
+                    beacon.0, beacon.1
+                )
+            })
+            .collect();
+        Some(lines.join("\n"))
     }
 }
 
 impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
-        let is_sample = input == Self::INPUT_SAMPLE;
-        let max_coordinate = if is_sample { 20 } else { 4_000_000 };
+    fn solve_gold(puzzle: &Puzzle) -> usize {
+        Self::try_solve_gold(puzzle).unwrap_or_else(|e| panic!("{e}"))
+    }
 
-        let input = parse_input(input);
+    /// Fails if the sensors cover the whole search area, so there is no room for the distress
+    /// beacon.
+    fn try_solve_gold(puzzle: &Puzzle) -> Result<usize, SolveError> {
+        let (input, max_coordinate) = (&puzzle.sensors, puzzle.bound);
 
-        let coordinate = contains_square(&input, 0..max_coordinate, 0..max_coordinate).unwrap();
+        let coordinate = contains_square(input, 0..max_coordinate, 0..max_coordinate)
+            .ok_or_else(|| SolveError::invalid("the sensors cover the whole search area"))?;
 
-        coordinate.0 * 4_000_000 + coordinate.1
+        Ok(coordinate.0 * 4_000_000 + coordinate.1)
     }
 }
 
@@ -129,25 +231,44 @@ fn manhattan_distance(s: (isize, isize), b: (isize, isize)) -> usize {
     s.0.abs_diff(b.0) + s.1.abs_diff(b.1)
 }
 
-fn parse_input(input: &str) -> TinyVec<[((isize, isize), usize); 32]> {
-    input
-        .lines()
-        .map(|line| {
-            let (sensor, beacon) = line.split_once(": ").unwrap();
-            let (sensor_1, sensor_2) = sensor.split_once(',').unwrap();
-            let (beacon_1, beacon_2) = beacon.split_once(',').unwrap();
+fn parse_input(input: &str) -> Result<(Sensors, TinyVec<[(isize, isize); 32]>), SolveError> {
+    let mut cursor = Cursor::new(input);
+    let mut sensors = Sensors::new();
+    let mut beacons = TinyVec::new();
+    while !cursor.is_empty() {
+        cursor.expect(b"Sensor at ")?;
+        let sensor = parse_position(&mut cursor)?;
+        cursor.expect(b": closest beacon is at ")?;
+        let beacon = parse_position(&mut cursor)?;
+        if !cursor.is_empty() {
+            cursor.expect(b"\n")?;
+        }
 
-            let sensor_1 = str::parse(&sensor_1["Sensor at x=".len()..]).unwrap();
-            let sensor_2 = str::parse(&sensor_2[" y=".len()..]).unwrap();
+        sensors.push((sensor, manhattan_distance(sensor, beacon)));
+        beacons.push(beacon);
+    }
 
-            let beacon_1 = str::parse(&beacon_1["closest beacon is at x=".len()..]).unwrap();
-            let beacon_2 = str::parse(&beacon_2[" y=".len()..]).unwrap();
+    Ok((sensors, beacons))
+}
 
-            let distance = manhattan_distance((sensor_1, sensor_2), (beacon_1, beacon_2));
+/// Parses an `x=.., y=..` position.
+fn parse_position(cursor: &mut Cursor) -> Result<(isize, isize), SolveError> {
+    cursor.expect(b"x=")?;
+    let x = parse_coordinate(cursor)?;
+    cursor.expect(b", y=")?;
+    let y = parse_coordinate(cursor)?;
+    Ok((x, y))
+}
 
-            ((sensor_1, sensor_2), distance)
-        })
-        .collect()
+fn parse_coordinate(cursor: &mut Cursor) -> Result<isize, SolveError> {
+    let start = *cursor;
+    let coordinate = cursor.int()?;
+    if !(-MAX_COORDINATE..=MAX_COORDINATE).contains(&coordinate) {
+        return Err(start.error(format!(
+            "expected a coordinate from -{MAX_COORDINATE} to {MAX_COORDINATE}"
+        )));
+    }
+    Ok(coordinate)
 }
 
 #[test]
@@ -173,3 +294,63 @@ fn test_gold_real() {
     let output = Day::calculate_gold(Day::INPUT_REAL);
     assert_eq!(13543690671045, output);
 }
+
+#[test]
+fn test_params() {
+    let params = Params::parse("row = 2000000\nbound = 4000000").unwrap();
+    let puzzle = Day::parse_with(Day::INPUT_REAL, &params).unwrap();
+    assert_eq!(4665948, Day::solve_silver(&puzzle));
+
+    let params = Params::parse("bound = 0").unwrap();
+    assert!(Day::parse_with(Day::INPUT_SAMPLE, &params).is_err());
+    let params = Params::parse("row = ten").unwrap();
+    assert!(Day::parse_with(Day::INPUT_SAMPLE, &params).is_err());
+}
+
+#[test]
+fn test_covered_search_area() {
+    let input = "Sensor at x=2, y=2: closest beacon is at x=2, y=6";
+    let params = Params::parse("bound = 4").unwrap();
+    let puzzle = Day::parse_with(input, &params).unwrap();
+    assert!(matches!(
+        Day::try_solve_gold(&puzzle),
+        Err(SolveError::Invalid(_))
+    ));
+}
+
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen("x=2, y=18", "x=2 y=18", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 1,
+            column: 14,
+            message: "expected `, y=`".to_string()
+        }
+    );
+
+    // found by fuzzing, these used to slice out of bounds, unwrap a missing number or overflow
+    for input in [
+        "S",
+        "Sensor at x=2, y=18: ",
+        "Sensor at x=2, y=18: closest beacon is at x=-2, y=99999999999",
+        "Sensor at x=-9223372036854775808, y=0: closest beacon is at x=0, y=0",
+    ] {
+        assert!(Day::try_calculate_silver(input).is_err(), "{input:?}");
+    }
+}
+
+#[test]
+fn test_beacons_on_row() {
+    // both sensors share the beacon on the row, and no other beacon is on it
+    let input = "Sensor at x=0, y=0: closest beacon is at x=2, y=0\n\
+                 Sensor at x=4, y=0: closest beacon is at x=2, y=0";
+    let params = Params::parse("row = 0\nbound = 4").unwrap();
+    let puzzle = Day::parse_with(input, &params).unwrap();
+    assert_eq!(8, Day::solve_silver(&puzzle));
+
+    let params = Params::parse("row = 5").unwrap();
+    let puzzle = Day::parse_with(input, &params).unwrap();
+    assert_eq!(0, Day::solve_silver(&puzzle));
+}
@@ -3,7 +3,7 @@ use std::fmt::Debug;
 use ahash::{AHashMap, AHashSet};
 use tinyvec::{tiny_vec, ArrayVec, TinyVec};
 
-use crate::utils::fast_parse_int;
+use crate::{cancel, search};
 
 use super::*;
 
@@ -18,28 +18,97 @@ impl SolutionSilver<usize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> usize {
-        let input: ValveInfo = setup::parse_input(input);
+    type Parsed<'a> = (ValveInfoWeighted, ValveDistanceMap);
+
+    /// Parses the input, drops the valves that cannot be opened and calculates the distances
+    /// between the remaining ones.
+    fn parse(input: &str) -> Result<(ValveInfoWeighted, ValveDistanceMap), SolveError> {
+        let input: ValveInfo = setup::parse_input(input)?;
         let input = setup::optimize_input(&input);
         let distances = setup::build_distance_map(&input);
         // println!("len: {}", distances.len());
         // println!("tree: {distances:#?}");
 
-        recursive_search(&input, &distances, Path::new_start(), 0)
+        Ok((input, distances))
+    }
+
+    fn solve_silver((input, distances): &(ValveInfoWeighted, ValveDistanceMap)) -> usize {
+        recursive_search(input, distances, Path::new_start(), 0)
+    }
+
+    /// Generates `size` valves (2 to 676) that are all connected by tunnels. Like in the puzzle
+    /// input, the valves with a flow rate of zero other than `AA` form corridors between the
+    /// others, which keeps folding them away cheap. At most 8 valves have a non-zero flow rate,
+    /// because the search takes exponential time in their number.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let count = size.clamp(2, 26 * 26);
+
+        // `AA` is the first valve, the others get random names
+        let mut names: Vec<ValveName> = (1..26 * 26).map(ValveName).collect();
+        rng.shuffle(&mut names);
+        names.insert(0, ValveName::AA);
+        names.truncate(count);
+
+        // `AA` and the useful valves come first. A random tree between them makes sure every
+        // valve can be reached, the other corridors add loops.
+        let useful = rng.usize(1..=(count - 1).min(8));
+        let mut corridors: Vec<(usize, usize, Vec<usize>)> = vec![];
+        for valve in 1..=useful {
+            corridors.push((rng.usize(0..=valve - 1), valve, vec![]));
+        }
+        for _ in 0..useful / 2 {
+            let (a, b) = (rng.usize(0..=useful), rng.usize(0..=useful));
+            if a != b
+                && !corridors
+                    .iter()
+                    .any(|c| (c.0, c.1) == (a, b) || (c.0, c.1) == (b, a))
+            {
+                corridors.push((a, b, vec![]));
+            }
+        }
+        for valve in useful + 1..count {
+            let corridor = rng.usize(0..=corridors.len() - 1);
+            corridors[corridor].2.push(valve);
+        }
+
+        let mut tunnels = vec![vec![]; count];
+        for (a, b, valves) in corridors {
+            let path: Vec<usize> = [a].into_iter().chain(valves).chain([b]).collect();
+            for pair in path.windows(2) {
+                tunnels[pair[0]].push(pair[1]);
+                tunnels[pair[1]].push(pair[0]);
+            }
+        }
+
+        let mut rates = vec![0; count];
+        for rate in &mut rates[1..=useful] {
+            *rate = rng.usize(1..=25);
+        }
+
+        let mut lines: Vec<String> = (0..count)
+            .map(|valve| {
+                let targets: Vec<String> = tunnels[valve]
+                    .iter()
+                    .map(|&target| names[target].to_string())
+                    .collect();
+                let tunnels = match targets.len() {
+                    1 => format!("tunnel leads to valve {}", targets[0]),
+                    _ => format!("tunnels lead to valves {}", targets.join(", ")),
+                };
+                let name = names[valve].to_string();
+                format!("Valve {name} has flow rate={}; {tunnels}", rates[valve])
+            })
+            .collect();
+        rng.shuffle(&mut lines);
+        Some(lines.join("\n"))
     }
 }
 
 impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
-        let input: ValveInfo = setup::parse_input(input);
-        let input = setup::optimize_input(&input);
-        let distances = setup::build_distance_map(&input);
-        // println!("len: {}", distances.len());
-        // println!("tree: {distances:#?}");
-
+    fn solve_gold((input, distances): &(ValveInfoWeighted, ValveDistanceMap)) -> usize {
         recursive_search_gold(
-            &input,
-            &distances,
+            input,
+            distances,
             &Path::new_start(),
             4,
             &Path::new_start(),
@@ -105,6 +174,8 @@ fn recursive_search_gold(
     path_2: &Path,
     current_minute_2: usize,
 ) -> usize {
+    cancel::checkpoint();
+
     // println!("Current minute: {current_minute}");
     if current_minute_1 >= 30 && current_minute_2 >= 30 {
         // println!("Exit because time limit is reached ({current_score}): {path:?}");
@@ -315,15 +386,15 @@ mod setup {
     pub fn build_distance_map(valve_info: &ValveInfoWeighted) -> ValveDistanceMap {
         let mut distance_map = ValveDistanceMap::new();
         for valve in valve_info.keys() {
-            let mut distances = AHashMap::new();
-
             // calculate the distance to each other node
-            calculate_distance_recursive(&mut distances, *valve, *valve, valve_info, 0);
+            let distances = search::dijkstra_distances([*valve], |current| {
+                valve_info[current].1.iter().copied()
+            });
 
             // add those distances to the main map
             for (target, distance) in distances {
                 let target_pressure = valve_info[&target].0;
-                if target_pressure != 0 && target != ValveName::AA {
+                if target_pressure != 0 && target != ValveName::AA && target != *valve {
                     distance_map.insert((*valve, target), distance);
                 }
             }
@@ -332,76 +403,89 @@ mod setup {
         distance_map
     }
 
-    fn calculate_distance_recursive(
-        found_distances: &mut AHashMap<ValveName, usize>,
-        start: ValveName,
-        current: ValveName,
-        valve_info: &ValveInfoWeighted,
-        accumulated_distance: usize,
-    ) {
-        let (_, targets) = &valve_info[&current];
+    pub fn parse_input(input: &str) -> Result<ValveInfo, SolveError> {
+        let valve_info = input
+            .lines()
+            .map(|line| {
+                let Some((part1, part2)) = line.split_once("; ") else {
+                    return Err(SolveError::at(input, line, "expected `;`"));
+                };
+                let rest = part1
+                    .strip_prefix("Valve ")
+                    .ok_or_else(|| SolveError::at(input, part1, "expected `Valve `"))?;
+                let (valve_name, rate_str) = rest
+                    .split_once(" has flow rate=")
+                    .ok_or_else(|| SolveError::at(input, rest, "expected a flow rate"))?;
+
+                let part2 = ["tunnels lead to valves ", "tunnel leads to valve "]
+                    .iter()
+                    .find_map(|prefix| part2.strip_prefix(prefix))
+                    .ok_or_else(|| SolveError::at(input, part2, "expected a list of tunnels"))?;
+                let targets = part2
+                    .split(", ")
+                    .map(|s| parse_valve_name(input, s))
+                    .collect::<Result<Vec<_>, _>>()?;
 
-        for (target, distance) in targets {
-            if start == *target {
-                continue;
-            }
+                let valve_name = parse_valve_name(input, valve_name)?;
+                let rate = rate_str.parse().map_err(|_| {
+                    SolveError::at(input, rate_str, format!("invalid flow rate `{rate_str}`"))
+                })?;
 
-            let total_distance = accumulated_distance + distance;
-            if !found_distances.contains_key(target) {
-                let target_pressure = valve_info[target].0;
-                if target_pressure != 0 && *target != ValveName::AA {
-                    // println!("{start:?} > inserting {target:?} with val {total_distance}");
-                    found_distances.insert(*target, total_distance);
-                }
-                calculate_distance_recursive(
-                    found_distances,
-                    start,
-                    *target,
-                    valve_info,
-                    total_distance,
-                );
-            } else if total_distance < found_distances[target] {
-                // already exists but we got there in a faster way
-                // println!("{start:?} > inserting {target:?} with val {total_distance} (faster!)");
-                found_distances.insert(*target, total_distance);
-                debug_assert_ne!(valve_info[target].0, 0);
-
-                calculate_distance_recursive(
-                    found_distances,
-                    start,
-                    *target,
-                    valve_info,
-                    total_distance,
-                );
-            }
+                Ok((valve_name, (rate, targets)))
+            })
+            .collect::<Result<ValveInfo, _>>()?;
+
+        validate_input(&valve_info)?;
+        Ok(valve_info)
+    }
+
+    fn parse_valve_name(input: &str, name: &str) -> Result<ValveName, SolveError> {
+        if name.len() == 2 && name.bytes().all(|b| b.is_ascii_uppercase()) {
+            Ok(ValveName::from_bytes(name.as_bytes()))
+        } else {
+            Err(SolveError::at(
+                input,
+                name,
+                format!("invalid valve name `{name}`"),
+            ))
         }
     }
 
-    pub fn parse_input(input: &str) -> ValveInfo {
-        input
-            .split('\n')
-            .map(|line| {
-                let (part1, part2) = line.split_once(';').unwrap();
-                let valve_name = &part1["Valve ".len().."Valve XX".len()];
-                let rate_str = &part1["Valve XX has flow rate=".len()..];
-
-                let part2_start_len = if part2.as_bytes()[7] == b's' {
-                    " tunnels lead to valves ".len()
-                } else {
-                    " tunnel leads to valve ".len()
-                };
-                let part2 = &part2[part2_start_len..];
-                let targets: Vec<_> = part2
-                    .split(", ")
-                    .map(|s| ValveName::from_bytes(s.as_bytes()))
-                    .collect();
+    /// Makes sure the valve graph can be searched: all tunnels lead to known valves, AA exists, the
+    /// number of useful valves fits in a [`Path`] and the pressure they release fits in a `usize`.
+    fn validate_input(valve_info: &ValveInfo) -> Result<(), SolveError> {
+        if !valve_info.contains_key(&ValveName::AA) {
+            return Err(SolveError::invalid("there is no valve AA"));
+        }
 
-                let valve_name = ValveName::from_bytes(valve_name.as_bytes());
-                let rate = fast_parse_int(rate_str);
+        for (valve, (_, targets)) in valve_info {
+            if let Some(target) = targets.iter().find(|t| !valve_info.contains_key(t)) {
+                return Err(SolveError::invalid(format!(
+                    "valve {} has a tunnel to unknown valve {}",
+                    valve.to_string(),
+                    target.to_string()
+                )));
+            }
+        }
 
-                (valve_name, (rate, targets))
-            })
-            .collect()
+        let useful_valves = valve_info.values().filter(|(rate, _)| *rate != 0).count();
+        if useful_valves > 15 {
+            return Err(SolveError::invalid(format!(
+                "there are {useful_valves} valves with a non-zero flow rate, at most 15 are supported"
+            )));
+        }
+
+        // a valve releases its flow rate for at most 30 minutes
+        let total_rate = valve_info
+            .values()
+            .try_fold(0usize, |total, (rate, _)| total.checked_add(*rate));
+        if total_rate.and_then(|total| total.checked_mul(30)).is_none() {
+            return Err(SolveError::invalid(
+                "the total flow rate of the valves is too large",
+            ));
+        }
+
+        Ok(())
     }
 }
 
@@ -430,3 +514,28 @@ fn test_gold_real() {
     let output = Day::calculate_gold(Day::INPUT_REAL);
     assert_eq!(2474, output);
 }
+
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen("flow rate=13", "flow rate=x", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 2,
+            column: 24,
+            message: "invalid flow rate `x`".to_string()
+        }
+    );
+
+    let input = Day::INPUT_SAMPLE.replacen("valves DD, II, BB", "valves DD, II, ZZ", 1);
+    assert!(matches!(
+        Day::try_calculate_silver(&input),
+        Err(SolveError::Invalid(_))
+    ));
+
+    let input = Day::INPUT_SAMPLE.replacen("flow rate=13", "flow rate=18446744073709551615", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::invalid("the total flow rate of the valves is too large")
+    );
+}
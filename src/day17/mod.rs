@@ -1,4 +1,4 @@
-use ringbuffer::{AllocRingBuffer, RingBuffer, RingBufferExt, RingBufferWrite};
+use crate::cycle;
 
 use super::*;
 
@@ -9,171 +9,178 @@ impl SolutionSilver<usize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> usize {
-        let input: Vec<_> = input.as_bytes().iter().map(|b| *b == b'>').collect();
-        let mut chamber: Vec<[bool; 7]> = vec![];
-
-        let mut highest_y = -1isize;
-        let mut input_index = 0;
-        for i in 0..2023 {
-            // iteration
-            let block = BlockType::TYPES[i % 5];
-            let block_height = block.get_height();
-            let mut x = 2usize;
-            let mut y = (highest_y + 3) as usize + block_height;
-
-            while chamber.len() <= y {
-                chamber.push([false; 7]);
-            }
+    /// The jet pattern, where `true` pushes to the right.
+    type Parsed<'a> = Vec<bool>;
 
-            debug_assert!(
-                !block.collides(x as isize, y as isize, &chamber),
-                "newly spawned block should not collide"
-            );
-
-            loop {
-                // move horizontal
-                let movement = input[input_index % input.len()];
-                input_index += 1;
-
-                let new_x = if movement {
-                    x as isize + 1
-                } else {
-                    x as isize - 1
-                };
-                if !block.collides(new_x, y as isize, &chamber) {
-                    x = new_x as usize;
-                } else {
-                }
+    fn parse(input: &str) -> Result<Vec<bool>, SolveError> {
+        if input.is_empty() {
+            return Err(SolveError::invalid("expected a jet pattern"));
+        }
 
-                // drop down
-                if !block.collides(x as isize, y as isize - 1, &chamber) {
-                    y -= 1;
-                } else {
-                    // could not drop down
-                    // write the block to the chamber
-                    for (tile_x, tile_y) in block.get_tiles() {
-                        // let tile_y = block_height - tile_y;
-                        chamber[y - *tile_y as usize][x + *tile_x as usize] = true;
-                    }
-                    highest_y = highest_y.max(y as isize);
-                    break;
-                }
-            }
+        input
+            .bytes()
+            .enumerate()
+            .map(|(i, b)| match b {
+                b'<' => Ok(false),
+                b'>' => Ok(true),
+                _ => Err(SolveError::at_offset(
+                    input,
+                    i,
+                    "expected a jet (`<` or `>`)",
+                )),
+            })
+            .collect()
+    }
+
+    fn solve_silver(input: &Vec<bool>) -> usize {
+        let mut chamber = Chamber::new(input);
+        for _ in 0..2022 {
+            chamber.drop_block();
         }
 
-        highest_y as usize - 1
+        chamber.height
+    }
+
+    /// Generates a pattern of `size` jets (at least 100). Short patterns often keep pushing the
+    /// blocks away from some column, so the tower never repeats, and those are thrown away.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        loop {
+            let jets: Vec<bool> = (0..size.max(100)).map(|_| rng.chance(1, 2)).collect();
+            if tower_cycle(&jets).is_some() {
+                return Some(
+                    jets.iter()
+                        .map(|&right| if right { '>' } else { '<' })
+                        .collect(),
+                );
+            }
+        }
     }
 }
 
 impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
+    fn solve_gold(input: &Vec<bool>) -> usize {
+        Self::try_solve_gold(input).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Fails if the height of the tower does not repeat within [`MAX_BLOCKS_BEFORE_CYCLE`]
+    /// blocks.
+    fn try_solve_gold(input: &Vec<bool>) -> Result<usize, SolveError> {
         const REQUIRED_ITERATIONS: usize = 1_000_000_000_000;
 
-        let input: Vec<_> = input.as_bytes().iter().map(|b| *b == b'>').collect();
-        let mut chamber: Vec<[bool; 7]> = vec![];
-
-        let max_period_length: usize = input.len(); // the max length a period probably is. could be more
-        let ring_buffer_size = (max_period_length * 2).next_power_of_two();
-
-        let mut found_y_addition = None;
-        let mut history = AllocRingBuffer::<(usize, usize)>::with_capacity(ring_buffer_size);
-
-        let mut highest_y = -1isize; // highest y value that is in use
-        let mut input_index = 0;
-
-        let mut iteration = 0;
-        while iteration < REQUIRED_ITERATIONS {
-            if found_y_addition.is_none() {
-                // push the current "state" so we can detect loops
-                history.push(((highest_y + 1) as usize, input_index));
-
-                // check if we hit looping point
-                for period in 5..=(ring_buffer_size / 2) {
-                    if iteration != 0 && history.len() >= (period * 2) {
-                        let mut all_match = true;
-                        for period_index in 0..period {
-                            let one_index = period_index as isize;
-                            let other_index = (period + period_index) as isize;
-                            let one = history.get(-1 - one_index);
-                            let other = history.get(-1 - other_index);
-
-                            if let (Some((_y1, input_index1)), Some((_y2, input_index2))) =
-                                (one, other)
-                            {
-                                if input_index1 == input_index2 {
-                                    continue;
-                                }
-                            }
-
-                            all_match = false;
-                            break;
-                        }
-
-                        if all_match {
-                            let inc = history.get(-1).unwrap().0
-                                - history.get(-1 - (period as isize)).unwrap().0;
-                            let periods_to_skip = (REQUIRED_ITERATIONS - iteration) / period;
-
-                            iteration += period * periods_to_skip;
-                            let y_addition = inc * periods_to_skip;
-                            found_y_addition = Some(y_addition);
-                            break;
-                        }
-                    }
-                }
-            }
+        let tracked = tower_cycle(input).ok_or_else(|| {
+            let message = format!("no cycle found in the first {MAX_BLOCKS_BEFORE_CYCLE} blocks");
+            SolveError::invalid(message)
+        })?;
 
-            // iteration
-            let block = BlockType::TYPES[iteration % 5];
-            let block_height = block.get_height();
-            let mut x = 2usize;
-            let mut y = (highest_y + 3) as usize + block_height;
+        Ok(tracked.value_at(REQUIRED_ITERATIONS) as usize)
+    }
+}
 
-            while chamber.len() <= y {
-                chamber.push([false; 7]);
-            }
+/// Finds how the height of the tower repeats as blocks are dropped, or `None` if it does not
+/// repeat within [`MAX_BLOCKS_BEFORE_CYCLE`] blocks.
+fn tower_cycle(jets: &[bool]) -> Option<cycle::TrackedCycle> {
+    // the tower grows the same way every time the chamber gets back into the same state, so
+    // simulate until that happens and extrapolate the height from there
+    let mut chamber = Chamber::new(jets);
+    cycle::detect_by_key(|iteration| {
+        // patterns that never fill a column do not repeat, give up instead of running out of
+        // memory
+        if iteration > MAX_BLOCKS_BEFORE_CYCLE {
+            return None;
+        }
+        if iteration != 0 {
+            chamber.drop_block();
+        }
+        Some((chamber.state_key(), chamber.height as isize))
+    })
+}
 
-            debug_assert!(
-                !block.collides(x as isize, y as isize, &chamber),
-                "newly spawned block should not collide"
-            );
-
-            loop {
-                // move horizontal
-                let movement = input[input_index % input.len()];
-                input_index += 1;
-                input_index %= input.len();
-
-                let new_x = if movement {
-                    x as isize + 1
-                } else {
-                    x as isize - 1
-                };
-                if !block.collides(new_x, y as isize, &chamber) {
-                    x = new_x as usize;
-                } else {
-                }
+/// How many blocks the gold part drops while looking for a cycle. Real inputs repeat after a few
+/// thousand blocks.
+const MAX_BLOCKS_BEFORE_CYCLE: usize = 1_000_000;
+
+/// The chamber that the blocks fall into, with the blocks that came to rest.
+struct Chamber<'a> {
+    /// The jet pattern, where `true` pushes to the right.
+    jets: &'a [bool],
+    /// The index of the next jet in `jets`.
+    jet_index: usize,
+    /// The number of blocks that have been dropped so far.
+    block_count: usize,
+    /// The rows of the chamber from the bottom up. There can be empty rows above `height`.
+    rows: Vec<[bool; 7]>,
+    /// The height of the tower.
+    height: usize,
+    /// The height of the highest tile in each column.
+    column_heights: [usize; 7],
+}
 
-                // drop down
-                if !block.collides(x as isize, y as isize - 1, &chamber) {
-                    y -= 1;
-                } else {
-                    // could not drop down
-                    // write the block to the chamber
-                    for (tile_x, tile_y) in block.get_tiles() {
-                        // let tile_y = block_height - tile_y;
-                        chamber[y - *tile_y as usize][x + *tile_x as usize] = true;
-                    }
-                    highest_y = highest_y.max(y as isize);
-                    break;
-                }
-            }
+impl<'a> Chamber<'a> {
+    fn new(jets: &'a [bool]) -> Self {
+        Self {
+            jets,
+            jet_index: 0,
+            block_count: 0,
+            rows: vec![],
+            height: 0,
+            column_heights: [0; 7],
+        }
+    }
 
-            iteration += 1;
+    /// Identifies the state of the chamber. Chambers with the same key behave the same from then
+    /// on, as long as falling blocks cannot pass the highest tile of every column.
+    fn state_key(&self) -> (usize, usize, [usize; 7]) {
+        let depths = self.column_heights.map(|h| self.height - h);
+        (self.block_count % 5, self.jet_index, depths)
+    }
+
+    /// Drops the next block until it comes to rest.
+    fn drop_block(&mut self) {
+        let block = BlockType::TYPES[self.block_count % 5];
+        self.block_count += 1;
+
+        // blocks are positioned by their top left corner
+        let mut x = 2usize;
+        let mut y = self.height + 2 + block.get_height();
+
+        while self.rows.len() <= y {
+            self.rows.push([false; 7]);
         }
 
-        highest_y as usize - 1 + found_y_addition.unwrap() + 2
+        debug_assert!(
+            !block.collides(x as isize, y as isize, &self.rows),
+            "newly spawned block should not collide"
+        );
+
+        loop {
+            // move horizontal
+            let movement = self.jets[self.jet_index];
+            self.jet_index = (self.jet_index + 1) % self.jets.len();
+
+            let new_x = if movement {
+                x as isize + 1
+            } else {
+                x as isize - 1
+            };
+            if !block.collides(new_x, y as isize, &self.rows) {
+                x = new_x as usize;
+            }
+
+            // drop down
+            if !block.collides(x as isize, y as isize - 1, &self.rows) {
+                y -= 1;
+            } else {
+                // could not drop down
+                // write the block to the chamber
+                for (tile_x, tile_y) in block.get_tiles() {
+                    let (tile_x, tile_y) = (x + *tile_x as usize, y - *tile_y as usize);
+                    self.rows[tile_y][tile_x] = true;
+                    self.column_heights[tile_x] = self.column_heights[tile_x].max(tile_y + 1);
+                }
+                self.height = self.height.max(y + 1);
+                break;
+            }
+        }
     }
 }
 
@@ -249,3 +256,28 @@ fn test_gold_real() {
     let output = Day::calculate_gold(Day::INPUT_REAL);
     assert_eq!(1561739130391, output);
 }
+
+#[test]
+fn test_malformed_input() {
+    assert_eq!(
+        Day::try_calculate_silver(">><U>").unwrap_err(),
+        SolveError::Parse {
+            line: 1,
+            column: 4,
+            message: "expected a jet (`<` or `>`)".to_string()
+        }
+    );
+    assert!(matches!(
+        Day::try_calculate_gold(""),
+        Err(SolveError::Invalid(_))
+    ));
+}
+
+#[test]
+fn test_no_cycle() {
+    // found by fuzzing: the rightmost column is never filled, which used to run out of memory
+    assert!(matches!(
+        Day::try_calculate_gold("<"),
+        Err(SolveError::Invalid(_))
+    ));
+}
@@ -3,7 +3,7 @@ use std::{collections::VecDeque, ops::Add};
 use ahash::AHashSet;
 use tinyvec::ArrayVec;
 
-use crate::utils::{fast_parse_int_from_bytes, split_once};
+use crate::utils::Cursor;
 
 use super::*;
 
@@ -51,21 +51,35 @@ impl SolutionSilver<isize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> isize {
-        let mut cubes = AHashSet::<Vec3>::default();
-        input
-            .as_bytes()
-            .split(|b| *b == b'\n')
-            .map(|line| {
-                let (num1, line) = split_once(line, b',').unwrap();
-                let (num2, num3) = split_once(line, b',').unwrap();
+    type Parsed<'a> = Vec<Vec3>;
+
+    fn parse(input: &str) -> Result<Vec<Vec3>, SolveError> {
+        let mut cursor = Cursor::new(input);
+        let mut cubes = vec![];
+        while !cursor.is_empty() {
+            let x = parse_coordinate(&mut cursor)?;
+            cursor.expect(b",")?;
+            let y = parse_coordinate(&mut cursor)?;
+            cursor.expect(b",")?;
+            let z = parse_coordinate(&mut cursor)?;
+            if !cursor.is_empty() {
+                cursor.expect(b"\n")?;
+            }
 
-                let num1 = fast_parse_int_from_bytes(num1) as isize;
-                let num2 = fast_parse_int_from_bytes(num2) as isize;
-                let num3 = fast_parse_int_from_bytes(num3) as isize;
+            cubes.push(Vec3(x, y, z));
+        }
 
-                let vec = Vec3(num1, num2, num3);
+        if cubes.is_empty() {
+            return Err(SolveError::invalid("the droplet has no cubes"));
+        }
+        Ok(cubes)
+    }
 
+    fn solve_silver(input: &Vec<Vec3>) -> isize {
+        let mut cubes = AHashSet::<Vec3>::default();
+        input
+            .iter()
+            .map(|&vec| {
                 let sides = 6;
                 let count_touching = IMMEDIATE_NEIGHBOURS
                     .into_iter()
@@ -74,30 +88,40 @@ impl SolutionSilver<isize> for Day {
                         cubes.contains(&neighbour)
                     })
                     .count() as isize;
-                cubes.insert(Vec3(num1, num2, num3));
+                cubes.insert(vec);
                 sides - count_touching * 2
             })
             .sum()
     }
+
+    /// Generates a droplet of `size` cubes, which grows from a single cube by adding cubes next to
+    /// the ones that are already there. It can enclose pockets of air.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let mut cubes = vec![Vec3(0, 0, 0)];
+        let mut taken: AHashSet<Vec3> = cubes.iter().copied().collect();
+        while cubes.len() < size.max(1) {
+            let cube = *rng.choose(&cubes) + *rng.choose(&IMMEDIATE_NEIGHBOURS);
+            if taken.insert(cube) {
+                cubes.push(cube);
+            }
+        }
+        rng.shuffle(&mut cubes);
+
+        // the puzzle input has no negative coordinates
+        let min = |axis: fn(&Vec3) -> isize| cubes.iter().map(axis).min().unwrap();
+        let min = Vec3(min(|c| c.0), min(|c| c.1), min(|c| c.2));
+        let lines: Vec<String> = cubes
+            .iter()
+            .map(|c| format!("{},{},{}", c.0 - min.0, c.1 - min.1, c.2 - min.2))
+            .collect();
+        Some(lines.join("\n"))
+    }
 }
 
 impl SolutionGold<isize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
+    fn solve_gold(input: &Vec<Vec3>) -> usize {
         // insert all cubes
-        let voxels: AHashSet<Vec3> = input
-            .as_bytes()
-            .split(|b| *b == b'\n')
-            .map(|line| {
-                let (num1, line) = split_once(line, b',').unwrap();
-                let (num2, num3) = split_once(line, b',').unwrap();
-
-                let num1 = fast_parse_int_from_bytes(num1) as isize;
-                let num2 = fast_parse_int_from_bytes(num2) as isize;
-                let num3 = fast_parse_int_from_bytes(num3) as isize;
-
-                Vec3(num1, num2, num3)
-            })
-            .collect();
+        let voxels: AHashSet<Vec3> = input.iter().copied().collect();
 
         let first_voxel = *voxels.iter().min().unwrap();
         let exposed_direction = 0;
@@ -120,6 +144,17 @@ impl SolutionGold<isize, usize> for Day {
     }
 }
 
+/// Coordinates are at most this large, so the neighbours of a cube cannot overflow.
+const MAX_COORDINATE: usize = i32::MAX as usize;
+
+fn parse_coordinate(cursor: &mut Cursor) -> Result<isize, SolveError> {
+    let start = *cursor;
+    match cursor.uint()? {
+        coordinate @ 0..=MAX_COORDINATE => Ok(coordinate as isize),
+        _ => Err(start.error(format!("expected a coordinate up to {MAX_COORDINATE}"))),
+    }
+}
+
 /// DFS to find other exposed cubes.
 ///
 /// We need to take care to only count faces that are actually exposed to outside air.
@@ -191,7 +226,7 @@ fn find_other_exposed_voxels(
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
-struct Vec3(isize, isize, isize);
+pub struct Vec3(isize, isize, isize);
 
 impl Add for Vec3 {
     type Output = Vec3;
@@ -239,3 +274,27 @@ fn test_gold_real() {
     let output = Day::calculate_gold(Day::INPUT_REAL);
     assert_eq!(2458, output);
 }
+
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen("2,2,2", "2,2;2", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 1,
+            column: 4,
+            message: "expected `,`".to_string()
+        }
+    );
+
+    // found by fuzzing, these used to unwrap a missing comma or cube, or overflow
+    for input in [
+        "",
+        "1",
+        "1,1,",
+        "1,1,1\n\n1,1,2",
+        "1,1,99999999999999999999",
+    ] {
+        assert!(Day::try_calculate_gold(input).is_err(), "{input:?}");
+    }
+}
@@ -1,6 +1,6 @@
 use rayon::prelude::*;
 
-use crate::utils::fast_parse_int;
+use crate::cancel;
 
 use super::*;
 
@@ -11,42 +11,101 @@ impl SolutionSilver<usize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> usize {
-        let input = parse_input(input);
+    type Parsed<'a> = Vec<Blueprint>;
 
-        input
+    fn parse(input: &str) -> Result<Vec<Blueprint>, SolveError> {
+        parse_input(input)
+    }
+
+    fn solve_silver(blueprints: &Vec<Blueprint>) -> usize {
+        // the blueprints can be searched on other threads, which should stop with this one
+        let token = cancel::current();
+        blueprints
             .par_iter()
-            .map(get_quality::<24>)
+            .map(|blueprint| cancel::with_token(token.clone(), || get_quality::<24>(blueprint)))
             // .inspect(|quality| println!("quality: {}", quality))
             .enumerate()
             .map(|(i, v)| (i + 1) * v)
             .sum()
     }
+
+    /// Generates `size` blueprints, with costs in the same ranges as the puzzle input.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let blueprints: Vec<String> = (1..=size.max(1))
+            .map(|id| {
+                format!(
+                    "Blueprint {id}: Each ore robot costs {} ore. Each clay robot costs {} ore. \
+                     Each obsidian robot costs {} ore and {} clay. \
+                     Each geode robot costs {} ore and {} obsidian.",
+                    rng.usize(2..=4),
+                    rng.usize(2..=4),
+                    rng.usize(2..=4),
+                    rng.usize(5..=20),
+                    rng.usize(2..=4),
+                    rng.usize(7..=20),
+                )
+            })
+            .collect();
+        Some(blueprints.join("\n"))
+    }
 }
 
 impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
-        let input = parse_input(input);
-
-        input
+    fn solve_gold(blueprints: &Vec<Blueprint>) -> usize {
+        let token = cancel::current();
+        blueprints
             .par_iter()
             .take(3)
-            .map(get_quality::<32>)
+            .map(|blueprint| cancel::with_token(token.clone(), || get_quality::<32>(blueprint)))
             // .inspect(|quality| println!("quality: {}", quality))
             .product()
     }
 }
 
 fn get_quality<const MAX_TIME: usize>(costs: &Blueprint) -> usize {
-    get_quality_recursive::<MAX_TIME>(costs, State::new())
+    get_quality_recursive::<MAX_TIME>(costs, State::new(), &mut 0)
 }
 
-fn get_quality_recursive<const MAX_TIME: usize>(blueprint: &Blueprint, state: State) -> usize {
+/// The most geodes that could be opened in the `time_left` minutes after `state` if clay was free,
+/// an ore and an obsidian robot were built every minute for free, and a geode robot whenever there
+/// is enough ore and obsidian.
+fn geode_upper_bound(blueprint: &Blueprint, state: &State, time_left: usize) -> usize {
+    let (mut ore, mut ore_robots) = (state.ore, state.ore_robots);
+    let (mut obsidian, mut obsidian_robots) = (state.obsidian, state.obsidian_robots);
+    let (mut geodes, mut geode_robots) = (state.geodes, state.geode_robots);
+    for _ in 0..time_left {
+        let (ore_cost, obsidian_cost) = blueprint.geode_robot;
+        let build_geode_robot = ore >= ore_cost && obsidian >= obsidian_cost;
+        if build_geode_robot {
+            ore -= ore_cost;
+            obsidian -= obsidian_cost;
+        }
+        ore += ore_robots;
+        obsidian += obsidian_robots;
+        geodes += geode_robots;
+        ore_robots += 1;
+        obsidian_robots += 1;
+        geode_robots += usize::from(build_geode_robot);
+    }
+    geodes
+}
+
+/// Finds the most geodes that can be opened from `state`. Branches that cannot beat `best`, the
+/// most geodes found so far, are skipped.
+fn get_quality_recursive<const MAX_TIME: usize>(
+    blueprint: &Blueprint,
+    state: State,
+    best: &mut usize,
+) -> usize {
+    // the search can take very long for some blueprints
+    cancel::checkpoint();
+
     // NOTE: a lot of the optimizations here are not needed/not effective
     if state.time > MAX_TIME {
         return 0;
     }
     if state.time == MAX_TIME {
+        *best = (*best).max(state.geodes);
         return state.geodes;
     }
 
@@ -54,6 +113,10 @@ fn get_quality_recursive<const MAX_TIME: usize>(blueprint: &Blueprint, state: St
 
     let time_left = MAX_TIME - state.time;
 
+    if geode_upper_bound(blueprint, &state, time_left) <= *best {
+        return 0;
+    }
+
     let can_build_ore_robot = blueprint.ore_robot <= state.ore;
     let can_build_clay_robot = blueprint.clay_robot <= state.ore;
     let can_build_obsidian_robot =
@@ -73,25 +136,22 @@ fn get_quality_recursive<const MAX_TIME: usize>(blueprint: &Blueprint, state: St
     let can_keep_rebuying_geode_robot = state.ore_robots >= blueprint.geode_robot.0
         && state.obsidian_robots >= blueprint.geode_robot.1;
 
-    let have_enough_ore = state.ore > blueprint.max_qty_needed(ResourceType::Ore) * time_left;
-    let have_enough_clay = state.clay > blueprint.max_qty_needed(ResourceType::Clay) * time_left;
-    let have_enough_obsidian =
-        state.obsidian > blueprint.max_qty_needed(ResourceType::Obsidian) * time_left;
+    let needed_until_end = |resource| blueprint.max_qty_needed(resource).saturating_mul(time_left);
+    let have_enough_ore = state.ore > needed_until_end(ResourceType::Ore);
+    let have_enough_clay = state.clay > needed_until_end(ResourceType::Clay);
+    let have_enough_obsidian = state.obsidian > needed_until_end(ResourceType::Obsidian);
 
     // consider waiting to buy each type of robot
+    let buy = |resource, best: &mut usize| {
+        let Some(state) = state.clone().wait_and_buy(resource, blueprint, MAX_TIME) else {
+            return 0;
+        };
+        get_quality_recursive::<MAX_TIME>(blueprint, state, best)
+    };
 
     // Geode
     if state.obsidian_robots > 0 && state.time < (MAX_TIME - 1) {
-        let mut state = state.clone();
-
-        while !state.can_buy(ResourceType::Geode, blueprint) {
-            state.tick();
-        }
-
-        state.buy(ResourceType::Geode, blueprint);
-        state.tick();
-
-        best_outcome = best_outcome.max(get_quality_recursive::<MAX_TIME>(blueprint, state));
+        best_outcome = best_outcome.max(buy(ResourceType::Geode, best));
     }
 
     // no need to check other options if we can just keep rebuying the geode robot
@@ -101,16 +161,7 @@ fn get_quality_recursive<const MAX_TIME: usize>(blueprint: &Blueprint, state: St
 
     // Obsidian
     if state.clay_robots > 0 && !have_enough_obsidian_gen && state.time < (MAX_TIME - 2) {
-        let mut state = state.clone();
-
-        while !state.can_buy(ResourceType::Obsidian, blueprint) {
-            state.tick();
-        }
-
-        state.buy(ResourceType::Obsidian, blueprint);
-        state.tick();
-
-        best_outcome = best_outcome.max(get_quality_recursive::<MAX_TIME>(blueprint, state));
+        best_outcome = best_outcome.max(buy(ResourceType::Obsidian, best));
     }
 
     // don't bother waiting or buying other stuff if we're close to geode buy loop
@@ -123,30 +174,12 @@ fn get_quality_recursive<const MAX_TIME: usize>(blueprint: &Blueprint, state: St
 
     // Clay
     if !have_enough_clay_gen && state.time < (MAX_TIME - 3) {
-        let mut state = state.clone();
-
-        while !state.can_buy(ResourceType::Clay, blueprint) {
-            state.tick();
-        }
-
-        state.buy(ResourceType::Clay, blueprint);
-        state.tick();
-
-        best_outcome = best_outcome.max(get_quality_recursive::<MAX_TIME>(blueprint, state));
+        best_outcome = best_outcome.max(buy(ResourceType::Clay, best));
     }
 
     // Ore
     if !have_enough_ore_gen && state.time < (MAX_TIME - 2) {
-        let mut state = state.clone();
-
-        while !state.can_buy(ResourceType::Ore, blueprint) {
-            state.tick();
-        }
-
-        state.buy(ResourceType::Ore, blueprint);
-        state.tick();
-
-        best_outcome = best_outcome.max(get_quality_recursive::<MAX_TIME>(blueprint, state));
+        best_outcome = best_outcome.max(buy(ResourceType::Ore, best));
     }
 
     // consider doing nothing
@@ -185,8 +218,7 @@ fn get_quality_recursive<const MAX_TIME: usize>(blueprint: &Blueprint, state: St
         while state.time < MAX_TIME {
             state.tick();
         }
-        best_outcome =
-            best_outcome.max(get_quality_recursive::<MAX_TIME>(blueprint, state.clone()));
+        best_outcome = best_outcome.max(get_quality_recursive::<MAX_TIME>(blueprint, state, best));
     }
 
     best_outcome
@@ -254,6 +286,26 @@ impl State {
         }
     }
 
+    /// Waits until the robot for `resource` can be bought, buys it and waits for it to be built.
+    /// Returns `None` if it cannot be bought before `max_time`.
+    fn wait_and_buy(
+        mut self,
+        resource: ResourceType,
+        blueprint: &Blueprint,
+        max_time: usize,
+    ) -> Option<Self> {
+        while !self.can_buy(resource.clone(), blueprint) {
+            if self.time >= max_time {
+                return None;
+            }
+            self.tick();
+        }
+
+        self.buy(resource, blueprint);
+        self.tick();
+        Some(self)
+    }
+
     fn buy(&mut self, geode: ResourceType, blueprint: &Blueprint) {
         match geode {
             ResourceType::Ore => {
@@ -279,49 +331,75 @@ impl State {
 }
 
 #[derive(Debug, Clone)]
-enum ResourceType {
+pub enum ResourceType {
     Ore,
     Clay,
     Obsidian,
     Geode,
 }
 
-fn parse_input(input: &str) -> Vec<Blueprint> {
+fn parse_input(input: &str) -> Result<Vec<Blueprint>, SolveError> {
     input
         .lines()
-        .map(|l| {
-            let mut split = l.split(" costs ");
-            split.next().expect("first part");
+        .enumerate()
+        .map(|(index, line)| {
+            let id_str = expect(input, line, "Blueprint ")?;
+            let (id, rest) = parse_number(input, id_str)?;
+            if id != index + 1 {
+                let message = format!("expected blueprint {}", index + 1);
+                return Err(SolveError::at(input, id_str, message));
+            }
 
-            let ore_cost = split.next().expect("ore cost");
-            let ore = fast_parse_int(ore_cost.split_once(' ').unwrap().0);
+            let rest = expect(input, rest, ": Each ore robot costs ")?;
+            let (ore, rest) = parse_number(input, rest)?;
 
-            let clay_cost = split.next().expect("clay cost");
-            let clay = fast_parse_int(clay_cost.split_once(' ').unwrap().0);
+            let rest = expect(input, rest, " ore. Each clay robot costs ")?;
+            let (clay, rest) = parse_number(input, rest)?;
 
-            let obs_cost = split.next().expect("obs cost");
-            let (obs_cost_1, obs_cost_2) = obs_cost.split_once(' ').unwrap();
-            let obs_1 = fast_parse_int(obs_cost_1);
-            let obs_2 = fast_parse_int(obs_cost_2["ore and ".len()..].split_once(' ').unwrap().0);
+            let rest = expect(input, rest, " ore. Each obsidian robot costs ")?;
+            let (obs_1, rest) = parse_number(input, rest)?;
+            let rest = expect(input, rest, " ore and ")?;
+            let (obs_2, rest) = parse_number(input, rest)?;
 
-            let geode_cost = split.next().expect("geode cost");
-            let (geode_cost_1, geode_cost_2) = geode_cost.split_once(' ').unwrap();
-            let geode_1 = fast_parse_int(geode_cost_1);
-            let geode_2 =
-                fast_parse_int(geode_cost_2["ore and ".len()..].split_once(' ').unwrap().0);
+            let rest = expect(input, rest, " clay. Each geode robot costs ")?;
+            let (geode_1, rest) = parse_number(input, rest)?;
+            let rest = expect(input, rest, " ore and ")?;
+            let (geode_2, rest) = parse_number(input, rest)?;
 
-            Blueprint {
+            let rest = expect(input, rest, " obsidian.")?;
+            if !rest.is_empty() {
+                return Err(SolveError::at(input, rest, "expected the end of the line"));
+            }
+
+            Ok(Blueprint {
                 ore_robot: ore,
                 clay_robot: clay,
                 obsidian_robot: (obs_1, obs_2),
                 geode_robot: (geode_1, geode_2),
-            }
+            })
         })
         .collect()
 }
 
+/// Strips `literal` from the start of `text`, which is a subslice of `input`.
+fn expect<'a>(input: &str, text: &'a str, literal: &str) -> Result<&'a str, SolveError> {
+    text.strip_prefix(literal)
+        .ok_or_else(|| SolveError::at(input, text, format!("expected `{literal}`")))
+}
+
+/// Parses the number at the start of `text`, which is a subslice of `input`, and returns it
+/// together with the remaining text.
+fn parse_number<'a>(input: &str, text: &'a str) -> Result<(usize, &'a str), SolveError> {
+    let len = text.bytes().take_while(u8::is_ascii_digit).count();
+    let (number, rest) = text.split_at(len);
+    let number = number
+        .parse()
+        .map_err(|_| SolveError::at(input, text, "expected a number"))?;
+    Ok((number, rest))
+}
+
 #[derive(Debug, Clone)]
-struct Blueprint {
+pub struct Blueprint {
     /// Price in ore
     ore_robot: usize,
     /// Price in ore
@@ -373,7 +451,7 @@ fn test_gold_real() {
 
 #[test]
 fn simulate_example_run() {
-    let blueprint = parse_input(Day::INPUT_SAMPLE)[0].clone();
+    let blueprint = parse_input(Day::INPUT_SAMPLE).unwrap()[0].clone();
     let mut state = State::new();
 
     debug_assert_eq!(state.time, 0);
@@ -402,3 +480,46 @@ fn simulate_example_run() {
     debug_assert_eq!(state.ore, 2);
     debug_assert_eq!(state.clay, 1);
 }
+
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen("costs 3 ore and 8 clay", "costs 3 ore and clay", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 2,
+            column: 107,
+            message: "expected a number".to_string()
+        }
+    );
+
+    let input = Day::INPUT_SAMPLE.replacen("Blueprint 2", "Blueprint 3", 1);
+    assert!(matches!(
+        Day::try_calculate_gold(&input),
+        Err(SolveError::Parse {
+            line: 2,
+            column: 11,
+            ..
+        })
+    ));
+
+    // found by fuzzing, these used to take seconds, minutes or forever, or overflow
+    let blueprint = |ore: usize, clay: usize, geode_ore: usize, obsidian: usize| {
+        format!(
+            "Blueprint 1: Each ore robot costs {ore} ore. Each clay robot costs 2 ore. \
+             Each obsidian robot costs 3 ore and {clay} clay. \
+             Each geode robot costs {geode_ore} ore and {obsidian} obsidian."
+        )
+    };
+    for ((ore, clay, geode_ore, obsidian), silver, gold) in [
+        ((4, 14, 2, 7), 9, 56),
+        ((4, 14, 2, 2147483647), 0, 0),
+        ((1000000000000, 14, 2, 7), 9, 43),
+        ((4, 1000000000000, 2, 7), 0, 0),
+        ((4, 14, 9223372036854775807, 7), 0, 0),
+    ] {
+        let input = blueprint(ore, clay, geode_ore, obsidian);
+        assert_eq!(Day::try_calculate_silver(&input), Ok(silver), "{input:?}");
+        assert_eq!(Day::try_calculate_gold(&input), Ok(gold), "{input:?}");
+    }
+}
@@ -1,5 +1,7 @@
 use std::collections::VecDeque;
 
+use crate::utils::checked_parse_signed;
+
 use super::*;
 
 pub struct Day;
@@ -9,9 +11,34 @@ impl SolutionSilver<isize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> isize {
-        let input_original: Vec<isize> = input.lines().map(|l| l.parse().unwrap()).collect();
+    type Parsed<'a> = Vec<isize>;
+
+    fn parse(input: &str) -> Result<Vec<isize>, SolveError> {
+        // the gold part multiplies the numbers by the key and adds three of them together
+        let max = isize::MAX / KEY / 3;
+        let numbers = input
+            .lines()
+            .map(|l| match checked_parse_signed(l.as_bytes()) {
+                Ok(number) if !(-max..=max).contains(&number) => Err(SolveError::at(
+                    input,
+                    l,
+                    format!("expected a number of at most {max}"),
+                )),
+                number => number.map_err(|e| e.at(input, l)),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if numbers.len() < 2 {
+            return Err(SolveError::invalid("expected at least 2 numbers"));
+        }
+        if !numbers.contains(&0) {
+            return Err(SolveError::invalid("expected a 0 to count from"));
+        }
+
+        Ok(numbers)
+    }
 
+    fn solve_silver(input_original: &Vec<isize>) -> isize {
         // add an `enumerate` with original indices. We can't scan later because there are duplicate numbers.
         let mut values = input_original
             .iter()
@@ -38,13 +65,26 @@ impl SolutionSilver<isize> for Day {
             + values[(index_of_0 + 2000) % values.len()].1
             + values[(index_of_0 + 3000) % values.len()].1
     }
+
+    /// Generates a file of `size` numbers with duplicates and exactly one zero.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let size = size.max(2);
+        let range = size as isize * 3;
+        let mut numbers: Vec<isize> = (0..size - 1)
+            .map(|_| match rng.isize(-range..=range) {
+                0 => range + 1,
+                n => n,
+            })
+            .collect();
+        numbers.insert(rng.usize(0..=numbers.len()), 0);
+
+        let lines: Vec<String> = numbers.iter().map(ToString::to_string).collect();
+        Some(lines.join("\n"))
+    }
 }
 
 impl SolutionGold<isize, isize> for Day {
-    fn calculate_gold(input: &str) -> isize {
-        const KEY: isize = 811_589_153;
-        let input_original: Vec<isize> = input.lines().map(|l| l.parse().unwrap()).collect();
-
+    fn solve_gold(input_original: &Vec<isize>) -> isize {
         // add an `enumerate` with original indices. We can't scan later because there are duplicate numbers.
         let mut values = input_original
             .iter()
@@ -76,6 +116,9 @@ impl SolutionGold<isize, isize> for Day {
     }
 }
 
+/// The decryption key of the gold part.
+const KEY: isize = 811_589_153;
+
 #[test]
 fn test_silver_sample() {
     let output = Day::calculate_silver(Day::INPUT_SAMPLE);
@@ -99,3 +142,72 @@ fn test_gold_real() {
     let output = Day::calculate_gold(Day::INPUT_REAL);
     assert_eq!(2455057187825, output);
 }
+
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen("-3", "-3x", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 3,
+            column: 3,
+            message: "expected a digit".to_string()
+        }
+    );
+
+    // found by fuzzing, these used to divide by zero or not find the 0
+    for input in ["", "1", "0", "1\n2"] {
+        assert!(
+            matches!(Day::try_calculate_gold(input), Err(SolveError::Invalid(_))),
+            "{input:?}"
+        );
+    }
+
+    // the second one was found by fuzzing, and used to overflow
+    for number in [isize::MAX / KEY, isize::MIN] {
+        let input = format!("0\n{number}");
+        assert!(matches!(
+            Day::try_calculate_gold(&input),
+            Err(SolveError::Parse {
+                line: 2,
+                column: 1,
+                ..
+            })
+        ));
+    }
+}
+
+/// Mixes the numbers by removing each one and inserting it at its new index.
+#[cfg(test)]
+fn calculate_naive(input: &str) -> (isize, isize) {
+    let mix = |key: isize, rounds: usize| {
+        let numbers: Vec<isize> = input
+            .lines()
+            .map(|l| l.parse::<isize>().unwrap() * key)
+            .collect();
+        let mut mixed: Vec<(usize, isize)> = numbers.iter().copied().enumerate().collect();
+        for _ in 0..rounds {
+            for index in 0..numbers.len() {
+                let position = mixed.iter().position(|&(i, _)| i == index).unwrap();
+                let element = mixed.remove(position);
+                let new_position = (position as isize + element.1).rem_euclid(mixed.len() as isize);
+                mixed.insert(new_position as usize, element);
+            }
+        }
+
+        let zero = mixed.iter().position(|&(_, n)| n == 0).unwrap();
+        [1000, 2000, 3000]
+            .into_iter()
+            .map(|offset| mixed[(zero + offset) % mixed.len()].1)
+            .sum()
+    };
+    (mix(1, 1), mix(811_589_153, 10))
+}
+
+#[test]
+fn test_differential() {
+    crate::property::check(1000, crate::property::input::<Day, _>, |input| {
+        let actual = (Day::calculate_silver(input), Day::calculate_gold(input));
+        assert_eq!(calculate_naive(input), actual);
+    });
+}
@@ -1,85 +1,125 @@
-use ahash::AHashMap;
+use ahash::{AHashMap, AHashSet};
 
-use crate::utils::fast_parse_int_from_bytes;
+use crate::utils::Cursor;
 
 use super::*;
 
 pub struct Day;
 
+/// The most monkeys in a chain from `root`, which keeps the recursive solvers from overflowing the
+/// stack. Real inputs have chains of about 90 monkeys.
+const MAX_DEPTH: usize = 1000;
+
 impl SolutionSilver<isize> for Day {
     const DAY: u32 = 21;
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> isize {
-        let hashmap = parse_input(input);
+    type Parsed<'a> = AHashMap<u32, Operation>;
+
+    fn parse(input: &str) -> Result<AHashMap<u32, Operation>, SolveError> {
+        parse_input(input)
+    }
+
+    fn solve_silver(hashmap: &AHashMap<u32, Operation>) -> isize {
+        Self::try_solve_silver(hashmap).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Fails if a monkey divides by zero or yells a number that does not fit in an `isize`.
+    fn try_solve_silver(hashmap: &AHashMap<u32, Operation>) -> Result<isize, SolveError> {
         let root = u32::from_ne_bytes([b'r', b'o', b'o', b't']);
 
-        solve_recursively(&hashmap, root)
+        solve_recursively(hashmap, root)
+    }
+
+    /// Generates about `size` monkeys (3 to 200000). One side of `root` depends on `humn` through
+    /// a chain of up to 100 operations. Every division is exact, both for the number `humn` yells
+    /// in the input and for the number the gold part looks for.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        Some(MonkeyGenerator::new(rng).generate(size.clamp(3, 200_000)))
     }
 }
 
 impl SolutionGold<isize, isize> for Day {
-    fn calculate_gold(input: &str) -> isize {
-        let map = parse_input(input);
+    fn solve_gold(map: &AHashMap<u32, Operation>) -> isize {
+        Self::try_solve_gold(map).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Fails if `root` does not compare `humn` to a number, or if a number overflows or is divided
+    /// by zero on the way.
+    fn try_solve_gold(map: &AHashMap<u32, Operation>) -> Result<isize, SolveError> {
         let root = u32::from_ne_bytes([b'r', b'o', b'o', b't']);
 
         let (a, b) = match map[&root] {
-            Operation::Constant(_) => unreachable!("root should not be constant"),
+            Operation::Constant(_) => {
+                return Err(SolveError::invalid("`root` should wait for two monkeys"));
+            }
             Operation::Add(a, b) => (a, b),
             Operation::Sub(a, b) => (a, b),
             Operation::Mul(a, b) => (a, b),
             Operation::Div(a, b) => (a, b),
         };
 
-        let a = solve_recursively_inverse(&map, a);
-        let b = solve_recursively_inverse(&map, b);
+        let a = solve_recursively_inverse(map, a)?;
+        let b = solve_recursively_inverse(map, b)?;
 
         match (a, b) {
             (InverseResult::Value(mut val), InverseResult::InverseOperations(ops))
             | (InverseResult::InverseOperations(ops), InverseResult::Value(mut val)) => {
                 for op in ops.iter().rev() {
-                    match op {
-                        SimpleOperation::Add(c) => val += c,
-                        SimpleOperation::Sub(c) => val -= c,
-                        SimpleOperation::Mul(c) => val *= c,
-                        SimpleOperation::Div(c) => val /= c,
-                        SimpleOperation::Neg => val = -val,
-                        SimpleOperation::Inv => val = 1 / val,
-                    }
+                    let new_val = match op {
+                        SimpleOperation::Add(c) => val.checked_add(*c),
+                        SimpleOperation::Sub(c) => val.checked_sub(*c),
+                        SimpleOperation::Mul(c) => val.checked_mul(*c),
+                        SimpleOperation::Div(c) => val.checked_div(*c),
+                        SimpleOperation::Neg => val.checked_neg(),
+                        SimpleOperation::Inv => 1isize.checked_div(val),
+                    };
+                    val = new_val.ok_or_else(|| {
+                        SolveError::invalid("the number `humn` should yell overflows")
+                    })?;
                 }
 
-                val
+                Ok(val)
+            }
+            _ => {
+                let message = "only one side of `root` should wait for `humn`";
+                Err(SolveError::invalid(message))
             }
-            _ => unreachable!(),
         }
     }
 }
 
-fn solve_recursively(map: &AHashMap<u32, Operation>, name: u32) -> isize {
-    match map[&name] {
-        Operation::Constant(x) => x,
-        Operation::Add(a, b) => solve_recursively(map, a) + solve_recursively(map, b),
-        Operation::Sub(a, b) => solve_recursively(map, a) - solve_recursively(map, b),
-        Operation::Mul(a, b) => solve_recursively(map, a) * solve_recursively(map, b),
-        Operation::Div(a, b) => solve_recursively(map, a) / solve_recursively(map, b),
-    }
+fn solve_recursively(map: &AHashMap<u32, Operation>, name: u32) -> Result<isize, SolveError> {
+    let value = match map[&name] {
+        Operation::Constant(x) => Some(x),
+        Operation::Add(a, b) => solve_recursively(map, a)?.checked_add(solve_recursively(map, b)?),
+        Operation::Sub(a, b) => solve_recursively(map, a)?.checked_sub(solve_recursively(map, b)?),
+        Operation::Mul(a, b) => solve_recursively(map, a)?.checked_mul(solve_recursively(map, b)?),
+        Operation::Div(a, b) => solve_recursively(map, a)?.checked_div(solve_recursively(map, b)?),
+    };
+    value.ok_or_else(|| overflow(name))
 }
 
-fn solve_recursively_inverse(map: &AHashMap<u32, Operation>, name: u32) -> InverseResult {
+fn solve_recursively_inverse(
+    map: &AHashMap<u32, Operation>,
+    name: u32,
+) -> Result<InverseResult, SolveError> {
     let humn = u32::from_ne_bytes([b'h', b'u', b'm', b'n']);
 
     debug_assert_ne!(name, u32::from_ne_bytes([b'r', b'o', b'o', b't']));
 
-    match map[&name] {
+    let result = match map[&name] {
         Operation::Constant(_) if name == humn => InverseResult::InverseOperations(vec![]),
         Operation::Constant(x) => InverseResult::Value(x),
         Operation::Add(a, b) => {
-            let a = solve_recursively_inverse(map, a);
-            let b = solve_recursively_inverse(map, b);
+            let a = solve_recursively_inverse(map, a)?;
+            let b = solve_recursively_inverse(map, b)?;
 
             match (a, b) {
-                (InverseResult::Value(a), InverseResult::Value(b)) => InverseResult::Value(a + b),
+                (InverseResult::Value(a), InverseResult::Value(b)) => {
+                    InverseResult::Value(a.checked_add(b).ok_or_else(|| overflow(name))?)
+                }
                 (InverseResult::Value(c), InverseResult::InverseOperations(mut ops)) => {
                     // X = c + H -> H = X - c
                     ops.push(SimpleOperation::Sub(c));
@@ -96,11 +136,13 @@ fn solve_recursively_inverse(map: &AHashMap<u32, Operation>, name: u32) -> Inver
             }
         }
         Operation::Sub(a, b) => {
-            let a = solve_recursively_inverse(map, a);
-            let b = solve_recursively_inverse(map, b);
+            let a = solve_recursively_inverse(map, a)?;
+            let b = solve_recursively_inverse(map, b)?;
 
             match (a, b) {
-                (InverseResult::Value(a), InverseResult::Value(b)) => InverseResult::Value(a - b),
+                (InverseResult::Value(a), InverseResult::Value(b)) => {
+                    InverseResult::Value(a.checked_sub(b).ok_or_else(|| overflow(name))?)
+                }
                 (InverseResult::Value(c), InverseResult::InverseOperations(mut ops)) => {
                     // X = c - H -> H = -X + c
                     ops.push(SimpleOperation::Add(c));
@@ -118,11 +160,13 @@ fn solve_recursively_inverse(map: &AHashMap<u32, Operation>, name: u32) -> Inver
             }
         }
         Operation::Mul(a, b) => {
-            let a = solve_recursively_inverse(map, a);
-            let b = solve_recursively_inverse(map, b);
+            let a = solve_recursively_inverse(map, a)?;
+            let b = solve_recursively_inverse(map, b)?;
 
             match (a, b) {
-                (InverseResult::Value(a), InverseResult::Value(b)) => InverseResult::Value(a * b),
+                (InverseResult::Value(a), InverseResult::Value(b)) => {
+                    InverseResult::Value(a.checked_mul(b).ok_or_else(|| overflow(name))?)
+                }
                 (InverseResult::Value(c), InverseResult::InverseOperations(mut ops)) => {
                     // X = c * H -> H = X / c
                     ops.push(SimpleOperation::Div(c));
@@ -139,11 +183,13 @@ fn solve_recursively_inverse(map: &AHashMap<u32, Operation>, name: u32) -> Inver
             }
         }
         Operation::Div(a, b) => {
-            let a = solve_recursively_inverse(map, a);
-            let b = solve_recursively_inverse(map, b);
+            let a = solve_recursively_inverse(map, a)?;
+            let b = solve_recursively_inverse(map, b)?;
 
             match (a, b) {
-                (InverseResult::Value(a), InverseResult::Value(b)) => InverseResult::Value(a / b),
+                (InverseResult::Value(a), InverseResult::Value(b)) => {
+                    InverseResult::Value(a.checked_div(b).ok_or_else(|| overflow(name))?)
+                }
                 (InverseResult::Value(c), InverseResult::InverseOperations(mut ops)) => {
                     // X = c / H -> H = 1 / X * c
                     // NOTE: `inv` is extremely lossy on ints! we should not be doing this!
@@ -162,40 +208,270 @@ fn solve_recursively_inverse(map: &AHashMap<u32, Operation>, name: u32) -> Inver
                 }
             }
         }
-    }
+    };
+    Ok(result)
 }
 
-fn parse_input(input: &str) -> AHashMap<u32, Operation> {
-    input
-        .lines()
-        .map(|line| {
-            let bytes = line.as_bytes();
-            let name = u32::from_ne_bytes(*bytes.array_chunks::<4>().next().unwrap());
-            let has_num = (bytes[6] as char).is_ascii_digit();
-            debug_assert_ne!(bytes[6], b'-'); // sanity check
-
-            let operation = if has_num {
-                Operation::Constant(fast_parse_int_from_bytes(&bytes[6..]) as isize)
-            } else {
-                let name1 = u32::from_ne_bytes(*(bytes[6..].array_chunks::<4>().next().unwrap()));
-                let name2 = u32::from_ne_bytes(*(bytes[13..].array_chunks::<4>().next().unwrap()));
-                let operation_char = bytes[11];
-                match operation_char {
-                    b'+' => Operation::Add(name1, name2),
-                    b'-' => Operation::Sub(name1, name2),
-                    b'*' => Operation::Mul(name1, name2),
-                    b'/' => Operation::Div(name1, name2),
-                    _ => unreachable!(),
+fn overflow(name: u32) -> SolveError {
+    let name = name_to_string(name);
+    SolveError::invalid(format!("monkey `{name}` overflows or divides by zero"))
+}
+
+/// Builds the random trees of monkeys for [`Day::generate`].
+struct MonkeyGenerator<'a> {
+    rng: &'a mut generate::Rng,
+    names: AHashSet<String>,
+    lines: Vec<String>,
+}
+
+impl<'a> MonkeyGenerator<'a> {
+    /// Numbers above this are not made any larger, so they cannot overflow.
+    const LIMIT: isize = 1 << 40;
+
+    fn new(rng: &'a mut generate::Rng) -> Self {
+        Self {
+            rng,
+            names: ["root", "humn"].map(String::from).into_iter().collect(),
+            lines: vec![],
+        }
+    }
+
+    fn generate(mut self, size: usize) -> String {
+        // a tree of n operations has n + 1 numbers. `root` is the first operation, the others are
+        // spread over the chain to `humn` and the subtrees next to it.
+        let operations = (size - 1) / 2;
+        let chain = self.rng.usize(0..=(operations - 1).min(100));
+        let mut others = vec![0; chain + 1];
+        for _ in 0..operations - 1 - chain {
+            others[self.rng.usize(0..=chain)] += 1;
+        }
+
+        let value = self.rng.isize(1..=1_000_000_000);
+        let other = self.number(value, others[0]);
+        let human = self.human(value, &others[1..]);
+        let (left, right) = match self.rng.chance(1, 2) {
+            true => (human, other),
+            false => (other, human),
+        };
+        self.lines.push(format!("root: {left} + {right}"));
+
+        self.rng.shuffle(&mut self.lines);
+        self.lines.join("\n")
+    }
+
+    fn name(&mut self) -> String {
+        loop {
+            let name = self.rng.word(4..=4);
+            if self.names.insert(name.clone()) {
+                return name;
+            }
+        }
+    }
+
+    /// A random number from 2 to 9 that divides `value`, if there is one.
+    fn divisor(&mut self, value: isize) -> Option<isize> {
+        let divisors: Vec<isize> = (2..=9).filter(|d| value % d == 0).collect();
+        (!divisors.is_empty()).then(|| *self.rng.choose(&divisors))
+    }
+
+    /// Adds a monkey that yells the positive `value` after `operations` operations, and returns
+    /// its name.
+    fn number(&mut self, value: isize, operations: usize) -> String {
+        let name = self.name();
+        if operations == 0 {
+            self.lines.push(format!("{name}: {value}"));
+            return name;
+        }
+
+        let (left, operator, right) = loop {
+            match self.rng.usize(0..=3) {
+                0 if value >= 2 => {
+                    let left = self.rng.isize(1..=value - 1);
+                    break (left, '+', value - left);
+                }
+                1 if value < Self::LIMIT => {
+                    let right = self.rng.isize(1..=1000);
+                    break (value + right, '-', right);
+                }
+                2 => {
+                    if let Some(divisor) = self.divisor(value) {
+                        break (value / divisor, '*', divisor);
+                    }
+                }
+                3 if value < Self::LIMIT => {
+                    let divisor = self.rng.isize(2..=9);
+                    break (value * divisor, '/', divisor);
                 }
+                _ => {}
+            }
+        };
+
+        let left_operations = self.rng.usize(0..=operations - 1);
+        let left = self.number(left, left_operations);
+        let right = self.number(right, operations - 1 - left_operations);
+        self.lines
+            .push(format!("{name}: {left} {operator} {right}"));
+        name
+    }
+
+    /// Adds the chain of monkeys from a monkey that yells `value` in the gold part to `humn`. The
+    /// other side of the monkey at each step gets the number of operations in `others`. Returns
+    /// the name of the first monkey.
+    fn human(&mut self, mut value: isize, others: &[usize]) -> String {
+        let new_name = |generator: &mut Self, last: bool| match last {
+            true => "humn".to_string(),
+            false => generator.name(),
+        };
+
+        let first = new_name(self, others.is_empty());
+        let mut name = first.clone();
+        let (mut multiplied, mut divided) = (1, 1);
+        for (i, &operations) in others.iter().enumerate() {
+            // `swap` puts the chain on the right side
+            let (operator, other, next_value, swap) = loop {
+                match self.rng.usize(0..=4) {
+                    0 if value >= 2 => {
+                        let other = self.rng.isize(1..=value - 1);
+                        break ('+', other, value - other, self.rng.chance(1, 2));
+                    }
+                    1 if value < Self::LIMIT => {
+                        let other = self.rng.isize(1..=1000);
+                        break ('-', other, value + other, false);
+                    }
+                    2 => {
+                        let next_value = self.rng.isize(1..=1000);
+                        break ('-', value + next_value, next_value, true);
+                    }
+                    3 if multiplied < 1_000_000 => {
+                        if let Some(divisor) = self.divisor(value) {
+                            multiplied *= divisor;
+                            break ('*', divisor, value / divisor, self.rng.chance(1, 2));
+                        }
+                    }
+                    4 if value < Self::LIMIT && divided < 1_000_000 => {
+                        let divisor = self.rng.isize(2..=9);
+                        divided *= divisor;
+                        break ('/', divisor, value * divisor, false);
+                    }
+                    _ => {}
+                }
+            };
+
+            let next = new_name(self, i + 1 == others.len());
+            let other = self.number(other, operations);
+            let line = match swap {
+                true => format!("{name}: {other} {operator} {next}"),
+                false => format!("{name}: {next} {operator} {other}"),
+            };
+            self.lines.push(line);
+            (name, value) = (next, next_value);
+        }
+
+        // adding a multiple of all divisors in the chain keeps every division exact
+        let offset = divided * self.rng.isize(0..=3);
+        self.lines.push(format!("humn: {}", value + offset));
+        first
+    }
+}
+
+fn parse_input(input: &str) -> Result<AHashMap<u32, Operation>, SolveError> {
+    let mut cursor = Cursor::new(input);
+    let mut map = AHashMap::new();
+    while !cursor.is_empty() {
+        let line = cursor;
+        let name = parse_name(&mut cursor)?;
+        cursor.expect(b": ")?;
+
+        let operation = if cursor.peek().map_or(false, |b| b.is_ascii_digit()) {
+            let start = cursor;
+            let number = cursor.uint()?;
+            let number = isize::try_from(number).map_err(|_| start.error("number is too large"))?;
+            Operation::Constant(number)
+        } else {
+            let name1 = parse_name(&mut cursor)?;
+            cursor.expect(b" ")?;
+            let operation: fn(u32, u32) -> Operation = match cursor.peek() {
+                Some(b'+') => Operation::Add,
+                Some(b'-') => Operation::Sub,
+                Some(b'*') => Operation::Mul,
+                Some(b'/') => Operation::Div,
+                _ => return Err(cursor.error("expected an operator (`+`, `-`, `*` or `/`)")),
             };
+            cursor.eat(&cursor.rest()[..1]);
+            cursor.expect(b" ")?;
+            let name2 = parse_name(&mut cursor)?;
+            operation(name1, name2)
+        };
+        if !cursor.is_empty() {
+            cursor.expect(b"\n")?;
+        }
+
+        if map.insert(name, operation).is_some() {
+            let message = format!("monkey `{}` is listed twice", name_to_string(name));
+            return Err(line.error(message));
+        }
+    }
+
+    check_references(&map)?;
+    Ok(map)
+}
+
+/// Parses the name of a monkey, which is 4 lowercase letters.
+fn parse_name(cursor: &mut Cursor) -> Result<u32, SolveError> {
+    let name = cursor.rest().get(..4);
+    let Some(name) = name.filter(|name| name.iter().all(u8::is_ascii_lowercase)) else {
+        return Err(cursor.error("expected a name of 4 lowercase letters"));
+    };
+    cursor.eat(name);
+    Ok(u32::from_ne_bytes(name.try_into().unwrap()))
+}
+
+fn name_to_string(name: u32) -> String {
+    String::from_utf8_lossy(&name.to_ne_bytes()).into_owned()
+}
+
+/// Checks that `root` and every monkey it waits for exist, and that they form a tree of at most
+/// [`MAX_DEPTH`] levels. Only one monkey may wait for each monkey, so no monkey ends up waiting for
+/// itself and the recursive solvers visit every monkey once.
+fn check_references(map: &AHashMap<u32, Operation>) -> Result<(), SolveError> {
+    let root = u32::from_ne_bytes([b'r', b'o', b'o', b't']);
+    if !map.contains_key(&root) {
+        return Err(SolveError::invalid("there is no monkey named `root`"));
+    }
+
+    let mut seen = AHashSet::from([root]);
+    let mut stack = vec![(root, 1)];
+    while let Some((name, depth)) = stack.pop() {
+        let (a, b) = match map[&name] {
+            Operation::Constant(_) => continue,
+            Operation::Add(a, b)
+            | Operation::Sub(a, b)
+            | Operation::Mul(a, b)
+            | Operation::Div(a, b) => (a, b),
+        };
+        if depth == MAX_DEPTH {
+            let message = format!("`root` waits for more than {MAX_DEPTH} monkeys in a row");
+            return Err(SolveError::invalid(message));
+        }
+
+        for other in [a, b] {
+            if !map.contains_key(&other) {
+                let message = format!("there is no monkey named `{}`", name_to_string(other));
+                return Err(SolveError::invalid(message));
+            }
+            if !seen.insert(other) {
+                let message = format!("monkey `{}` is waited for twice", name_to_string(other));
+                return Err(SolveError::invalid(message));
+            }
+            stack.push((other, depth + 1));
+        }
+    }
 
-            (name, operation)
-        })
-        .collect()
+    Ok(())
 }
 
 #[derive(Debug)]
-enum Operation {
+pub enum Operation {
     Constant(isize),
     Add(u32, u32),
     Sub(u32, u32),
@@ -242,3 +518,54 @@ fn test_gold_real() {
     let output = Day::calculate_gold(Day::INPUT_REAL);
     assert_eq!(3509819803065, output);
 }
+
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen("drzm * dbpl", "drzm % dbpl", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 10,
+            column: 12,
+            message: "expected an operator (`+`, `-`, `*` or `/`)".to_string()
+        }
+    );
+
+    // found by fuzzing, these used to index out of bounds, unwrap a missing monkey or recurse
+    // forever
+    for input in [
+        "",
+        "root",
+        "root: 5\nroot: 6",
+        "root: abcd + humn\nhumn: 5",
+        "root: humn + humn\nhumn: root / humn",
+        "root: 99999999999999999999",
+    ] {
+        assert!(Day::try_calculate_silver(input).is_err(), "{input:?}");
+    }
+
+    // these used to overflow, divide by zero, walk the same monkeys over and over or overflow the
+    // stack
+    let name = |i: usize| -> String {
+        (0..4)
+            .map(|d| (b'a' + (i / 26usize.pow(d) % 26) as u8) as char)
+            .collect()
+    };
+    let mut chain = format!("root: {} + humn\nhumn: 5", name(1));
+    for i in 1..=MAX_DEPTH {
+        let (next, number) = (name(i + 1), name(i + 10_000));
+        chain += &format!("\n{}: {next} + {number}\n{number}: 1", name(i));
+    }
+    chain += &format!("\n{}: 1", name(MAX_DEPTH + 1));
+    for input in [
+        "root: aaaa * aaaa\naaaa: 9999999999".to_string(),
+        "root: aaaa / bbbb\naaaa: 5\nbbbb: 0".to_string(),
+        "root: aaaa + bbbb\naaaa: 5\nbbbb: 5".to_string(),
+        "root: aaaa + bbbb\naaaa: humn * cccc\nhumn: 5\ncccc: 0\nbbbb: 5".to_string(),
+        "root: 5\nhumn: 5".to_string(),
+        "root: aaaa + aaaa\naaaa: 5".to_string(),
+        chain,
+    ] {
+        assert!(Day::try_calculate_gold(&input).is_err(), "{input:?}");
+    }
+}
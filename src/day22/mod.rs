@@ -1,4 +1,6 @@
-use std::{iter::FusedIterator, str::Lines};
+use std::iter::FusedIterator;
+
+use crate::utils::Cursor;
 
 use super::*;
 
@@ -8,39 +10,104 @@ impl SolutionSilver<usize> for Day {
     const DAY: u32 = 22;
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
+    // the map is aligned with spaces, only strip line breaks from the end
+    const INPUT_FORMAT: InputFormat = InputFormat::DEFAULT.trim_end(TrimEnd::Newlines);
+    const PARAMS_SAMPLE: &'static str = include_str!("params_sample.txt");
+
+    /// The maze, its instructions and the size of a cube side if it was given as a parameter.
+    type Parsed<'a> = (Maze, InstructionsIter<'a>, Option<usize>);
 
-    fn calculate_silver(input: &str) -> usize {
-        let mut lines = input.lines();
-        let maze = Maze::parse(&mut lines);
-        let instructions = InstructionsIter::from_str(lines.next().unwrap());
-        debug_assert_eq!(lines.next(), None);
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, SolveError> {
+        Self::parse_with(input, &Params::default())
+    }
 
+    fn parse_with<'a>(input: &'a str, params: &Params) -> Result<Self::Parsed<'a>, SolveError> {
+        let cube_size = params.get("cube_size")?;
+        if cube_size == Some(0) {
+            return Err(SolveError::invalid("the cube size should not be 0"));
+        }
+
+        let mut cursor = Cursor::new(input);
+        let maze = Maze::parse(input, &mut cursor)?;
+        let instructions = InstructionsIter::new(cursor.rest());
+        check_instructions(&mut cursor)?;
+
+        Ok((maze, instructions, cube_size))
+    }
+
+    fn solve_silver((maze, instructions, _): &Self::Parsed<'_>) -> usize {
         let mut location = maze.get_start_location();
-        for instruction in instructions {
+        for instruction in instructions.clone() {
             maze.execute_instruction(&mut location, instruction, &None);
         }
 
         location.get_solution()
     }
+
+    /// Generates a map whose cube sides are `size` tiles wide (at least 2), laid out like the
+    /// sample or the real input, followed by `2 * size` moves.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        // the position of every side in the layout, in units of whole sides
+        const LAYOUTS: [[(usize, usize); 6]; 2] = [
+            [(2, 0), (0, 1), (1, 1), (2, 1), (2, 2), (3, 2)],
+            [(1, 0), (2, 0), (1, 1), (0, 2), (1, 2), (0, 3)],
+        ];
+
+        let size = size.max(2);
+        let layout = rng.choose(&LAYOUTS);
+        let rows = layout.iter().map(|side| side.1).max().unwrap() + 1;
+
+        let mut lines = vec![];
+        for y in 0..rows * size {
+            // the sides in a row of the layout are next to each other
+            let columns = layout
+                .iter()
+                .filter(|side| side.1 == y / size)
+                .map(|side| side.0);
+            let (first, last) = (columns.clone().min().unwrap(), columns.max().unwrap());
+
+            let mut line = " ".repeat(first * size);
+            line.extend(
+                (first * size..(last + 1) * size).map(|_| match rng.chance(1, 10) {
+                    true => '#',
+                    false => '.',
+                }),
+            );
+            lines.push(line);
+        }
+
+        // the path starts on the first tile of the first side, which has to be open
+        let start = layout[0].0 * size;
+        lines[0].replace_range(start..=start, ".");
+
+        let mut path = rng.usize(1..=2 * size).to_string();
+        for _ in 1..2 * size {
+            path.push(*rng.choose(&['L', 'R']));
+            path += &rng.usize(1..=2 * size).to_string();
+        }
+        Some(format!("{}\n\n{path}", lines.join("\n")))
+    }
 }
 
 impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
-        let mut lines = input.lines();
-        let maze = Maze::parse(&mut lines);
-        let instructions = InstructionsIter::from_str(lines.next().unwrap());
-        debug_assert_eq!(lines.next(), None);
+    fn solve_gold(input: &Self::Parsed<'_>) -> usize {
+        Self::try_solve_gold(input).unwrap_or_else(|e| panic!("{e}"))
+    }
 
-        // note: could hardcode this, it's always 4 for sample and 50 for real input
+    /// Fails if the map is not the net of a cube with sides of the cube size.
+    fn try_solve_gold(
+        (maze, instructions, cube_size): &Self::Parsed<'_>,
+    ) -> Result<usize, SolveError> {
+        // the cube size can be given as a parameter, otherwise derive it from the amount of tiles
         // TODO: move impl to maze
-        let cube_size = {
+        let cube_size = cube_size.unwrap_or_else(|| {
             let total_tiles = maze.0.iter().map(|x| x.1.len()).sum::<usize>();
             let tile_per_side = total_tiles / 6;
             (tile_per_side as f64).sqrt() as usize
-        };
+        });
 
         // Create a map between a position in the 2D grid, and a cube side id
-        let cube_side_id_map = maze.create_grid_map(cube_size);
+        let cube_side_id_map = maze.create_grid_map(cube_size)?;
 
         // Map the grid chunks onto a cube
         let cube = {
@@ -96,7 +163,7 @@ impl SolutionGold<usize, usize> for Day {
                             })
                             .next()
                     })
-                    .expect("there should be an unused side");
+                    .ok_or_else(|| SolveError::invalid("the sides of the map are not connected"))?;
 
                 // Calculate the direction we need to place the new face on
                 let direction_from_used_neighbour = Direction2D::from_unit_vector((
@@ -122,7 +189,11 @@ impl SolutionGold<usize, usize> for Day {
                     direction_from_used_neighbour,
                 );
 
-                *partial_cube.get_side_mut(neighbour_face) = Some(CubeSideContent {
+                let neighbour_content = partial_cube.get_side_mut(neighbour_face);
+                if neighbour_content.is_some() {
+                    return Err(SolveError::invalid("the map does not fold into a cube"));
+                }
+                *neighbour_content = Some(CubeSideContent {
                     id: *unused_side.0,
                     facing_direction: neighbour_facing,
                 });
@@ -147,25 +218,33 @@ impl SolutionGold<usize, usize> for Day {
         });
 
         let mut location = maze.get_start_location();
-        for instruction in instructions {
+        for instruction in instructions.clone() {
             maze.execute_instruction(&mut location, instruction, &wrap_info);
         }
 
-        location.get_solution()
+        Ok(location.get_solution())
     }
 }
 
 #[derive(Debug)]
-struct Maze(Vec<MazeLine>);
+pub struct Maze(Vec<MazeLine>);
 
 impl Maze {
-    pub fn parse(lines: &mut Lines) -> Self {
-        Self(
-            lines
-                .take_while(|l| !l.is_empty())
-                .map(MazeLine::parse)
-                .collect(),
-        )
+    /// Parses the lines of the map up to the empty line after it.
+    pub fn parse(input: &str, cursor: &mut Cursor) -> Result<Self, SolveError> {
+        let mut lines = vec![];
+        loop {
+            let line = cursor.until(b'\n')?;
+            if line.is_empty() {
+                break;
+            }
+            lines.push(MazeLine::parse(input, line)?);
+        }
+
+        if lines.is_empty() {
+            return Err(SolveError::at(input, input, "expected a map"));
+        }
+        Ok(Self(lines))
     }
 
     pub fn get_start_location(&self) -> Location {
@@ -185,16 +264,23 @@ impl Maze {
             Instruction::RotateLeft => location.rotate_left(),
             Instruction::RotateRight => location.rotate_right(),
             Instruction::Move(count) => {
-                // TODO: use fold instead
-                for _ in 0..count {
+                let start = (location.position, location.heading);
+                let mut remaining = count;
+                while remaining > 0 {
                     if let Some((wrapped_position, new_heading)) =
                         self.try_move(location, wrap_info)
                     {
                         location.position = wrapped_position;
                         location.heading = new_heading;
+                        remaining -= 1;
                     } else {
                         break;
                     }
+
+                    // back where we started, so skip the loops around the maze
+                    if (location.position, location.heading) == start {
+                        remaining %= count - remaining;
+                    }
                 }
             }
         }
@@ -384,7 +470,17 @@ impl Maze {
         new_position
     }
 
-    fn create_grid_map(&self, cube_size: usize) -> Vec<(CubeSideId, (isize, isize))> {
+    /// Finds the 6 sides of the cube in the map. Fails if the map is not made of exactly 6 whole
+    /// sides.
+    fn create_grid_map(
+        &self,
+        cube_size: usize,
+    ) -> Result<Vec<(CubeSideId, (isize, isize))>, SolveError> {
+        let not_a_net = || SolveError::invalid("the map is not the net of a cube");
+        if cube_size == 0 || self.0.len() % cube_size != 0 {
+            return Err(not_a_net());
+        }
+
         let mut id_counter = 0;
         let mut map = vec![];
 
@@ -392,6 +488,14 @@ impl Maze {
             let y = line_idx / cube_size;
 
             let line = &self.0[line_idx];
+            // every line of a row of sides should cover the same sides
+            let same_sides = self.0[line_idx..line_idx + cube_size]
+                .iter()
+                .all(|other| other.0 == line.0 && other.1.len() == line.1.len());
+            if !same_sides || line.0 as usize % cube_size != 0 || line.1.len() % cube_size != 0 {
+                return Err(not_a_net());
+            }
+
             let x_offset = (line.0 as usize) / cube_size;
             for x_index in 0..(line.1.len() / cube_size) {
                 let x = x_offset + x_index;
@@ -400,9 +504,10 @@ impl Maze {
             }
         }
 
-        debug_assert_eq!(map.len(), 6);
-
-        map
+        if map.len() != 6 {
+            return Err(not_a_net());
+        }
+        Ok(map)
     }
 }
 
@@ -410,14 +515,24 @@ impl Maze {
 struct MazeLine(isize, Vec<bool>);
 
 impl MazeLine {
-    pub fn parse(line: &str) -> Self {
-        let bytes = line.as_bytes();
+    /// Parses a line of the map, which is indented with spaces and has at least one tile.
+    pub fn parse(input: &str, bytes: &[u8]) -> Result<Self, SolveError> {
         let prefix = bytes.iter().take_while(|b| **b == b' ').count();
+        let tiles = &bytes[prefix..];
+        let invalid = tiles.iter().position(|b| !matches!(b, b'.' | b'#'));
+        if tiles.is_empty() || invalid.is_some() {
+            let offset = invalid.unwrap_or_default();
+            return Err(SolveError::at(
+                input,
+                &tiles[offset..],
+                "expected a tile (`.` or `#`)",
+            ));
+        }
 
-        Self(
+        Ok(Self(
             prefix as isize,
-            bytes[prefix..].iter().map(|b| *b == b'#').collect(),
-        )
+            tiles.iter().map(|b| *b == b'#').collect(),
+        ))
     }
 
     pub fn wrap_horizontal(&self, x: isize) -> isize {
@@ -425,14 +540,28 @@ impl MazeLine {
     }
 }
 
-struct InstructionsIter<'a>(&'a [u8], usize);
+#[derive(Clone)]
+pub struct InstructionsIter<'a>(&'a [u8], usize);
 
 impl<'a> InstructionsIter<'a> {
-    pub fn from_str(string: &'a str) -> Self {
-        Self(string.as_bytes(), 0)
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self(bytes, 0)
     }
 }
 
+/// Reads the instructions up to the end of the input, to check them before [`InstructionsIter`]
+/// reads them without checking.
+fn check_instructions(cursor: &mut Cursor) -> Result<(), SolveError> {
+    while !cursor.is_empty() {
+        if cursor.peek().map_or(false, |b| b.is_ascii_digit()) {
+            cursor.uint()?;
+        } else if !cursor.eat(b"L") && !cursor.eat(b"R") {
+            return Err(cursor.error("expected a number of tiles, `L` or `R`"));
+        }
+    }
+    Ok(())
+}
+
 impl<'a> Iterator for InstructionsIter<'a> {
     type Item = Instruction;
 
@@ -461,14 +590,14 @@ impl<'a> Iterator for InstructionsIter<'a> {
 impl<'a> FusedIterator for InstructionsIter<'a> {}
 
 #[derive(Debug, Clone, Copy)]
-enum Instruction {
+pub enum Instruction {
     RotateLeft,
     RotateRight,
     Move(usize),
 }
 
 #[derive(Debug)]
-struct Location {
+pub struct Location {
     position: (isize, isize),
     /// 0 means east and counting up goes to south, west and north
     heading: usize,
@@ -500,7 +629,7 @@ impl Location {
     }
 }
 
-struct CubeWrappingInfo {
+pub struct CubeWrappingInfo {
     cube_size: usize,
     cube: Cube<CubeSideContent>,
     cube_side_id_map: Vec<(CubeSideId, (isize, isize))>,
@@ -805,6 +934,60 @@ fn test_gold_real() {
     assert_eq!(55364, output);
 }
 
+#[test]
+fn test_gold_params() {
+    let params = Params::parse(Day::PARAMS_SAMPLE).unwrap();
+    let parsed = Day::parse_with(Day::INPUT_SAMPLE, &params).unwrap();
+    assert_eq!(Some(4), parsed.2);
+    assert_eq!(5031, Day::solve_gold(&parsed));
+
+    let params = Params::parse("cube_size = 0").unwrap();
+    assert!(Day::parse_with(Day::INPUT_SAMPLE, &params).is_err());
+}
+
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen("10R5L5", "10R5X5", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 14,
+            column: 5,
+            message: "expected a number of tiles, `L` or `R`".to_string()
+        }
+    );
+
+    // found by fuzzing, these used to unwrap a missing line of instructions, index out of bounds
+    // or overflow
+    for input in [
+        "",
+        "..",
+        "..\n\n1\n2",
+        "  \n\n1",
+        "..\n.x\n\n1",
+        "..\n\n99999999999999999999",
+    ] {
+        assert!(Day::try_calculate_silver(input).is_err(), "{input:?}");
+    }
+
+    // found by fuzzing, these used to walk every tile of a long move or panic on a map that is
+    // not the net of a cube
+    assert_eq!(
+        Day::try_calculate_silver("..\n\n1000000000000000000"),
+        Ok(1004)
+    );
+    let sample_map = Day::INPUT_SAMPLE.split("\n\n").next().unwrap();
+    for input in [
+        "#.\n\n10R5L5".to_string(),
+        sample_map.replacen("        ...#\n", "", 1) + "\n\n10R5L5",
+        sample_map.replacen("        .#......\n", "        .#..\n", 1) + "\n\n10R5L5",
+        "......\n\n1".to_string(),
+        ".\n.\n.\n.\n  .\n  .\n\n1".to_string(),
+    ] {
+        assert!(Day::try_calculate_gold(&input).is_err(), "{input:?}");
+    }
+}
+
 #[test]
 fn test_cube_neighbour_calc_from_start_pos() {
     assert_eq!(
@@ -9,10 +9,14 @@ impl SolutionSilver<usize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> usize {
-        let input = parse_input(input);
+    type Parsed<'a> = AHashSet<(i32, i32)>;
 
-        let mut positions = input;
+    fn parse(input: &str) -> Result<AHashSet<(i32, i32)>, SolveError> {
+        parse_input(input)
+    }
+
+    fn solve_silver(input: &AHashSet<(i32, i32)>) -> usize {
+        let mut positions = input.clone();
         let mut movements = AHashMap::default();
         let mut directions = Direction::DEFAULT_DIRECTIONS;
         for _ in 0..10 {
@@ -96,13 +100,32 @@ impl SolutionSilver<usize> for Day {
 
         x_diff * y_diff - positions.len()
     }
+
+    /// Generates a scan of the grove that is `size` tiles wide and high, where about half of the
+    /// tiles hold an elf.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let size = size.max(1);
+        let mut rows: Vec<Vec<u8>> = (0..size)
+            .map(|_| {
+                (0..size)
+                    .map(|_| if rng.chance(1, 2) { b'#' } else { b'.' })
+                    .collect()
+            })
+            .collect();
+        // there is at least one elf
+        rows[rng.usize(0..=size - 1)][rng.usize(0..=size - 1)] = b'#';
+
+        let rows: Vec<String> = rows
+            .into_iter()
+            .map(|row| String::from_utf8(row).unwrap())
+            .collect();
+        Some(rows.join("\n"))
+    }
 }
 
 impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
-        let input = parse_input(input);
-
-        let mut positions = input;
+    fn solve_gold(input: &AHashSet<(i32, i32)>) -> usize {
+        let mut positions = input.clone();
         let mut movements = AHashMap::default();
         let mut directions = Direction::DEFAULT_DIRECTIONS;
         let mut round = 0;
@@ -199,23 +222,30 @@ impl Direction {
     ];
 }
 
-fn parse_input(input: &str) -> AHashSet<(i32, i32)> {
-    input
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.as_bytes()
-                .iter()
-                .enumerate()
-                .filter_map(move |(x, c)| {
-                    if *c == b'#' {
-                        Some((x as i32, y as i32))
-                    } else {
-                        None
-                    }
-                })
-        })
-        .collect()
+fn parse_input(input: &str) -> Result<AHashSet<(i32, i32)>, SolveError> {
+    let mut positions = AHashSet::default();
+    for (y, line) in input.lines().enumerate() {
+        for (x, c) in line.bytes().enumerate() {
+            match c {
+                b'#' => {
+                    positions.insert((x as i32, y as i32));
+                }
+                b'.' => {}
+                _ => {
+                    return Err(SolveError::at(
+                        input,
+                        &line[x..],
+                        "expected a tile (`.` or `#`)",
+                    ))
+                }
+            }
+        }
+    }
+
+    if positions.is_empty() {
+        return Err(SolveError::invalid("the grove has no elves"));
+    }
+    Ok(positions)
 }
 
 #[test]
@@ -241,3 +271,21 @@ fn test_gold_real() {
     let output = Day::calculate_gold(Day::INPUT_REAL);
     assert_eq!(1019, output);
 }
+
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen('#', "x", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 3,
+            column: 8,
+            message: "expected a tile (`.` or `#`)".to_string()
+        }
+    );
+
+    // found by fuzzing, these used to overflow while measuring an empty grove
+    for input in ["", "..", "............\n...........", "....1000000...."] {
+        assert!(Day::try_calculate_gold(input).is_err(), "{input:?}");
+    }
+}
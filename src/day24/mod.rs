@@ -1,6 +1,7 @@
-use std::collections::BinaryHeap;
+use ahash::AHashMap;
+use tinyvec::ArrayVec;
 
-use ahash::{AHashMap, AHashSet};
+use crate::search;
 
 use super::*;
 
@@ -11,105 +12,209 @@ impl SolutionSilver<usize> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> usize {
-        let input = Input::parse(input);
+    type Parsed<'a> = Input;
 
-        do_dfs::<false>(&input, 0)
+    fn parse(input: &str) -> Result<Input, SolveError> {
+        Input::parse(input)
+    }
+
+    fn solve_silver(input: &Input) -> usize {
+        Self::try_solve_silver(input).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Fails if the blizzards never leave a way through the valley.
+    fn try_solve_silver(input: &Input) -> Result<usize, SolveError> {
+        shortest_trip::<false>(input, 0)
+    }
+
+    /// Generates a valley that is `size` tiles wide (at least 2) and a fifth as high, where about
+    /// a quarter of the tiles start with a blizzard. Like in the puzzle input, no blizzards move
+    /// up or down in the columns of the entrance and the exit. Valleys where a trip of the gold
+    /// part is impossible or takes too long are thrown away.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let width = size.max(2);
+        let height = width / 5 + 1;
+
+        loop {
+            let mut blizzards = vec![];
+            for y in 0..height {
+                for x in 0..width {
+                    let directions: &[u8] = match x == 0 || x == width - 1 {
+                        true => b"<>",
+                        false => b"<>^v",
+                    };
+                    if rng.chance(1, 4) {
+                        blizzards.push(((x, y), *rng.choose(directions)));
+                    }
+                }
+            }
+
+            if !can_make_trips(width, height, &blizzards, 4 * (width + height) + 20) {
+                continue;
+            }
+
+            let mut rows = vec![vec![b'.'; width]; height];
+            for ((x, y), direction) in blizzards {
+                rows[y][x] = direction;
+            }
+
+            let mut lines = vec![format!("#.{}", "#".repeat(width))];
+            lines.extend(
+                rows.into_iter()
+                    .map(|row| format!("#{}#", String::from_utf8(row).unwrap())),
+            );
+            lines.push(format!("{}.#", "#".repeat(width)));
+            return Some(lines.join("\n"));
+        }
     }
 }
 
 impl SolutionGold<usize, usize> for Day {
-    fn calculate_gold(input: &str) -> usize {
-        let input = Input::parse(input);
+    fn solve_gold(input: &Input) -> usize {
+        Self::try_solve_gold(input).unwrap_or_else(|e| panic!("{e}"))
+    }
 
+    /// Fails if the blizzards never leave a way through the valley.
+    fn try_solve_gold(input: &Input) -> Result<usize, SolveError> {
         let time_0 = 0;
-        let time_1 = do_dfs::<false>(&input, time_0);
-        let time_2 = do_dfs::<true>(&input, time_1);
-        do_dfs::<false>(&input, time_2)
+        let time_1 = shortest_trip::<false>(input, time_0)?;
+        let time_2 = shortest_trip::<true>(input, time_1)?;
+        shortest_trip::<false>(input, time_2)
     }
 }
 
-fn do_dfs<const REV: bool>(input: &Input, start_time: usize) -> usize {
+/// Finds the earliest minute at which the exit can be reached when leaving the entrance at
+/// `start_time`. With `REV`, the trip goes from the exit back to the entrance.
+fn shortest_trip<const REV: bool>(input: &Input, start_time: usize) -> Result<usize, SolveError> {
     // we're essentially pathfinding in a 3D field, where time is the Z direction. We cannot go
     // back in the Z direction, but an exit is available at every value of Z.
     // this can be optimized this more by building up these Z levels.
+    let start = input.get_start_pos::<REV>();
+    let end = input.get_end_pos::<REV>();
 
-    let mut visited = AHashSet::default();
-    let mut queue = BinaryHeap::new();
-
-    queue.push(ProgressPosition::<REV>(
-        input.get_start_pos::<REV>(),
-        start_time,
-    ));
+    // the blizzards are back where they started after this many minutes, so the field repeats
+    // along the Z direction and the search runs out of nodes if the end cannot be reached
+    let period = input.width / gcd(input.width, input.height) * input.height;
 
-    let mut best_result = usize::MAX;
-
-    'main_loop: while let Some(current) = queue.pop() {
-        if visited.contains(&current) {
-            continue;
-        }
-        visited.insert(current.clone());
-
-        let ProgressPosition(pos, minute) = current;
-
-        debug_assert!((minute + 1) < best_result);
-        debug_assert!(input.pos_free_at_minute(pos, minute));
+    let neighbours = |&(pos, minute): &((isize, isize), usize)| {
+        let mut next = ArrayVec::<[(isize, isize); 5]>::default();
 
         // try waiting, if we can
         if input.pos_free_at_minute(pos, minute + 1) {
-            let next_pos = ProgressPosition(pos, minute + 1);
-            if next_pos.best_end_time(input) < best_result {
-                queue.push(next_pos);
-            }
+            next.push(pos);
         }
 
-        if pos == input.get_start_pos::<REV>() {
+        if pos == start {
             // we need special handling for the start position. We can only move down or wait then.
-            if input.pos_free_at_minute(input.get_first_move::<REV>(), minute + 1) {
-                let next_pos = ProgressPosition(input.get_first_move::<REV>(), minute + 1);
-                if next_pos.best_end_time(input) < best_result {
-                    queue.push(next_pos);
-                }
+            let first_move = input.get_first_move::<REV>();
+            if input.pos_free_at_minute(first_move, minute + 1) {
+                next.push(first_move);
             }
         } else {
-            // non-start position, look at all 4 directions
-            // because we use a priority queue, the order should not be relevant
-            let offsets = [(-1, 0), (0, -1), (1, 0), (0, 1)];
-
-            for offset in offsets {
+            for offset in [(-1, 0), (0, -1), (1, 0), (0, 1)] {
                 let new_pos = (pos.0 + offset.0, pos.1 + offset.1);
-                if new_pos == input.get_end_pos::<REV>() {
-                    // we got there!
-                    best_result = best_result.min(minute + 1);
-                    // dbg!(best_result);
-
-                    // println!("Len before cleanup: {}", queue.len());
-                    // clear some branches with no potential
-                    queue.retain(|x| x.best_end_time(input) < best_result);
-                    // println!("Len after cleanup: {}", queue.len());
 
-                    continue 'main_loop;
+                // the end position is always free, other positions need to be in bounds
+                if new_pos == end
+                    || (0..input.width).contains(&(new_pos.0 as usize))
+                        && (0..input.height).contains(&(new_pos.1 as usize))
+                        && input.pos_free_at_minute(new_pos, minute + 1)
+                {
+                    next.push(new_pos);
                 }
+            }
+        }
 
-                // if we're not moving to the end position, check that the new position is in bounds
-                if (0..input.width).contains(&(new_pos.0 as usize))
-                    && (0..input.height).contains(&(new_pos.1 as usize))
-                    && input.pos_free_at_minute(new_pos, minute + 1)
-                {
-                    let next_pos = ProgressPosition(new_pos, minute + 1);
-                    if next_pos.best_end_time(input) < best_result {
-                        queue.push(next_pos);
+        next.into_iter()
+            .map(move |pos| ((pos, (minute + 1) % period), 1))
+    };
+
+    // every minute moves at most one step closer to the end
+    let heuristic =
+        |&(pos, _): &((isize, isize), usize)| end.0.abs_diff(pos.0) + end.1.abs_diff(pos.1);
+
+    let found = search::astar(
+        [(start, start_time % period)],
+        neighbours,
+        heuristic,
+        |&(pos, _)| pos == end,
+    )
+    .ok_or_else(|| SolveError::invalid("the blizzards never leave a way through the valley"))?;
+    Ok(start_time + found.cost)
+}
+
+fn gcd(a: usize, b: usize) -> usize {
+    match b {
+        0 => a,
+        _ => gcd(b, a % b),
+    }
+}
+
+/// Whether the three trips of the gold part can be made one after another through a valley with
+/// the given `blizzards`, each within `limit` minutes. Unlike [`shortest_trip`], this tracks every
+/// position that can be reached at each minute, so it gives up as soon as a trip takes too long.
+fn can_make_trips(
+    width: usize,
+    height: usize,
+    blizzards: &[((usize, usize), u8)],
+    limit: usize,
+) -> bool {
+    let (w, h) = (width as isize, height as isize);
+
+    // the valley with an extra row above and below it for the entrance and the exit
+    let index = |x: isize, y: isize| ((y + 1) * w + x) as usize;
+    let (entrance, exit) = (index(0, -1), index(w - 1, h));
+    let mut open = vec![false; width * (height + 2)];
+    let mut reachable = open.clone();
+    let mut next = open.clone();
+
+    let mut minute = 0;
+    for (start, end) in [(entrance, exit), (exit, entrance), (entrance, exit)] {
+        let deadline = minute + limit;
+        reachable.fill(false);
+        reachable[start] = true;
+        while !reachable[end] {
+            if minute == deadline {
+                return false;
+            }
+            minute += 1;
+
+            open.fill(false);
+            open[width..width * (height + 1)].fill(true);
+            (open[entrance], open[exit]) = (true, true);
+            for &((x, y), direction) in blizzards {
+                let (x, y, m) = (x as isize, y as isize, minute as isize);
+                let (x, y) = match direction {
+                    b'>' => ((x + m).rem_euclid(w), y),
+                    b'<' => ((x - m).rem_euclid(w), y),
+                    b'v' => (x, (y + m).rem_euclid(h)),
+                    _ => (x, (y - m).rem_euclid(h)),
+                };
+                open[index(x, y)] = false;
+            }
+
+            // nothing can stand in the way outside of the valley, so waiting at the start is always
+            // possible
+            next.fill(false);
+            for (i, _) in reachable.iter().enumerate().filter(|(_, &r)| r) {
+                let left = i.checked_sub(1).filter(|_| i % width != 0);
+                let right = (i % width != width - 1).then_some(i + 1);
+                let neighbours = [Some(i), left, right, i.checked_sub(width), Some(i + width)];
+                for j in neighbours.into_iter().flatten() {
+                    if open.get(j) == Some(&true) {
+                        next[j] = true;
                     }
                 }
             }
+            std::mem::swap(&mut reachable, &mut next);
         }
     }
 
-    best_result
+    true
 }
 
 #[derive(Debug, Default)]
-struct Input {
+pub struct Input {
     /// The width, excluding walls
     width: usize,
     /// The height, excluding walls
@@ -130,35 +235,74 @@ struct Input {
 }
 
 impl Input {
-    fn parse(input: &str) -> Input {
+    fn parse(input: &str) -> Result<Input, SolveError> {
         let mut ret = Input::default();
-        // the outer characters will always be either a wall (`#`) or the start/end position. Because
-        // the start/end position are always the same, we don't need to keep track of rows/columns.
-        input.lines().skip(1).enumerate().for_each(|(y, line)| {
-            let bytes = line.as_bytes();
-            if bytes[1] == b'#' {
-                // this is the last line
-                ret.height = y;
-                return;
+        let lines: Vec<_> = input.lines().collect();
+
+        if lines.len() < 3 {
+            let end = &input[input.len()..];
+            return Err(SolveError::at(input, end, "expected at least 3 lines"));
+        }
+        let line_len = lines[0].len();
+        if line_len < 3 {
+            return Err(SolveError::at(
+                input,
+                lines[0],
+                "expected at least 3 columns",
+            ));
+        }
+
+        ret.width = line_len - 2;
+        ret.height = lines.len() - 2;
+
+        // the outer characters will always be either a wall (`#`) or the start/end position. The
+        // start position is always in the top left and the end position always in the bottom
+        // right, so we don't need to keep track of them.
+        let last_y = lines.len() - 1;
+        for (y, line) in lines.iter().enumerate() {
+            if line.len() != line_len {
+                let message = format!("expected a line of {line_len} characters");
+                return Err(SolveError::at(input, line, message));
             }
-            bytes.iter().skip(1).enumerate().for_each(|(x, char)| {
-                let y = y;
+
+            for (x, char) in line.bytes().enumerate() {
+                let is_wall = x == 0
+                    || x == line_len - 1
+                    || (y == 0 && x != 1)
+                    || (y == last_y && x != line_len - 2);
+                let is_entrance = !is_wall && (y == 0 || y == last_y);
+
+                // coordinates without the walls
+                let (bx, by) = (x.wrapping_sub(1), y.wrapping_sub(1));
                 match char {
-                    b'<' => ret.horizontal_left.entry(y).or_insert(vec![]).push(x),
-                    b'>' => ret.horizontal_right.entry(y).or_insert(vec![]).push(x),
-                    b'^' => ret.vertical_up.entry(x).or_insert(vec![]).push(y),
-                    b'v' => ret.vertical_down.entry(x).or_insert(vec![]).push(y),
-                    b'.' => {} // open space, ignore it
-                    b'#' => {
-                        // end of a line, update the width
-                        ret.width = x;
+                    b'#' if is_wall => {}
+                    b'.' if !is_wall => {} // open space, ignore it
+                    b'<' if !is_wall && !is_entrance => {
+                        ret.horizontal_left.entry(by).or_insert(vec![]).push(bx);
+                    }
+                    b'>' if !is_wall && !is_entrance => {
+                        ret.horizontal_right.entry(by).or_insert(vec![]).push(bx);
+                    }
+                    b'^' if !is_wall && !is_entrance => {
+                        ret.vertical_up.entry(bx).or_insert(vec![]).push(by);
+                    }
+                    b'v' if !is_wall && !is_entrance => {
+                        ret.vertical_down.entry(bx).or_insert(vec![]).push(by);
+                    }
+                    _ => {
+                        let expected = match (is_wall, is_entrance) {
+                            (true, _) => "a wall (`#`)",
+                            (false, true) => "an open space (`.`)",
+                            (false, false) => "an open space or a blizzard",
+                        };
+                        let message = format!("unexpected character, expected {expected}");
+                        return Err(SolveError::at(input, &line[x..], message));
                     }
-                    _ => panic!("unexpected char: {}", *char as char),
                 }
-            });
-        });
+            }
+        }
 
-        ret
+        Ok(ret)
     }
 
     const fn get_start_pos<const REV: bool>(&self) -> (isize, isize) {
@@ -215,41 +359,6 @@ impl Input {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-struct ProgressPosition<const REV: bool>((isize, isize), usize);
-
-impl<const REV: bool> ProgressPosition<REV> {
-    fn score(&self) -> isize {
-        -(self.1 as isize)
-            + if REV {
-                -(self.0 .0 + self.0 .1)
-            } else {
-                self.0 .0 + self.0 .1
-            }
-    }
-
-    /// Calculates the best time this can reach the end with
-    fn best_end_time(&self, input: &Input) -> usize {
-        let current_time = self.1;
-        let (end_x, end_y) = input.get_end_pos::<REV>();
-        let manhattan_distance = end_x.abs_diff(self.0 .0) + end_y.abs_diff(self.0 .1);
-
-        current_time + manhattan_distance
-    }
-}
-
-impl<const REV: bool> PartialOrd for ProgressPosition<REV> {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl<const REV: bool> Ord for ProgressPosition<REV> {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.score().cmp(&other.score())
-    }
-}
-
 #[test]
 fn test_silver_sample() {
     let output = Day::calculate_silver(Day::INPUT_SAMPLE);
@@ -273,3 +382,34 @@ fn test_gold_real() {
     let output = Day::calculate_gold(Day::INPUT_REAL);
     assert_eq!(717, output);
 }
+
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen(">>.<^<", ">>.x^<", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 2,
+            column: 5,
+            message: "unexpected character, expected an open space or a blizzard".to_string()
+        }
+    );
+
+    let input = Day::INPUT_SAMPLE.replacen("#.#", "#..", 1);
+    assert!(matches!(
+        Day::try_calculate_gold(&input),
+        Err(SolveError::Parse {
+            line: 1,
+            column: 3,
+            ..
+        })
+    ));
+
+    // found by fuzzing, these used to search forever because the blizzards block the only way
+    for input in ["#.#\n#>#\n#.#", "#.##\n#><#\n##.#"] {
+        assert!(
+            matches!(Day::try_calculate_gold(input), Err(SolveError::Invalid(_))),
+            "{input:?}"
+        );
+    }
+}
@@ -9,25 +9,54 @@ impl SolutionSilver<String> for Day {
     const INPUT_SAMPLE: &'static str = include_str!("input_sample.txt");
     const INPUT_REAL: &'static str = include_str!("input_real.txt");
 
-    fn calculate_silver(input: &str) -> String {
-        encode_snafu(input.lines().map(decode_snafu).sum())
+    type Parsed<'a> = Vec<isize>;
+
+    fn parse(input: &str) -> Result<Vec<isize>, SolveError> {
+        let mut sum = 0isize;
+        let mut numbers = vec![];
+        for line in input.lines() {
+            if line.is_empty() || line.len() > MAX_DIGITS {
+                let message = format!("expected a number of 1 to {MAX_DIGITS} digits");
+                return Err(SolveError::at(input, line, message));
+            }
+            if let Some(offset) = line.bytes().position(|b| !b"=-012".contains(&b)) {
+                let message = "expected a SNAFU digit (`=`, `-`, `0`, `1` or `2`)";
+                return Err(SolveError::at(input, &line[offset..], message));
+            }
+
+            let number = decode_snafu(line);
+            sum = sum
+                .checked_add(number)
+                .ok_or_else(|| SolveError::at(input, line, "the sum is too large"))?;
+            numbers.push(number);
+        }
+
+        Ok(numbers)
+    }
+
+    fn solve_silver(numbers: &Vec<isize>) -> String {
+        encode_snafu(numbers.iter().sum())
     }
-}
 
-// NOTE: this solution stub is here to make the benchmarking setup work. it didn't expect a fully
-// solved day to not have a gold part.
-impl SolutionGold<String, usize> for Day {
-    fn calculate_gold(_input: &str) -> usize {
-        0
+    /// Generates `size` random positive numbers.
+    fn generate(rng: &mut generate::Rng, size: usize) -> Option<String> {
+        let max = 5usize.pow(rng.usize(1..=14) as u32);
+        let numbers: Vec<String> = (0..size.max(1))
+            .map(|_| encode_snafu(rng.usize(1..=max) as isize))
+            .collect();
+        Some(numbers.join("\n"))
     }
 }
 
+/// The most digits a number can have without overflowing.
+const MAX_DIGITS: usize = 27;
+
 fn encode_snafu(input: isize) -> String {
     let mut buffer = TinyVec::<[char; 8]>::new();
 
     let mut with_offset = input;
     while with_offset != 0 {
-        let char_val = ((with_offset + 2) % 5) - 2;
+        let char_val = (with_offset + 2).rem_euclid(5) - 2;
         let char = encode_snafu_digit(char_val) as char;
 
         buffer.push(char);
@@ -85,6 +114,43 @@ fn test_silver_real() {
     assert_eq!("20-=0=02=-21=00-02=2", output);
 }
 
+#[test]
+fn test_malformed_input() {
+    let input = Day::INPUT_SAMPLE.replacen("2=0=", "2=0x", 1);
+    assert_eq!(
+        Day::try_calculate_silver(&input).unwrap_err(),
+        SolveError::Parse {
+            line: 3,
+            column: 4,
+            message: "expected a SNAFU digit (`=`, `-`, `0`, `1` or `2`)".to_string()
+        }
+    );
+
+    // found by fuzzing, these used to panic on the unknown digit or overflow
+    for input in ["1=-y-2\n12", "1=-0-2\n\n12", &"2".repeat(MAX_DIGITS + 1)] {
+        assert!(
+            matches!(
+                Day::try_calculate_silver(input),
+                Err(SolveError::Parse { .. })
+            ),
+            "{input:?}"
+        );
+    }
+
+    let max = "2".repeat(MAX_DIGITS);
+    assert_eq!(decode_snafu(&max).to_string().len(), 19);
+    assert!(Day::try_calculate_silver(&format!("{max}\n{max}\n{max}\n{max}")).is_err());
+}
+
+#[test]
+fn test_negative_sum() {
+    // found by fuzzing, negative numbers used to produce out of range digits
+    assert_eq!("-2", Day::calculate_silver("-\n="));
+    assert_eq!("=", encode_snafu(-2));
+    assert_eq!("-2", encode_snafu(-3));
+    assert_eq!("=11", encode_snafu(-44));
+}
+
 #[test]
 fn test_snafu_encode() {
     assert_eq!("0", encode_snafu(0));
@@ -154,3 +220,37 @@ fn test_snafu_decode() {
     assert_eq!(3, decode_snafu("1="));
     assert_eq!(37, decode_snafu("122"));
 }
+
+/// Encodes a positive number by writing it in base 5 and carrying the digits 3 and 4 over as
+/// `=` and `-`.
+#[cfg(test)]
+fn encode_naive(mut number: isize) -> String {
+    let mut digits = vec![];
+    while number > 0 {
+        let digit = number % 5;
+        number /= 5;
+        match digit {
+            3 => digits.push('='),
+            4 => digits.push('-'),
+            _ => digits.push(char::from_digit(digit as u32, 10).unwrap()),
+        }
+        if digit > 2 {
+            number += 1;
+        }
+    }
+    digits.iter().rev().collect()
+}
+
+#[test]
+fn test_differential() {
+    crate::property::check(2000, crate::property::input::<Day, _>, |input| {
+        for number in input.lines() {
+            let decoded = decode_snafu(number);
+            assert_eq!(number, encode_naive(decoded));
+            assert_eq!(number, encode_snafu(decoded));
+        }
+
+        let sum = input.lines().map(decode_snafu).sum();
+        assert_eq!(encode_naive(sum), Day::calculate_silver(input));
+    });
+}
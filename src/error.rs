@@ -0,0 +1,150 @@
+use std::{fmt::Display, panic::AssertUnwindSafe};
+
+use crate::cancel::Cancelled;
+
+/// An error that occurred while solving a puzzle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    /// The input could not be parsed. Lines and columns are 1-based, columns count bytes.
+    Parse {
+        line: usize,
+        column: usize,
+        message: String,
+    },
+    /// The input could be parsed, but does not describe a valid puzzle.
+    Invalid(String),
+    /// The solution panicked.
+    Panic(String),
+    /// The solution was stopped at a [checkpoint](crate::cancel::checkpoint), because it ran out of
+    /// time.
+    Cancelled,
+}
+
+impl SolveError {
+    /// Creates a parse error at the start of `rest`, which must be a subslice of `input`. If `rest`
+    /// is empty, the error points at the end of the input.
+    pub fn at(input: impl AsRef<[u8]>, rest: impl AsRef<[u8]>, message: impl Into<String>) -> Self {
+        let (input, rest) = (input.as_ref(), rest.as_ref());
+        let offset = (rest.as_ptr() as usize).wrapping_sub(input.as_ptr() as usize);
+        debug_assert!(
+            offset <= input.len(),
+            "`rest` should be a subslice of `input`"
+        );
+
+        Self::at_offset(input, offset.min(input.len()), message)
+    }
+
+    /// Creates a parse error at the given byte offset in `input`.
+    pub fn at_offset(input: impl AsRef<[u8]>, offset: usize, message: impl Into<String>) -> Self {
+        let before = &input.as_ref()[..offset];
+        let line_start = before
+            .iter()
+            .rposition(|&b| b == b'\n')
+            .map_or(0, |pos| pos + 1);
+
+        Self::Parse {
+            line: before.iter().filter(|&&b| b == b'\n').count() + 1,
+            column: offset - line_start + 1,
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid(message: impl Into<String>) -> Self {
+        Self::Invalid(message.into())
+    }
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::Parse {
+                line,
+                column,
+                message,
+            } => write!(f, "parse error at line {line}, column {column}: {message}"),
+            SolveError::Invalid(message) => write!(f, "invalid input: {message}"),
+            SolveError::Panic(message) => write!(f, "panicked: {message}"),
+            SolveError::Cancelled => f.write_str("cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// Runs `fun`, turning a panic into a [`SolveError::Panic`], or into a [`SolveError::Cancelled`] if
+/// it was stopped at a checkpoint.
+///
+/// Note that this only works if the binary is not compiled with `panic = "abort"`.
+pub fn catch_panic<T>(fun: impl FnOnce() -> T) -> Result<T, SolveError> {
+    std::panic::catch_unwind(AssertUnwindSafe(fun)).map_err(|payload| {
+        if payload.is::<Cancelled>() {
+            return SolveError::Cancelled;
+        }
+
+        let message = if let Some(message) = payload.downcast_ref::<&str>() {
+            (*message).to_string()
+        } else if let Some(message) = payload.downcast_ref::<String>() {
+            message.clone()
+        } else {
+            "unknown panic payload".to_string()
+        };
+
+        SolveError::Panic(message)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_at() {
+        let input = "abc\ndef\nghi";
+        assert_eq!(
+            SolveError::at(input, &input[0..], "x"),
+            SolveError::Parse {
+                line: 1,
+                column: 1,
+                message: "x".to_string()
+            }
+        );
+        assert_eq!(
+            SolveError::at(input, &input[5..], "x"),
+            SolveError::Parse {
+                line: 2,
+                column: 2,
+                message: "x".to_string()
+            }
+        );
+        assert_eq!(
+            SolveError::at(input, &input[input.len()..], "x"),
+            SolveError::Parse {
+                line: 3,
+                column: 4,
+                message: "x".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let error = SolveError::at_offset("ab\ncd", 4, "expected a number");
+        assert_eq!(
+            error.to_string(),
+            "parse error at line 2, column 2: expected a number"
+        );
+    }
+
+    #[test]
+    fn test_catch_panic() {
+        assert_eq!(catch_panic(|| 5), Ok(5));
+        assert_eq!(
+            catch_panic(|| -> usize { panic!("oh no {}", 5) }),
+            Err(SolveError::Panic("oh no 5".to_string()))
+        );
+        assert_eq!(
+            catch_panic(|| std::panic::resume_unwind(Box::new(Cancelled))),
+            Err::<(), _>(SolveError::Cancelled)
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! Random puzzle inputs of any size.
+//!
+//! Every day can generate valid inputs for itself through [`SolutionSilver::generate`], which is
+//! used by the property tests, the `generate` command and the scaling benchmarks. What the size
+//! means depends on the day, such as the number of elves or the width of a map, and is documented
+//! on each generator. Use [`DayEntry::generate`](crate::registry::DayEntry::generate) to generate
+//! the input of a day from the registry.
+
+use std::ops::RangeInclusive;
+
+/// A small deterministic random number generator (xorshift64*), so every generated input can be
+/// reproduced from its seed.
+#[derive(Debug, Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // scramble the seed with splitmix64, so similar seeds give unrelated sequences and the
+        // state is never zero
+        let mut z = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        Self((z ^ (z >> 31)) | 1)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// A number in `range`. The distribution is slightly biased for very large ranges, which does
+    /// not matter for generating inputs.
+    pub fn usize(&mut self, range: RangeInclusive<usize>) -> usize {
+        let (start, end) = (*range.start(), *range.end());
+        assert!(start <= end, "empty range {start}..={end}");
+        match (end - start).checked_add(1) {
+            Some(len) => start + (self.next_u64() % len as u64) as usize,
+            None => self.next_u64() as usize,
+        }
+    }
+
+    pub fn isize(&mut self, range: RangeInclusive<isize>) -> isize {
+        let (start, end) = (*range.start(), *range.end());
+        let offset = self.usize(0..=end.abs_diff(start));
+        start.wrapping_add_unsigned(offset)
+    }
+
+    /// Returns true with a chance of `numerator` out of `denominator`.
+    pub fn chance(&mut self, numerator: usize, denominator: usize) -> bool {
+        self.usize(1..=denominator) <= numerator
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.usize(0..=items.len() - 1)]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.usize(0..=i));
+        }
+    }
+
+    /// A random lowercase letter.
+    pub fn letter(&mut self) -> char {
+        (b'a' + self.usize(0..=25) as u8) as char
+    }
+
+    /// A word of random lowercase letters, with a length in `len`.
+    pub fn word(&mut self, len: RangeInclusive<usize>) -> String {
+        (0..self.usize(len)).map(|_| self.letter()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rng() {
+        let mut rng = Rng::new(0);
+        let values: Vec<_> = (0..1000).map(|_| rng.usize(3..=7)).collect();
+        assert!(values.iter().all(|v| (3..=7).contains(v)));
+        assert!((3..=7).all(|v| values.contains(&v)));
+
+        let values: Vec<_> = (0..1000).map(|_| rng.isize(-2..=2)).collect();
+        assert!((-2..=2).all(|v| values.contains(&v)));
+        assert_eq!(rng.usize(5..=5), 5);
+        let _ = rng.usize(0..=usize::MAX);
+
+        let mut items = [1, 2, 3, 4, 5];
+        rng.shuffle(&mut items);
+        items.sort_unstable();
+        assert_eq!(items, [1, 2, 3, 4, 5]);
+
+        let word = rng.word(2..=4);
+        assert!((2..=4).contains(&word.len()));
+        assert!(word.bytes().all(|b| b.is_ascii_lowercase()));
+
+        let mut a = Rng::new(42);
+        let mut b = Rng::new(42);
+        assert!((0..10).all(|_| a.next_u64() == b.next_u64()));
+    }
+
+    #[test]
+    fn test_generated_inputs() {
+        // the inputs should be valid for every size, but solving large ones takes too long in
+        // debug builds. The gold search of day 19 takes minutes even for a single blueprint.
+        for entry in crate::registry::days() {
+            for (seed, size) in [(0, 1), (1, 2), (2, 5), (3, 12)] {
+                let Some(input) = entry.generate(size, seed) else {
+                    continue;
+                };
+                assert_eq!(entry.generate(size, seed).as_ref(), Some(&input));
+                assert_eq!(entry.format.normalize(&input), input, "day {}", entry.day);
+
+                let params = crate::Params::default();
+                for part in crate::Part::ALL {
+                    if entry.day == 19 && part == crate::Part::Gold {
+                        continue;
+                    }
+                    if let Some(Err(e)) = entry.solve(part, &input, &params) {
+                        panic!(
+                            "day {} {part} failed for size {size}: {e}\n{input}",
+                            entry.day
+                        );
+                    }
+                }
+            }
+        }
+    }
+}
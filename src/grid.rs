@@ -0,0 +1,378 @@
+use std::ops::{Index, IndexMut};
+
+use crate::SolveError;
+
+/// The offsets of the 4 orthogonal neighbours: right, down, left and up.
+const OFFSETS_4: [(isize, isize); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+/// The offsets of all 8 neighbours, clockwise starting from the right.
+const OFFSETS_8: [(isize, isize); 8] = [
+    (1, 0),
+    (1, 1),
+    (0, 1),
+    (-1, 1),
+    (-1, 0),
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+];
+
+/// A rectangular grid that is stored row by row in a single allocation.
+///
+/// Positions are `(x, y)` pairs where `(0, 0)` is the top left cell. Cells can also be addressed by
+/// their index in [`Grid::cells`], which is useful for days that store positions compactly.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid<T> {
+    cells: Vec<T>,
+    width: usize,
+    height: usize,
+}
+
+impl<T> Grid<T> {
+    /// Creates a grid where every cell is `fill`.
+    pub fn new(width: usize, height: usize, fill: T) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            cells: vec![fill; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// Creates a grid from cells that are stored row by row. Panics if the cells do not fill
+    /// complete rows.
+    pub fn from_cells(width: usize, cells: Vec<T>) -> Self {
+        let height = cells.len().checked_div(width).unwrap_or(0);
+        assert_eq!(
+            cells.len(),
+            width * height,
+            "cells do not fit in rows of {width}"
+        );
+
+        Self {
+            cells,
+            width,
+            height,
+        }
+    }
+
+    /// Parses a character map where every line is a row and every byte is a cell. `map` converts
+    /// a byte to a cell, or returns `None` if the byte is not valid. All lines must have the same
+    /// length.
+    pub fn parse(input: &str, mut map: impl FnMut(u8) -> Option<T>) -> Result<Self, SolveError> {
+        if input.is_empty() {
+            return Ok(Self::from_cells(0, vec![]));
+        }
+
+        let width = input.find('\n').unwrap_or(input.len());
+        let mut cells = Vec::with_capacity(input.len());
+        for line in input.split('\n') {
+            if line.len() != width {
+                return Err(SolveError::at(
+                    input,
+                    line,
+                    format!("expected a row of {width} cells, found {}", line.len()),
+                ));
+            }
+
+            for (i, &byte) in line.as_bytes().iter().enumerate() {
+                let Some(cell) = map(byte) else {
+                    return Err(SolveError::at(
+                        input,
+                        &line[i..],
+                        format!("unexpected character `{}`", byte.escape_ascii()),
+                    ));
+                };
+                cells.push(cell);
+            }
+        }
+
+        Ok(Self::from_cells(width, cells))
+    }
+
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The cells of the grid, row by row.
+    pub fn cells(&self) -> &[T] {
+        &self.cells
+    }
+
+    pub fn cells_mut(&mut self) -> &mut [T] {
+        &mut self.cells
+    }
+
+    /// Whether `(x, y)` is inside the grid.
+    pub const fn contains(&self, (x, y): (usize, usize)) -> bool {
+        x < self.width && y < self.height
+    }
+
+    /// The index in [`Grid::cells`] of the cell at `position`.
+    pub const fn index_of(&self, (x, y): (usize, usize)) -> usize {
+        y * self.width + x
+    }
+
+    /// The position of the cell at `index` in [`Grid::cells`].
+    pub const fn position_of(&self, index: usize) -> (usize, usize) {
+        (index % self.width, index / self.width)
+    }
+
+    pub fn get(&self, position: (usize, usize)) -> Option<&T> {
+        self.contains(position)
+            .then(|| &self.cells[self.index_of(position)])
+    }
+
+    pub fn get_mut(&mut self, position: (usize, usize)) -> Option<&mut T> {
+        let index = self.index_of(position);
+        self.contains(position).then(|| &mut self.cells[index])
+    }
+
+    /// The positions of all cells, row by row.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (x, y)))
+    }
+
+    /// The position of the first cell, row by row, that matches `predicate`.
+    pub fn find(&self, predicate: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+        let index = self.cells.iter().position(predicate)?;
+        Some(self.position_of(index))
+    }
+
+    pub fn row(&self, y: usize) -> &[T] {
+        &self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        &mut self.cells[y * self.width..(y + 1) * self.width]
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    /// The cells in column `x`, from top to bottom.
+    pub fn column(&self, x: usize) -> impl Iterator<Item = &T> {
+        assert!(x < self.width, "column {x} is outside the grid");
+        self.cells.iter().skip(x).step_by(self.width)
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = impl Iterator<Item = &T>> {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// Creates a grid where rows are columns and columns are rows.
+    pub fn transpose(&self) -> Self
+    where
+        T: Clone,
+    {
+        Self {
+            cells: self.columns().flatten().cloned().collect(),
+            width: self.height,
+            height: self.width,
+        }
+    }
+
+    /// Creates a grid of the same size by converting every cell.
+    pub fn map<U>(&self, fun: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid {
+            cells: self.cells.iter().map(fun).collect(),
+            width: self.width,
+            height: self.height,
+        }
+    }
+
+    /// The position that is `offset` away from `position`, if it is inside the grid.
+    pub fn offset(
+        &self,
+        (x, y): (usize, usize),
+        (dx, dy): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        let position = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+        self.contains(position).then_some(position)
+    }
+
+    /// The position that is `offset` away from `position`, wrapping around the edges.
+    pub fn wrapping_offset(
+        &self,
+        (x, y): (usize, usize),
+        (dx, dy): (isize, isize),
+    ) -> (usize, usize) {
+        let wrap = |value: usize, delta: isize, size: usize| {
+            (value as isize + delta).rem_euclid(size as isize) as usize
+        };
+        (wrap(x, dx, self.width), wrap(y, dy, self.height))
+    }
+
+    /// The orthogonal neighbours of `position` that are inside the grid.
+    pub fn neighbours4(
+        &self,
+        position: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        OFFSETS_4
+            .into_iter()
+            .filter_map(move |offset| self.offset(position, offset))
+    }
+
+    /// The orthogonal and diagonal neighbours of `position` that are inside the grid.
+    pub fn neighbours8(
+        &self,
+        position: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        OFFSETS_8
+            .into_iter()
+            .filter_map(move |offset| self.offset(position, offset))
+    }
+
+    /// The orthogonal neighbours of `position`, where the grid wraps around at its edges.
+    pub fn wrapping_neighbours4(
+        &self,
+        position: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        OFFSETS_4
+            .into_iter()
+            .map(move |offset| self.wrapping_offset(position, offset))
+    }
+
+    /// The orthogonal and diagonal neighbours of `position`, where the grid wraps around at its
+    /// edges.
+    pub fn wrapping_neighbours8(
+        &self,
+        position: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> + '_ {
+        OFFSETS_8
+            .into_iter()
+            .map(move |offset| self.wrapping_offset(position, offset))
+    }
+}
+
+impl<T> Index<(usize, usize)> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, position: (usize, usize)) -> &T {
+        assert!(self.contains(position), "{position:?} is outside the grid");
+        &self.cells[self.index_of(position)]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for Grid<T> {
+    fn index_mut(&mut self, position: (usize, usize)) -> &mut T {
+        assert!(self.contains(position), "{position:?} is outside the grid");
+        let index = self.index_of(position);
+        &mut self.cells[index]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digits(input: &str) -> Result<Grid<u8>, SolveError> {
+        Grid::parse(input, |b| (b as char).to_digit(10).map(|d| d as u8))
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = digits("123\n456").unwrap();
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert_eq!(grid.cells(), [1, 2, 3, 4, 5, 6]);
+        assert_eq!(grid[(2, 1)], 6);
+        assert_eq!(grid.get((3, 0)), None);
+        assert_eq!(grid.find(|&c| c == 5), Some((1, 1)));
+
+        let grid = digits("").unwrap();
+        assert_eq!((grid.width(), grid.height()), (0, 0));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            digits("123\n45"),
+            Err(SolveError::Parse {
+                line: 2,
+                column: 1,
+                message: "expected a row of 3 cells, found 2".to_string()
+            })
+        );
+        assert_eq!(
+            digits("123\n4x6"),
+            Err(SolveError::Parse {
+                line: 2,
+                column: 2,
+                message: "unexpected character `x`".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_index() {
+        let mut grid = Grid::new(3, 2, 0);
+        grid[(1, 1)] = 5;
+        *grid.get_mut((2, 0)).unwrap() = 3;
+        assert_eq!(grid.cells(), [0, 0, 3, 0, 5, 0]);
+        assert_eq!(grid.index_of((1, 1)), 4);
+        assert_eq!(grid.position_of(4), (1, 1));
+        assert!(grid.get_mut((0, 2)).is_none());
+        assert_eq!(
+            grid.positions().collect::<Vec<_>>(),
+            [(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)]
+        );
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = Grid::new(3, 3, ());
+        assert_eq!(
+            grid.neighbours4((0, 0)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1)]
+        );
+        assert_eq!(grid.neighbours4((1, 1)).count(), 4);
+        assert_eq!(
+            grid.neighbours8((2, 2)).collect::<Vec<_>>(),
+            [(1, 2), (1, 1), (2, 1)]
+        );
+        assert_eq!(grid.neighbours8((1, 1)).count(), 8);
+
+        assert_eq!(
+            grid.wrapping_neighbours4((0, 0)).collect::<Vec<_>>(),
+            [(1, 0), (0, 1), (2, 0), (0, 2)]
+        );
+        assert_eq!(
+            grid.wrapping_neighbours8((2, 2)).collect::<Vec<_>>(),
+            [
+                (0, 2),
+                (0, 0),
+                (2, 0),
+                (1, 0),
+                (1, 2),
+                (1, 1),
+                (2, 1),
+                (0, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = digits("123\n456").unwrap();
+        assert_eq!(grid.rows().collect::<Vec<_>>(), [[1, 2, 3], [4, 5, 6]]);
+        assert_eq!(grid.column(1).copied().collect::<Vec<_>>(), [2, 5]);
+
+        let transposed = grid.transpose();
+        assert_eq!((transposed.width(), transposed.height()), (2, 3));
+        assert_eq!(transposed.cells(), [1, 4, 2, 5, 3, 6]);
+        assert_eq!(transposed.transpose(), grid);
+
+        let mut grid = grid.map(|&c| c * 2);
+        grid.row_mut(0)[0] = 0;
+        assert_eq!(grid.row(0), [0, 4, 6]);
+    }
+}
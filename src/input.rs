@@ -0,0 +1,447 @@
+use std::{
+    borrow::Cow,
+    fmt::Display,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+/// The directory that is searched for inputs when no other source is configured.
+pub const DEFAULT_INPUT_DIR: &str = "inputs";
+
+/// Environment variable that overrides [`DEFAULT_INPUT_DIR`].
+pub const INPUT_DIR_ENV_VAR: &str = "AOC_INPUT_DIR";
+
+/// The kind of input a solution is run against.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum InputKind {
+    /// The example input from the puzzle description.
+    Sample,
+    /// The personal puzzle input.
+    Real,
+}
+
+impl InputKind {
+    pub const ALL: [InputKind; 2] = [InputKind::Sample, InputKind::Real];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            InputKind::Sample => "sample",
+            InputKind::Real => "real",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.name() == name)
+    }
+}
+
+impl Display for InputKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// How a day expects its input to be laid out. Inputs that are not embedded are normalized to
+/// this before they are parsed, so files saved with a different editor still give the same answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InputFormat {
+    /// Remove a leading byte order mark.
+    pub strip_bom: bool,
+    /// Replace `\r\n` line endings with `\n`.
+    pub unix_line_endings: bool,
+    /// What is removed from the end of the input.
+    pub trim_end: TrimEnd,
+}
+
+/// What [`InputFormat`] removes from the end of an input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrimEnd {
+    /// Keep the input as it is.
+    Nothing,
+    /// Remove trailing line breaks, but keep other whitespace.
+    Newlines,
+    /// Remove all trailing whitespace.
+    Whitespace,
+}
+
+impl InputFormat {
+    /// The layout of the embedded inputs: no byte order mark, `\n` line endings and nothing after
+    /// the last line.
+    pub const DEFAULT: InputFormat = InputFormat {
+        strip_bom: true,
+        unix_line_endings: true,
+        trim_end: TrimEnd::Whitespace,
+    };
+
+    /// Leaves the input untouched.
+    pub const RAW: InputFormat = InputFormat {
+        strip_bom: false,
+        unix_line_endings: false,
+        trim_end: TrimEnd::Nothing,
+    };
+
+    pub const fn trim_end(self, trim_end: TrimEnd) -> Self {
+        Self { trim_end, ..self }
+    }
+
+    /// Normalizes `input` to this format. Only allocates if line endings need to be replaced.
+    pub fn normalize(self, input: &str) -> Cow<'_, str> {
+        let mut text = input;
+        if self.strip_bom {
+            text = text.strip_prefix('\u{feff}').unwrap_or(text);
+        }
+        text = match self.trim_end {
+            TrimEnd::Nothing => text,
+            TrimEnd::Newlines => text.trim_end_matches(['\r', '\n']),
+            TrimEnd::Whitespace => text.trim_end(),
+        };
+
+        if self.unix_line_endings && text.contains('\r') {
+            Cow::Owned(text.replace("\r\n", "\n"))
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+
+    /// Like [`InputFormat::normalize`], but keeps the lifetime of a loaded input.
+    pub fn apply(self, input: Cow<'_, str>) -> Cow<'_, str> {
+        match input {
+            Cow::Borrowed(text) => self.normalize(text),
+            Cow::Owned(text) => match self.normalize(&text) {
+                Cow::Borrowed(normalized) if normalized.len() == text.len() => Cow::Owned(text),
+                normalized => Cow::Owned(normalized.into_owned()),
+            },
+        }
+    }
+}
+
+impl Default for InputFormat {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Where puzzle inputs are loaded from.
+#[derive(Debug, Clone)]
+pub enum InputSource {
+    /// Only use the inputs that are embedded in the binary.
+    Embedded,
+    /// Look for `dayNN.txt` (real) and `dayNN_sample.txt` (sample) in a directory. Days without a
+    /// file in this directory fall back to their embedded inputs. Parameters are read from
+    /// `dayNN.params` and `dayNN_sample.params`.
+    Directory(PathBuf),
+    /// Use the contents of a single file as the real input. Sample inputs stay embedded.
+    /// Parameters are read from a file next to it with the `params` extension.
+    File(PathBuf),
+    /// Use an in-memory string as the real input, for example the contents of stdin. Sample
+    /// inputs stay embedded.
+    Text(String),
+}
+
+/// Resolves the input text for a given day and [`InputKind`].
+///
+/// The inputs embedded through `include_str!` are only used when the configured source does not
+/// provide an input for that day.
+#[derive(Debug, Clone)]
+pub struct InputProvider {
+    source: InputSource,
+}
+
+impl InputProvider {
+    pub const fn new(source: InputSource) -> Self {
+        Self { source }
+    }
+
+    pub const fn embedded() -> Self {
+        Self::new(InputSource::Embedded)
+    }
+
+    pub fn directory(path: impl Into<PathBuf>) -> Self {
+        Self::new(InputSource::Directory(path.into()))
+    }
+
+    pub fn file(path: impl Into<PathBuf>) -> Self {
+        Self::new(InputSource::File(path.into()))
+    }
+
+    /// Reads all of stdin and uses it as the real input.
+    pub fn stdin() -> Result<Self, InputError> {
+        let mut text = String::new();
+        std::io::stdin()
+            .read_to_string(&mut text)
+            .map_err(|e| InputError::new("<stdin>", e))?;
+
+        Ok(Self::new(InputSource::Text(text)))
+    }
+
+    /// Creates a provider that reads from the directory in [`INPUT_DIR_ENV_VAR`], or from
+    /// [`DEFAULT_INPUT_DIR`] if it is not set.
+    pub fn from_env() -> Self {
+        match std::env::var_os(INPUT_DIR_ENV_VAR) {
+            Some(dir) => Self::directory(dir),
+            None => Self::directory(DEFAULT_INPUT_DIR),
+        }
+    }
+
+    pub const fn source(&self) -> &InputSource {
+        &self.source
+    }
+
+    /// Loads the input for `day`, using `embedded` if the source has no input for it.
+    pub fn load(
+        &self,
+        day: u32,
+        kind: InputKind,
+        embedded: &'static str,
+    ) -> Result<Cow<'_, str>, InputError> {
+        match (&self.source, kind) {
+            (InputSource::Embedded, _) => Ok(Cow::Borrowed(embedded)),
+            (InputSource::Directory(dir), _) => {
+                let path = dir.join(Self::file_name(day, kind));
+                if path.is_file() {
+                    read_file(&path).map(Cow::Owned)
+                } else {
+                    Ok(Cow::Borrowed(embedded))
+                }
+            }
+            (InputSource::File(path), InputKind::Real) => read_file(path).map(Cow::Owned),
+            (InputSource::Text(text), InputKind::Real) => Ok(Cow::Borrowed(text)),
+            (InputSource::File(_) | InputSource::Text(_), InputKind::Sample) => {
+                Ok(Cow::Borrowed(embedded))
+            }
+        }
+    }
+
+    /// Loads the [`Params`](crate::Params) text for the input of `day`. Embedded inputs use
+    /// `embedded`. Inputs from other sources use the parameters file next to them, and the
+    /// defaults of the day if there is none.
+    pub fn load_params(
+        &self,
+        day: u32,
+        kind: InputKind,
+        embedded: &'static str,
+    ) -> Result<Cow<'_, str>, InputError> {
+        let path = match (&self.source, kind) {
+            (InputSource::Directory(dir), _) if self.replaces(day, kind) => {
+                dir.join(Self::params_file_name(day, kind))
+            }
+            (InputSource::File(path), InputKind::Real) => path.with_extension("params"),
+            (InputSource::Text(_), InputKind::Real) => return Ok(Cow::Borrowed("")),
+            _ => return Ok(Cow::Borrowed(embedded)),
+        };
+
+        if path.is_file() {
+            read_file(&path).map(Cow::Owned)
+        } else {
+            Ok(Cow::Borrowed(""))
+        }
+    }
+
+    /// Whether the source replaces the embedded input for `day`.
+    pub fn replaces(&self, day: u32, kind: InputKind) -> bool {
+        match &self.source {
+            InputSource::Embedded => false,
+            InputSource::Directory(dir) => dir.join(Self::file_name(day, kind)).is_file(),
+            InputSource::File(_) | InputSource::Text(_) => kind == InputKind::Real,
+        }
+    }
+
+    /// The file name that is used for `day` in an input directory.
+    pub fn file_name(day: u32, kind: InputKind) -> String {
+        match kind {
+            InputKind::Sample => format!("day{day:02}_sample.txt"),
+            InputKind::Real => format!("day{day:02}.txt"),
+        }
+    }
+
+    /// The file name of the parameters for `day` in an input directory.
+    pub fn params_file_name(day: u32, kind: InputKind) -> String {
+        match kind {
+            InputKind::Sample => format!("day{day:02}_sample.params"),
+            InputKind::Real => format!("day{day:02}.params"),
+        }
+    }
+}
+
+impl Default for InputProvider {
+    fn default() -> Self {
+        Self::from_env()
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, InputError> {
+    std::fs::read_to_string(path).map_err(|e| InputError::new(path, e))
+}
+
+/// An input that was configured but could not be read.
+#[derive(Debug)]
+pub struct InputError {
+    path: PathBuf,
+    source: std::io::Error,
+}
+
+impl InputError {
+    pub(crate) fn new(path: impl Into<PathBuf>, source: std::io::Error) -> Self {
+        Self {
+            path: path.into(),
+            source,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Display for InputError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "could not read {}: {}", self.path.display(), self.source)
+    }
+}
+
+impl std::error::Error for InputError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("aoc2022-{name}-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn test_file_name() {
+        assert_eq!(InputProvider::file_name(1, InputKind::Real), "day01.txt");
+        assert_eq!(
+            InputProvider::file_name(25, InputKind::Sample),
+            "day25_sample.txt"
+        );
+    }
+
+    #[test]
+    fn test_embedded() {
+        let provider = InputProvider::embedded();
+        assert_eq!(provider.load(1, InputKind::Real, "abc").unwrap(), "abc");
+        assert_eq!(provider.load(1, InputKind::Sample, "def").unwrap(), "def");
+    }
+
+    #[test]
+    fn test_directory_with_fallback() {
+        let dir = temp_dir("directory");
+        std::fs::write(dir.join("day03.txt"), "from disk").unwrap();
+
+        let provider = InputProvider::directory(&dir);
+        assert!(provider.replaces(3, InputKind::Real));
+        assert!(!provider.replaces(3, InputKind::Sample));
+        assert_eq!(
+            provider.load(3, InputKind::Real, "embedded").unwrap(),
+            "from disk"
+        );
+        assert_eq!(
+            provider.load(3, InputKind::Sample, "embedded").unwrap(),
+            "embedded"
+        );
+        assert_eq!(
+            provider.load(4, InputKind::Real, "embedded").unwrap(),
+            "embedded"
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_file_only_replaces_real_input() {
+        let dir = temp_dir("file");
+        let path = dir.join("custom.txt");
+        std::fs::write(&path, "custom").unwrap();
+
+        let provider = InputProvider::file(&path);
+        assert_eq!(
+            provider.load(7, InputKind::Real, "embedded").unwrap(),
+            "custom"
+        );
+        assert_eq!(
+            provider.load(7, InputKind::Sample, "embedded").unwrap(),
+            "embedded"
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_params() {
+        let dir = temp_dir("params");
+        std::fs::write(dir.join("day15.txt"), "from disk").unwrap();
+        std::fs::write(dir.join("day15.params"), "row = 5").unwrap();
+        std::fs::write(dir.join("day22_sample.txt"), "from disk").unwrap();
+        std::fs::write(dir.join("day16_sample.params"), "ignored = 1").unwrap();
+
+        let provider = InputProvider::directory(&dir);
+        let load = |day, kind| provider.load_params(day, kind, "embedded").unwrap();
+        assert_eq!(load(15, InputKind::Real), "row = 5");
+        assert_eq!(load(15, InputKind::Sample), "embedded");
+        assert_eq!(load(22, InputKind::Sample), "");
+        assert_eq!(load(16, InputKind::Sample), "embedded");
+
+        let path = dir.join("custom.txt");
+        std::fs::write(dir.join("custom.params"), "bound = 7").unwrap();
+        let provider = InputProvider::file(path);
+        assert_eq!(
+            provider.load_params(15, InputKind::Real, "").unwrap(),
+            "bound = 7"
+        );
+        assert_eq!(
+            provider
+                .load_params(15, InputKind::Sample, "embedded")
+                .unwrap(),
+            "embedded"
+        );
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn test_normalize() {
+        let format = InputFormat::DEFAULT;
+        assert_eq!(format.normalize("a\nb"), "a\nb");
+        assert!(matches!(format.normalize("a\nb\n"), Cow::Borrowed("a\nb")));
+        assert_eq!(format.normalize("\u{feff}a\r\n b\r\n\r\n"), "a\n b");
+        assert_eq!(format.normalize("a \t\n"), "a");
+        assert_eq!(format.normalize(""), "");
+
+        let format = InputFormat::DEFAULT.trim_end(TrimEnd::Newlines);
+        assert_eq!(format.normalize("  a  \r\n  \r\n"), "  a  \n  ");
+
+        let format = InputFormat::RAW;
+        assert_eq!(format.normalize("\u{feff}a\r\n"), "\u{feff}a\r\n");
+
+        let owned = InputFormat::DEFAULT.apply(Cow::Owned("a\r\nb\n".to_string()));
+        assert_eq!(owned, "a\nb");
+    }
+
+    #[test]
+    fn test_missing_file_is_an_error() {
+        let provider = InputProvider::file("this/file/does/not/exist.txt");
+        let error = provider.load(1, InputKind::Real, "embedded").unwrap_err();
+        assert_eq!(error.path(), Path::new("this/file/does/not/exist.txt"));
+    }
+
+    #[test]
+    fn test_text() {
+        let provider = InputProvider::new(InputSource::Text("stdin".to_string()));
+        assert_eq!(
+            provider.load(2, InputKind::Real, "embedded").unwrap(),
+            "stdin"
+        );
+        assert_eq!(
+            provider.load(2, InputKind::Sample, "embedded").unwrap(),
+            "embedded"
+        );
+    }
+}
@@ -12,8 +12,32 @@
     clippy::type_complexity
 )]
 
-use std::fmt::Display;
+use std::{fmt::Display, time::Duration};
 
+use once_cell::sync::Lazy;
+
+pub use error::SolveError;
+pub use input::{InputFormat, InputKind, InputProvider, InputSource, TrimEnd};
+pub use params::Params;
+
+pub mod answers;
+pub mod baseline;
+pub mod cancel;
+pub mod cli;
+pub mod cycle;
+pub mod error;
+pub mod generate;
+pub mod grid;
+pub mod input;
+pub mod memory;
+pub mod params;
+#[cfg(test)]
+mod property;
+pub mod registry;
+pub mod report;
+pub mod runner;
+pub mod search;
+pub mod timing;
 mod utils;
 
 macro_rules! register_days {
@@ -21,47 +45,97 @@ macro_rules! register_days {
         // add `mod`
         $(paste::paste! { #[cfg(feature = "day" $day_index)] pub mod [<day $day_index>]; })*
 
-        /// Run implemenation for all days that are included in the feature set
+        paste::paste! {
+            /// Entries for all days that are included in the feature set. Use [`registry::days`]
+            /// to access them.
+            static REGISTRY: Lazy<Vec<registry::DayEntry>> = Lazy::new(|| vec![
+                $(#[cfg(feature = "day" $day_index)] register_days!(entry $day_index $type),)*
+            ]);
+        }
+
+        /// Run implemenation for all days that are included in the feature set, using the inputs
+        /// from [`InputProvider::from_env`].
         pub fn execute_all() {
-            $(paste::paste! {
-                #[cfg(feature = "day" $day_index)] register_days!(impl $day_index $type);
-            })*
+            execute_all_with(&InputProvider::from_env(), runner::Execution::Sequential, None);
+        }
+
+        /// Like [`execute_all`], but runs all days and parts concurrently. The results are still
+        /// printed in day order.
+        pub fn execute_all_parallel() {
+            execute_all_with(&InputProvider::from_env(), runner::Execution::Parallel, None);
+        }
+
+        /// Run implemenation for all days that are included in the feature set, using the given
+        /// input provider. Parts that take longer than `budget` are reported as a timeout.
+        pub fn execute_all_with(
+            inputs: &InputProvider,
+            execution: runner::Execution,
+            budget: Option<Duration>,
+        ) {
+            let answers = answers::Answers::for_inputs(inputs).unwrap_or_else(|e| {
+                println!("Could not load answers: {e}");
+                answers::Answers::default()
+            });
+            runner::run(
+                &runner::Selection::all(),
+                inputs,
+                &answers,
+                timing::Repetitions::ONCE,
+                report::Format::Pretty,
+                execution,
+                budget,
+            );
         }
     };
-    (impl $day_index:literal gold  ) => { paste::paste! { [<day $day_index>]::Day::execute(); }};
-    (impl $day_index:literal silver) => { paste::paste! { [<day $day_index>]::Day::execute_silver(); }};
+    (entry $day_index:literal gold) => { paste::paste! {
+        registry::DayEntry::gold::<[<day $day_index>]::Day, _, _>()
+    }};
+    (entry $day_index:literal silver) => { paste::paste! {
+        registry::DayEntry::silver::<[<day $day_index>]::Day, _>()
+    }};
 }
 
-// === Register days here! ===
-register_days! {
-    01 gold,
-    02 gold,
-    03 gold,
-    04 gold,
-    05 gold,
-    06 gold,
-    07 gold,
-    08 gold,
-    09 gold,
-    10 gold,
-    11 gold,
-    12 gold,
-    13 gold,
-    14 gold,
-    15 gold,
-    16 gold,
-    17 gold,
-    18 gold,
-    19 gold,
-    20 gold,
-    21 gold,
-    22 gold,
-    23 gold,
-    24 gold,
-    25 silver,
+/// Passes the list of all days to the macro `$callback`, as `$($day:literal $parts:ident,)*`. This
+/// is the only list of days, the registry and the benchmarks are generated from it.
+#[macro_export]
+macro_rules! for_each_day {
+    ($callback:ident) => {
+        // === Register days here! ===
+        // Days marked `gold` implement both parts. Days marked `silver` only implement the first
+        // part, so their gold part is reported as not implemented and skipped by the benchmarks.
+        $callback! {
+            01 gold,
+            02 gold,
+            03 gold,
+            04 gold,
+            05 gold,
+            06 gold,
+            07 gold,
+            08 gold,
+            09 gold,
+            10 gold,
+            11 gold,
+            12 gold,
+            13 gold,
+            14 gold,
+            15 gold,
+            16 gold,
+            17 gold,
+            18 gold,
+            19 gold,
+            20 gold,
+            21 gold,
+            22 gold,
+            23 gold,
+            24 gold,
+            25 silver,
+        }
+    };
 }
 
-fn run_timed<T, F>(fun: F) -> (T, std::time::Duration)
+for_each_day!(register_days);
+
+fn run_timed<T, F>(fun: F) -> (T, Duration)
 where
     F: FnOnce() -> T,
 {
@@ -71,40 +145,137 @@ where
     (ret, elapsed)
 }
 
+/// One of the two parts of a puzzle.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Part {
+    Silver,
+    Gold,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::Silver, Part::Gold];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Part::Silver => "silver",
+            Part::Gold => "gold",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|part| part.name() == name)
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The silver part of a day.
+///
+/// A solution is split into a [`parse`](SolutionSilver::parse) step, which is shared by both parts,
+/// and a solve step per part. This lets the runner and benchmarks time both phases separately.
 pub trait SolutionSilver<TSilver: Display> {
     const DAY: u32;
     const INPUT_SAMPLE: &'static str;
     const INPUT_REAL: &'static str;
 
-    fn execute_silver() {
-        let (output, time) = run_timed(|| Self::calculate_silver(Self::INPUT_SAMPLE));
-        println!("Day {}, silver (sample): {output} ({time:?})", Self::DAY,);
-        let (output, time) = run_timed(|| Self::calculate_silver(Self::INPUT_REAL));
-        println!("Day {}, silver: {output} ({time:?})", Self::DAY);
+    /// The [`Params`] of the embedded sample input. The real input always uses the defaults of the
+    /// day.
+    const PARAMS_SAMPLE: &'static str = "";
+
+    /// The layout this day expects its input to have. Inputs that are loaded from elsewhere are
+    /// normalized to it by the runner.
+    const INPUT_FORMAT: InputFormat = InputFormat::DEFAULT;
 
-        println!("Day {} has no gold implementation", Self::DAY);
+    /// The parsed input. Days that parse while solving can use the input itself (`&'a str`).
+    type Parsed<'a>;
+
+    /// Gets the embedded input of the given kind.
+    fn input(kind: InputKind) -> &'static str {
+        match kind {
+            InputKind::Sample => Self::INPUT_SAMPLE,
+            InputKind::Real => Self::INPUT_REAL,
+        }
+    }
+
+    /// Gets the parameters of the embedded input of the given kind, in the format of
+    /// [`Params::parse`].
+    fn params(kind: InputKind) -> &'static str {
+        match kind {
+            InputKind::Sample => Self::PARAMS_SAMPLE,
+            InputKind::Real => "",
+        }
+    }
+
+    /// Parses the input, reporting malformed input as an error.
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, SolveError>;
+
+    /// Parses the input using the puzzle parameters that belong to it. Only days that read
+    /// parameters need to implement this, the default ignores them.
+    fn parse_with<'a>(input: &'a str, _params: &Params) -> Result<Self::Parsed<'a>, SolveError> {
+        Self::parse(input)
+    }
+
+    /// Parses the input using the [`Params`] of the embedded sample if it is the embedded sample,
+    /// and the defaults of the day otherwise.
+    fn parse_with_embedded_params(input: &str) -> Result<Self::Parsed<'_>, SolveError> {
+        if input == Self::INPUT_SAMPLE {
+            Self::parse_with(input, &Params::parse(Self::PARAMS_SAMPLE)?)
+        } else {
+            Self::parse(input)
+        }
+    }
+
+    fn solve_silver(parsed: &Self::Parsed<'_>) -> TSilver;
+
+    /// Solves the silver part, reporting a parsed input that has no answer as an error. Only days
+    /// whose input can parse without having an answer need to implement this, and then implement
+    /// [`solve_silver`](SolutionSilver::solve_silver) by unwrapping it.
+    fn try_solve_silver(parsed: &Self::Parsed<'_>) -> Result<TSilver, SolveError> {
+        Ok(Self::solve_silver(parsed))
+    }
+
+    /// Generates a random valid input of the given size, or `None` if the day has no generator.
+    /// Each day documents what the size means for its input. See [`generate`].
+    fn generate(_rng: &mut generate::Rng, _size: usize) -> Option<String> {
+        None
+    }
+
+    /// Parses the input and solves the silver part. Panics if the input is malformed. The embedded
+    /// sample is solved with its own parameters, see
+    /// [`parse_with_embedded_params`](SolutionSilver::parse_with_embedded_params).
+    fn calculate_silver(input: &str) -> TSilver {
+        Self::try_calculate_silver(input).unwrap_or_else(|e| panic!("{e}"))
     }
 
-    fn calculate_silver(input: &str) -> TSilver;
+    /// Like [`SolutionSilver::calculate_silver`], but reports malformed input as an error instead
+    /// of panicking.
+    fn try_calculate_silver(input: &str) -> Result<TSilver, SolveError> {
+        Self::try_solve_silver(&Self::parse_with_embedded_params(input)?)
+    }
 }
 
+/// The gold part of a day, which shares its parsed input with the silver part.
 pub trait SolutionGold<TSilver: Display, TGold: Display>: SolutionSilver<TSilver> {
-    fn execute() {
-        let (output, time) = run_timed(|| Self::calculate_silver(Self::INPUT_SAMPLE));
-        println!("Day {}, silver (sample): {output} ({time:?})", Self::DAY);
-        let (output, time) = run_timed(|| Self::calculate_silver(Self::INPUT_REAL));
-        println!("Day {}, silver: {output} ({time:?})", Self::DAY);
-
-        let (output, time) = run_timed(|| Self::calculate_gold(Self::INPUT_SAMPLE));
-        println!("Day {}, gold (sample): {output} ({time:?})", Self::DAY);
-        let (output, time) = run_timed(|| Self::calculate_gold(Self::INPUT_REAL));
-        println!("Day {}, gold: {output} ({time:?})", Self::DAY);
-
-        #[cfg(feature = "profile")]
-        for _ in 0..100 {
-            Self::calculate_gold(Self::INPUT_REAL);
-        }
+    fn solve_gold(parsed: &Self::Parsed<'_>) -> TGold;
+
+    /// Solves the gold part, like [`SolutionSilver::try_solve_silver`].
+    fn try_solve_gold(parsed: &Self::Parsed<'_>) -> Result<TGold, SolveError> {
+        Ok(Self::solve_gold(parsed))
     }
 
-    fn calculate_gold(input: &str) -> TGold;
+    /// Parses the input and solves the gold part, like [`SolutionSilver::calculate_silver`]. Panics
+    /// if the input is malformed.
+    fn calculate_gold(input: &str) -> TGold {
+        Self::try_calculate_gold(input).unwrap_or_else(|e| panic!("{e}"))
+    }
+
+    /// Like [`SolutionGold::calculate_gold`], but reports malformed input as an error instead of
+    /// panicking.
+    fn try_calculate_gold(input: &str) -> Result<TGold, SolveError> {
+        Self::try_solve_gold(&Self::parse_with_embedded_params(input)?)
+    }
 }
@@ -1,3 +1,3 @@
 fn main() {
-    aoc2022::execute_all();
+    std::process::exit(aoc2022::cli::main(std::env::args().skip(1)));
 }
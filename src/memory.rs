@@ -0,0 +1,151 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+/// Whether heap usage is tracked. This requires the `memory` feature, which replaces the global
+/// allocator with [`CountingAllocator`].
+pub const ENABLED: bool = cfg!(feature = "memory");
+
+#[cfg(feature = "memory")]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// The number of bytes that are currently allocated.
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+/// The highest value of `CURRENT` since the last call to [`measure`].
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+/// The number of allocations and reallocations so far.
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// How much heap memory was used while running a part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Usage {
+    /// The most bytes that were allocated at the same time, not counting what was already
+    /// allocated before.
+    pub peak_bytes: usize,
+    /// The number of allocations, including reallocations.
+    pub allocations: usize,
+}
+
+/// An allocator that counts allocations and keeps track of the peak heap usage, and leaves the
+/// actual work to the system allocator.
+pub struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc_zeroed(layout);
+        if !ptr.is_null() {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            grow(layout.size());
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let new_ptr = System.realloc(ptr, layout, new_size);
+        if !new_ptr.is_null() {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            if new_size >= layout.size() {
+                grow(new_size - layout.size());
+            } else {
+                CURRENT.fetch_sub(layout.size() - new_size, Ordering::Relaxed);
+            }
+        }
+        new_ptr
+    }
+}
+
+fn grow(size: usize) {
+    let current = CURRENT.fetch_add(size, Ordering::Relaxed) + size;
+    PEAK.fetch_max(current, Ordering::Relaxed);
+}
+
+/// Calls `fun` and measures how much heap memory it used. The usage is `None` if tracking is not
+/// [`ENABLED`].
+///
+/// The counters are shared by all threads, so allocations made by other threads while `fun` runs
+/// are counted as well. Calls should not be nested, as every call resets the peak.
+pub fn measure<T>(fun: impl FnOnce() -> T) -> (T, Option<Usage>) {
+    if !ENABLED {
+        return (fun(), None);
+    }
+
+    let baseline = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(baseline, Ordering::Relaxed);
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed);
+
+    let output = fun();
+
+    let usage = Usage {
+        peak_bytes: PEAK.load(Ordering::Relaxed).saturating_sub(baseline),
+        allocations: ALLOCATIONS.load(Ordering::Relaxed) - allocations,
+    };
+    (output, Some(usage))
+}
+
+/// Formats a number of bytes with a binary unit, such as `1.5 MiB`.
+pub fn format_bytes(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+
+    let mut value = bytes as f64 / 1024.;
+    let mut unit = 0;
+    while value >= 1024. && unit < UNITS.len() - 1 {
+        value /= 1024.;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_measure() {
+        let (vec, usage) = measure(|| {
+            let mut vec = Vec::<u8>::with_capacity(4096);
+            vec.push(1);
+            vec
+        });
+        assert_eq!(vec, [1]);
+
+        if ENABLED {
+            // tests run in parallel, so other threads may have allocated as well
+            let usage = usage.unwrap();
+            assert!(usage.peak_bytes >= 4096, "{usage:?}");
+            assert!(usage.allocations >= 1, "{usage:?}");
+        } else {
+            assert_eq!(usage, None);
+        }
+    }
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(0), "0 B");
+        assert_eq!(format_bytes(1023), "1023 B");
+        assert_eq!(format_bytes(1024), "1.0 KiB");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(5 * 1024 * 1024), "5.0 MiB");
+        assert_eq!(format_bytes(3 << 40), "3.0 TiB");
+        assert_eq!(format_bytes(2048 << 40), "2048.0 TiB");
+    }
+}
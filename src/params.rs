@@ -0,0 +1,115 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use super::*;
+
+/// Puzzle parameters that are not part of the input text, such as the row that day 15 asks about.
+///
+/// Parameters are stored as `key = value` lines next to an input. Empty lines and lines starting
+/// with `#` are ignored. Days fall back to their own defaults for parameters that are not set,
+/// which are meant for the real inputs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Params {
+    values: BTreeMap<String, String>,
+}
+
+impl Params {
+    pub fn parse(text: &str) -> Result<Self, SolveError> {
+        let mut params = Self::default();
+
+        for line in text.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = trimmed.split_once('=') else {
+                return Err(SolveError::at(text, trimmed, "expected `key = value`"));
+            };
+            let (key, value) = (key.trim(), value.trim());
+            if key.is_empty() {
+                return Err(SolveError::at(text, trimmed, "expected a key"));
+            }
+            if params.values.contains_key(key) {
+                return Err(SolveError::at(
+                    text,
+                    trimmed,
+                    format!("duplicate parameter `{key}`"),
+                ));
+            }
+
+            params.insert(key, value);
+        }
+
+        Ok(params)
+    }
+
+    /// Gets the parameter `key`, or `None` if it is not set.
+    pub fn get<T: FromStr>(&self, key: &str) -> Result<Option<T>, SolveError> {
+        self.values
+            .get(key)
+            .map(|value| {
+                value.parse().map_err(|_| {
+                    SolveError::invalid(format!("invalid value `{value}` for parameter `{key}`"))
+                })
+            })
+            .transpose()
+    }
+
+    /// Gets the parameter `key`, or `default` if it is not set.
+    pub fn get_or<T: FromStr>(&self, key: &str, default: T) -> Result<T, SolveError> {
+        Ok(self.get(key)?.unwrap_or(default))
+    }
+
+    pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.values.insert(key.into(), value.into());
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let params = Params::parse("# comment\nrow = 10\n\n  bound=20  \nname = a = b\n").unwrap();
+        assert_eq!(params.len(), 3);
+        assert_eq!(params.get::<isize>("row"), Ok(Some(10)));
+        assert_eq!(params.get::<usize>("bound"), Ok(Some(20)));
+        assert_eq!(params.get::<String>("name"), Ok(Some("a = b".to_string())));
+        assert_eq!(params.get::<usize>("missing"), Ok(None));
+        assert_eq!(params.get_or("missing", 5usize), Ok(5));
+
+        assert!(Params::parse("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Params::parse("row = 10\n  bound 20"),
+            Err(SolveError::Parse {
+                line: 2,
+                column: 3,
+                message: "expected `key = value`".to_string()
+            })
+        );
+        assert!(Params::parse("= 5").is_err());
+        assert!(Params::parse("row = 1\nrow = 2").is_err());
+
+        let params = Params::parse("row = ten").unwrap();
+        assert_eq!(
+            params.get::<isize>("row"),
+            Err(SolveError::invalid(
+                "invalid value `ten` for parameter `row`"
+            ))
+        );
+        assert!(params.get_or("row", 0isize).is_err());
+    }
+}
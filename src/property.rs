@@ -0,0 +1,86 @@
+// not every day uses every generator helper, so single-day builds leave some of them unused
+#![allow(dead_code)]
+
+use std::{
+    fmt::{Debug, Display},
+    panic::{self, AssertUnwindSafe},
+};
+
+use crate::{generate::Rng, SolutionSilver};
+
+/// The environment variable that overrides the seed all generated cases are derived from.
+const SEED_ENV_VAR: &str = "AOC_PROPERTY_SEED";
+
+/// The environment variable that overrides the number of cases per property.
+const CASES_ENV_VAR: &str = "AOC_PROPERTY_CASES";
+
+/// The seed that is used when [`SEED_ENV_VAR`] is not set, so test runs are reproducible.
+const DEFAULT_SEED: u64 = 0x20221225;
+
+/// The largest size that is passed to generators.
+pub const MAX_SIZE: usize = 100;
+
+/// Generates the input of day `D` with a random size of at most `size`, so it can be passed to
+/// [`check`] as the generator.
+pub fn input<D: SolutionSilver<T>, T: Display>(rng: &mut Rng, size: usize) -> String {
+    let size = rng.usize(1..=size);
+    D::generate(rng, size).expect("the day should have an input generator")
+}
+
+/// Checks that `property` holds for `cases` inputs created by `generate`, which should panic
+/// (for example with `assert_eq!`) if it does not.
+///
+/// Generators get a size that grows from 1 to [`MAX_SIZE`] over the cases, so the first failing
+/// input tends to be small. A failure reports the seed of the case and the input that failed. The
+/// base seed and the number of cases can be changed with the `AOC_PROPERTY_SEED` and
+/// `AOC_PROPERTY_CASES` environment variables.
+pub fn check<T: Debug>(
+    cases: usize,
+    mut generate: impl FnMut(&mut Rng, usize) -> T,
+    mut property: impl FnMut(&T),
+) {
+    let seed = env_or(SEED_ENV_VAR, DEFAULT_SEED);
+    let cases = env_or(CASES_ENV_VAR, cases as u64) as usize;
+
+    for case in 0..cases {
+        let case_seed = seed.wrapping_add(case as u64);
+        let size = 1 + case * MAX_SIZE / cases.max(1);
+        let input = generate(&mut Rng::new(case_seed), size.min(MAX_SIZE));
+
+        if let Err(panic) = panic::catch_unwind(AssertUnwindSafe(|| property(&input))) {
+            eprintln!(
+                "property failed for case {case} of {cases} (seed {case_seed}, run with \
+                 {SEED_ENV_VAR}={seed}) with input:\n{input:?}"
+            );
+            panic::resume_unwind(panic);
+        }
+    }
+}
+
+fn env_or(name: &str, default: u64) -> u64 {
+    std::env::var(name)
+        .ok()
+        .map(|value| value.parse().unwrap_or_else(|_| panic!("invalid {name}")))
+        .unwrap_or(default)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check() {
+        let mut sizes = vec![];
+        check(10, |_, size| size, |&size| sizes.push(size));
+        assert_eq!(sizes, [1, 11, 21, 31, 41, 51, 61, 71, 81, 91]);
+
+        let result = panic::catch_unwind(|| {
+            check(
+                100,
+                |rng, _| rng.usize(0..=10),
+                |&value| assert!(value < 10),
+            );
+        });
+        assert!(result.is_err());
+    }
+}
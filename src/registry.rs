@@ -0,0 +1,290 @@
+use std::fmt::Display;
+
+use super::*;
+
+/// A type-erased entry point for one part of a day, taking the input and its [`Params`]. It returns
+/// the formatted answer, or an error if the input was malformed or the solution panicked.
+pub type SolveFn = fn(&str, &Params) -> Result<String, SolveError>;
+
+/// A type-erased parse step of a day. It parses the input and drops the result.
+pub type ParseFn = fn(&str, &Params) -> Result<(), SolveError>;
+
+/// A type-erased entry point for one part of a day that keeps parsing and solving apart. It parses
+/// the input once and passes a function to the callback that solves the part for the parsed input,
+/// so the solve step can be timed on its own. Parse errors are returned, the callback is not called
+/// for them.
+pub type WithParsedFn = fn(
+    &str,
+    &Params,
+    &mut dyn FnMut(&mut dyn FnMut() -> Result<String, SolveError>),
+) -> Result<(), SolveError>;
+
+/// A type-erased input generator of a day, see [`SolutionSilver::generate`].
+pub type GenerateFn = fn(&mut generate::Rng, usize) -> Option<String>;
+
+/// Describes a single day that is included in the feature set.
+#[derive(Clone, Copy)]
+pub struct DayEntry {
+    pub day: u32,
+    pub input_sample: &'static str,
+    pub input_real: &'static str,
+    pub params_sample: &'static str,
+    pub format: InputFormat,
+    pub parse: ParseFn,
+    pub silver: SolveFn,
+    pub gold: Option<SolveFn>,
+    pub silver_with_parsed: WithParsedFn,
+    pub gold_with_parsed: Option<WithParsedFn>,
+    pub generator: GenerateFn,
+}
+
+impl DayEntry {
+    /// Creates an entry for a day that only implements the silver part.
+    pub fn silver<D, TSilver>() -> Self
+    where
+        D: SolutionSilver<TSilver>,
+        TSilver: Display,
+    {
+        Self {
+            day: D::DAY,
+            input_sample: D::INPUT_SAMPLE,
+            input_real: D::INPUT_REAL,
+            params_sample: D::PARAMS_SAMPLE,
+            format: D::INPUT_FORMAT,
+            parse: parse::<D, TSilver>,
+            silver: solve_silver::<D, TSilver>,
+            gold: None,
+            silver_with_parsed: with_parsed_silver::<D, TSilver>,
+            gold_with_parsed: None,
+            generator: D::generate,
+        }
+    }
+
+    /// Creates an entry for a day that implements both parts.
+    pub fn gold<D, TSilver, TGold>() -> Self
+    where
+        D: SolutionGold<TSilver, TGold>,
+        TSilver: Display,
+        TGold: Display,
+    {
+        Self {
+            gold: Some(solve_gold::<D, TSilver, TGold>),
+            gold_with_parsed: Some(with_parsed_gold::<D, TSilver, TGold>),
+            ..Self::silver::<D, TSilver>()
+        }
+    }
+
+    pub const fn has_gold(&self) -> bool {
+        self.gold.is_some()
+    }
+
+    /// Gets the entry point for `part`, if it is implemented.
+    pub const fn part(&self, part: Part) -> Option<SolveFn> {
+        match part {
+            Part::Silver => Some(self.silver),
+            Part::Gold => self.gold,
+        }
+    }
+
+    /// Gets the entry point for `part` that keeps parsing and solving apart, if it is implemented.
+    pub fn with_parsed(&self, part: Part) -> Option<WithParsedFn> {
+        match part {
+            Part::Silver => Some(self.silver_with_parsed),
+            Part::Gold => self.gold_with_parsed,
+        }
+    }
+
+    /// Gets the embedded input of the given kind.
+    pub const fn input(&self, kind: InputKind) -> &'static str {
+        match kind {
+            InputKind::Sample => self.input_sample,
+            InputKind::Real => self.input_real,
+        }
+    }
+
+    /// Gets the parameters of the embedded input of the given kind, in the format of
+    /// [`Params::parse`].
+    pub const fn params(&self, kind: InputKind) -> &'static str {
+        match kind {
+            InputKind::Sample => self.params_sample,
+            InputKind::Real => "",
+        }
+    }
+
+    /// Generates a random input of the given size for the day, or `None` if it has no generator.
+    /// The same seed always gives the same input.
+    pub fn generate(&self, size: usize, seed: u64) -> Option<String> {
+        (self.generator)(&mut generate::Rng::new(seed), size)
+    }
+
+    /// Solves `part` for `input`, returning `None` if the part is not implemented.
+    pub fn solve(
+        &self,
+        part: Part,
+        input: &str,
+        params: &Params,
+    ) -> Option<Result<String, SolveError>> {
+        self.part(part).map(|fun| fun(input, params))
+    }
+
+    /// Solves `part` for one of the embedded inputs, using its embedded parameters.
+    pub fn solve_embedded(
+        &self,
+        part: Part,
+        kind: InputKind,
+    ) -> Option<Result<String, SolveError>> {
+        let params = Params::parse(self.params(kind)).expect("embedded parameters should be valid");
+        self.solve(part, self.input(kind), &params)
+    }
+}
+
+impl std::fmt::Debug for DayEntry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DayEntry")
+            .field("day", &self.day)
+            .field("has_gold", &self.has_gold())
+            .finish_non_exhaustive()
+    }
+}
+
+fn parse<D, TSilver>(input: &str, params: &Params) -> Result<(), SolveError>
+where
+    D: SolutionSilver<TSilver>,
+    TSilver: Display,
+{
+    error::catch_panic(|| D::parse_with(input, params).map(drop))?
+}
+
+fn solve_silver<D, TSilver>(input: &str, params: &Params) -> Result<String, SolveError>
+where
+    D: SolutionSilver<TSilver>,
+    TSilver: Display,
+{
+    error::catch_panic(|| {
+        let parsed = D::parse_with(input, params)?;
+        D::try_solve_silver(&parsed).map(|answer| answer.to_string())
+    })?
+}
+
+fn solve_gold<D, TSilver, TGold>(input: &str, params: &Params) -> Result<String, SolveError>
+where
+    D: SolutionGold<TSilver, TGold>,
+    TSilver: Display,
+    TGold: Display,
+{
+    error::catch_panic(|| {
+        let parsed = D::parse_with(input, params)?;
+        D::try_solve_gold(&parsed).map(|answer| answer.to_string())
+    })?
+}
+
+fn with_parsed_silver<D, TSilver>(
+    input: &str,
+    params: &Params,
+    fun: &mut dyn FnMut(&mut dyn FnMut() -> Result<String, SolveError>),
+) -> Result<(), SolveError>
+where
+    D: SolutionSilver<TSilver>,
+    TSilver: Display,
+{
+    let parsed = error::catch_panic(|| D::parse_with(input, params))??;
+    fun(&mut || {
+        error::catch_panic(|| D::try_solve_silver(&parsed).map(|answer| answer.to_string()))?
+    });
+    Ok(())
+}
+
+fn with_parsed_gold<D, TSilver, TGold>(
+    input: &str,
+    params: &Params,
+    fun: &mut dyn FnMut(&mut dyn FnMut() -> Result<String, SolveError>),
+) -> Result<(), SolveError>
+where
+    D: SolutionGold<TSilver, TGold>,
+    TSilver: Display,
+    TGold: Display,
+{
+    let parsed = error::catch_panic(|| D::parse_with(input, params))??;
+    fun(&mut || {
+        error::catch_panic(|| D::try_solve_gold(&parsed).map(|answer| answer.to_string()))?
+    });
+    Ok(())
+}
+
+/// All days that are included in the feature set, ordered by day.
+pub fn days() -> &'static [DayEntry] {
+    &REGISTRY
+}
+
+/// Gets the entry for `day`, if it is included in the feature set.
+pub fn get(day: u32) -> Option<&'static DayEntry> {
+    REGISTRY.iter().find(|entry| entry.day == day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days_are_ordered_and_unique() {
+        assert!(days().array_windows().all(|[a, b]| a.day < b.day));
+    }
+
+    #[test]
+    fn test_get() {
+        for entry in days() {
+            assert_eq!(get(entry.day).unwrap().day, entry.day);
+        }
+        assert!(get(0).is_none());
+        assert!(get(26).is_none());
+    }
+
+    #[test]
+    fn test_embedded_params() {
+        for entry in days() {
+            for kind in InputKind::ALL {
+                assert!(
+                    Params::parse(entry.params(kind)).is_ok(),
+                    "day {}",
+                    entry.day
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_normalized_variants() {
+        for entry in days() {
+            for kind in InputKind::ALL {
+                let input = entry.input(kind);
+                let variants = [
+                    input.replace('\n', "\r\n"),
+                    format!("{input}\n"),
+                    format!("{}\r\n\r\n", input.replace('\n', "\r\n")),
+                    format!("\u{feff}{input}\n"),
+                ];
+                for variant in variants {
+                    assert_eq!(entry.format.normalize(&variant), input, "day {}", entry.day);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_parsed() {
+        // solving every sample takes a while in debug builds, the first day is enough here
+        for entry in days().iter().take(1) {
+            for part in Part::ALL {
+                let Some(with_parsed) = entry.with_parsed(part) else {
+                    continue;
+                };
+
+                let input = entry.input(InputKind::Sample);
+                let params = Params::parse(entry.params(InputKind::Sample)).unwrap();
+                let mut answers = vec![];
+                with_parsed(input, &params, &mut |solve| answers.push(solve())).unwrap();
+                assert_eq!(answers, [entry.solve(part, input, &params).unwrap()]);
+            }
+        }
+    }
+}
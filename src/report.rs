@@ -0,0 +1,527 @@
+use std::fmt::Write;
+
+use crate::{
+    memory::{self, Usage},
+    timing::{PhaseStats, Stats},
+};
+
+use super::*;
+
+/// The format that results are printed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Format {
+    /// Human readable text, one line per result.
+    #[default]
+    Pretty,
+    /// A JSON array with one object per result.
+    Json,
+    /// CSV with a header row and one row per result.
+    Csv,
+}
+
+impl Format {
+    pub const ALL: [Format; 3] = [Format::Pretty, Format::Json, Format::Csv];
+
+    pub const fn name(self) -> &'static str {
+        match self {
+            Format::Pretty => "pretty",
+            Format::Json => "json",
+            Format::Csv => "csv",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|format| format.name() == name)
+    }
+}
+
+impl Display for Format {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// What happened when a part was run against an input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Answer(String),
+    /// The input could not be loaded, or the solution returned an error.
+    Error(String),
+    /// The part did not finish within its time budget.
+    Timeout(Duration),
+    /// The day does not implement this part.
+    NotImplemented,
+}
+
+/// How an answer compares to the expected answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail,
+    /// There is no expected answer to compare with.
+    Unknown,
+}
+
+impl Verdict {
+    pub const fn name(self) -> &'static str {
+        match self {
+            Verdict::Pass => "pass",
+            Verdict::Fail => "fail",
+            Verdict::Unknown => "unknown",
+        }
+    }
+}
+
+impl Display for Verdict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// The result of running a single part of a day against a single input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub day: u32,
+    pub part: Part,
+    pub kind: InputKind,
+    pub outcome: Outcome,
+    /// The answer the outcome is compared against, if it is known.
+    pub expected: Option<String>,
+    /// The time the measured runs of both phases took, or `None` if the part was not run.
+    pub timing: Option<PhaseStats>,
+    /// The heap usage of a single run, or `None` if it was not measured.
+    pub memory: Option<Usage>,
+}
+
+impl Record {
+    pub fn new(day: u32, part: Part, kind: InputKind, outcome: Outcome) -> Self {
+        Self {
+            day,
+            part,
+            kind,
+            outcome,
+            expected: None,
+            timing: None,
+            memory: None,
+        }
+    }
+
+    pub fn answer(&self) -> Option<&str> {
+        match &self.outcome {
+            Outcome::Answer(answer) => Some(answer),
+            _ => None,
+        }
+    }
+
+    pub fn error(&self) -> Option<&str> {
+        match &self.outcome {
+            Outcome::Error(error) => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Compares the outcome with [`Record::expected`]. Returns `None` if there is nothing to
+    /// compare, which is the case for parts that are not implemented and for errors without an
+    /// expected answer.
+    pub fn verdict(&self) -> Option<Verdict> {
+        match (&self.outcome, &self.expected) {
+            (Outcome::Answer(answer), Some(expected)) if answer == expected => Some(Verdict::Pass),
+            (Outcome::Answer(_) | Outcome::Error(_), Some(_)) => Some(Verdict::Fail),
+            (Outcome::Answer(_), None) => Some(Verdict::Unknown),
+            _ => None,
+        }
+    }
+
+    /// Whether this result should make the run fail.
+    pub fn is_failure(&self) -> bool {
+        matches!(self.outcome, Outcome::Error(_) | Outcome::Timeout(_))
+            || self.verdict() == Some(Verdict::Fail)
+    }
+
+    const fn status(&self) -> &'static str {
+        match self.outcome {
+            Outcome::Answer(_) => "ok",
+            Outcome::Error(_) => "error",
+            Outcome::Timeout(_) => "timeout",
+            Outcome::NotImplemented => "not_implemented",
+        }
+    }
+
+    fn render_pretty(&self) -> String {
+        let mut line = match self.kind {
+            InputKind::Sample => format!("Day {}, {} (sample): ", self.day, self.part),
+            InputKind::Real => format!("Day {}, {}: ", self.day, self.part),
+        };
+
+        match &self.outcome {
+            Outcome::Answer(answer) => line.push_str(answer),
+            Outcome::Error(error) => write!(line, "error: {error}").unwrap(),
+            Outcome::Timeout(budget) => write!(line, "TIMEOUT after {budget:?}").unwrap(),
+            Outcome::NotImplemented => line.push_str("not implemented"),
+        }
+
+        match self.timing {
+            Some(PhaseStats { parse, solve }) if solve.runs > 1 => write!(
+                line,
+                " (parse {}; solve {}; {} runs)",
+                pretty_stats(parse),
+                pretty_stats(solve),
+                solve.runs
+            )
+            .unwrap(),
+            Some(PhaseStats { parse, solve }) => {
+                write!(line, " (parse {:?}, solve {:?})", parse.mean, solve.mean).unwrap();
+            }
+            None => (),
+        }
+
+        if let Some(usage) = self.memory {
+            write!(
+                line,
+                " (peak {}, {} allocations)",
+                memory::format_bytes(usage.peak_bytes),
+                usage.allocations
+            )
+            .unwrap();
+        }
+
+        match (self.verdict(), &self.expected) {
+            (Some(Verdict::Pass), _) => line.push_str(" PASS"),
+            (Some(Verdict::Fail), Some(expected)) => {
+                write!(line, " FAIL (expected {expected})").unwrap();
+            }
+            (Some(Verdict::Unknown), _) => line.push_str(" UNKNOWN"),
+            _ => (),
+        }
+
+        line
+    }
+
+    fn render_json(&self) -> String {
+        let optional = |value: Option<&str>| value.map_or("null".to_string(), json_string);
+
+        format!(
+            "{{\"day\":{},\"part\":\"{}\",\"input\":\"{}\",\"status\":\"{}\",\"answer\":{},\"error\":{},\"expected\":{},\"verdict\":{},\"runs\":{},\"parse\":{},\"solve\":{},\"memory\":{}}}",
+            self.day,
+            self.part,
+            self.kind,
+            self.status(),
+            optional(self.answer()),
+            optional(self.error()),
+            optional(self.expected.as_deref()),
+            self.verdict()
+                .map_or("null".to_string(), |verdict| format!("\"{verdict}\"")),
+            self.timing.map_or(0, |stats| stats.solve.runs),
+            json_stats(self.timing.map(|stats| stats.parse)),
+            json_stats(self.timing.map(|stats| stats.solve)),
+            json_usage(self.memory),
+        )
+    }
+
+    fn render_csv(&self) -> String {
+        [
+            self.day.to_string(),
+            self.part.to_string(),
+            self.kind.to_string(),
+            self.status().to_string(),
+            csv_field(self.answer().unwrap_or_default()),
+            csv_field(self.error().unwrap_or_default()),
+            csv_field(self.expected.as_deref().unwrap_or_default()),
+            self.verdict()
+                .map(|verdict| verdict.to_string())
+                .unwrap_or_default(),
+            self.timing.map_or(0, |stats| stats.solve.runs).to_string(),
+            csv_stats(self.timing.map(|stats| stats.parse)),
+            csv_stats(self.timing.map(|stats| stats.solve)),
+            csv_usage(self.memory),
+        ]
+        .join(",")
+    }
+}
+
+const CSV_HEADER: &str = "day,part,input,status,answer,error,expected,verdict,runs,\
+    parse_min_ns,parse_median_ns,parse_mean_ns,parse_stddev_ns,\
+    solve_min_ns,solve_median_ns,solve_mean_ns,solve_stddev_ns,peak_bytes,allocations";
+
+/// All results of a run of the command line interface, in the order they were produced.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    pub records: Vec<Record>,
+}
+
+impl Report {
+    /// Whether no result failed. See [`Record::is_failure`].
+    pub fn is_success(&self) -> bool {
+        !self.records.iter().any(Record::is_failure)
+    }
+
+    /// Whether every answer is known to be correct.
+    pub fn is_verified(&self) -> bool {
+        self.is_success()
+            && self
+                .records
+                .iter()
+                .all(|record| record.verdict() != Some(Verdict::Unknown))
+    }
+
+    /// Renders the full report in the given format.
+    pub fn render(&self, format: Format) -> String {
+        let mut printer = Printer::new(format);
+        let mut output = String::new();
+        output.extend(printer.begin());
+        for record in &self.records {
+            output.push_str(&printer.record(record));
+        }
+        output.extend(printer.end());
+        output
+    }
+}
+
+/// Renders records one at a time, so results can be printed while the remaining days are still
+/// running.
+#[derive(Debug)]
+pub struct Printer {
+    format: Format,
+    count: usize,
+}
+
+impl Printer {
+    pub const fn new(format: Format) -> Self {
+        Self { format, count: 0 }
+    }
+
+    /// The text that precedes the first record, if any.
+    pub fn begin(&self) -> Option<String> {
+        match self.format {
+            Format::Pretty => None,
+            Format::Json => Some("[\n".to_string()),
+            Format::Csv => Some(format!("{CSV_HEADER}\n")),
+        }
+    }
+
+    /// The text for a single record, including any separator from the previous record.
+    pub fn record(&mut self, record: &Record) -> String {
+        let separator = if self.format == Format::Json && self.count > 0 {
+            ",\n"
+        } else {
+            ""
+        };
+        self.count += 1;
+
+        match self.format {
+            Format::Pretty => format!("{}\n", record.render_pretty()),
+            Format::Json => format!("{separator}  {}", record.render_json()),
+            Format::Csv => format!("{}\n", record.render_csv()),
+        }
+    }
+
+    /// The text that follows the last record, if any.
+    pub fn end(&self) -> Option<String> {
+        match self.format {
+            Format::Pretty | Format::Csv => None,
+            Format::Json if self.count == 0 => Some("]\n".to_string()),
+            Format::Json => Some("\n]\n".to_string()),
+        }
+    }
+}
+
+fn pretty_stats(stats: Stats) -> String {
+    format!(
+        "{:?} ± {:?}, median {:?}, min {:?}",
+        stats.mean, stats.stddev, stats.median, stats.min
+    )
+}
+
+/// Renders the statistics of a phase as a JSON object with values in nanoseconds.
+fn json_stats(stats: Option<Stats>) -> String {
+    stats.map_or("null".to_string(), |stats| {
+        format!(
+            "{{\"min_ns\":{},\"median_ns\":{},\"mean_ns\":{},\"stddev_ns\":{}}}",
+            stats.min.as_nanos(),
+            stats.median.as_nanos(),
+            stats.mean.as_nanos(),
+            stats.stddev.as_nanos()
+        )
+    })
+}
+
+/// Renders the statistics of a phase as 4 CSV fields with values in nanoseconds.
+fn csv_stats(stats: Option<Stats>) -> String {
+    stats.map_or(",,,".to_string(), |stats| {
+        [stats.min, stats.median, stats.mean, stats.stddev]
+            .map(|duration| duration.as_nanos().to_string())
+            .join(",")
+    })
+}
+
+/// Renders the heap usage of a part as a JSON object.
+fn json_usage(usage: Option<Usage>) -> String {
+    usage.map_or("null".to_string(), |usage| {
+        format!(
+            "{{\"peak_bytes\":{},\"allocations\":{}}}",
+            usage.peak_bytes, usage.allocations
+        )
+    })
+}
+
+/// Renders the heap usage of a part as 2 CSV fields.
+fn csv_usage(usage: Option<Usage>) -> String {
+    usage.map_or(",".to_string(), |usage| {
+        format!("{},{}", usage.peak_bytes, usage.allocations)
+    })
+}
+
+fn json_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len() + 2);
+    escaped.push('"');
+    for char in value.chars() {
+        match char {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => write!(escaped, "\\u{:04x}", c as u32).unwrap(),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn micros(values: &[u64]) -> Vec<Duration> {
+        values.iter().copied().map(Duration::from_micros).collect()
+    }
+
+    fn report() -> Report {
+        let mut answer = Record::new(
+            1,
+            Part::Silver,
+            InputKind::Sample,
+            Outcome::Answer("24".into()),
+        );
+        answer.timing = Some(PhaseStats {
+            parse: Stats::from_samples(&[Duration::from_micros(5)]).unwrap(),
+            solve: Stats::from_samples(&[Duration::from_micros(15)]).unwrap(),
+        });
+        answer.memory = Some(Usage {
+            peak_bytes: 1536,
+            allocations: 3,
+        });
+
+        let mut error = Record::new(
+            11,
+            Part::Gold,
+            InputKind::Real,
+            Outcome::Error("parse error at line 1, column 2: expected \"x\", found `y`".into()),
+        );
+        error.expected = Some("123".into());
+
+        Report {
+            records: vec![answer, error],
+        }
+    }
+
+    #[test]
+    fn test_pretty() {
+        assert_eq!(
+            report().render(Format::Pretty),
+            "Day 1, silver (sample): 24 (parse 5µs, solve 15µs) (peak 1.5 KiB, 3 allocations) UNKNOWN\n\
+             Day 11, gold: error: parse error at line 1, column 2: expected \"x\", found `y` FAIL (expected 123)\n"
+        );
+    }
+
+    #[test]
+    fn test_pretty_repeated() {
+        let mut record = Record::new(6, Part::Gold, InputKind::Real, Outcome::Answer("19".into()));
+        record.expected = Some("19".into());
+        record.timing = Some(PhaseStats {
+            parse: Stats::from_samples(&micros(&[1, 1, 1])).unwrap(),
+            solve: Stats::from_samples(&micros(&[2, 4, 6])).unwrap(),
+        });
+
+        assert_eq!(
+            Report {
+                records: vec![record]
+            }
+            .render(Format::Pretty),
+            "Day 6, gold: 19 (parse 1µs ± 0ns, median 1µs, min 1µs; \
+             solve 4µs ± 2µs, median 4µs, min 2µs; 3 runs) PASS\n"
+        );
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!(
+            report().render(Format::Json),
+            "[\n  \
+             {\"day\":1,\"part\":\"silver\",\"input\":\"sample\",\"status\":\"ok\",\"answer\":\"24\",\"error\":null,\"expected\":null,\"verdict\":\"unknown\",\"runs\":1,\
+             \"parse\":{\"min_ns\":5000,\"median_ns\":5000,\"mean_ns\":5000,\"stddev_ns\":0},\
+             \"solve\":{\"min_ns\":15000,\"median_ns\":15000,\"mean_ns\":15000,\"stddev_ns\":0},\
+             \"memory\":{\"peak_bytes\":1536,\"allocations\":3}},\n  \
+             {\"day\":11,\"part\":\"gold\",\"input\":\"real\",\"status\":\"error\",\"answer\":null,\"error\":\"parse error at line 1, column 2: expected \\\"x\\\", found `y`\",\"expected\":\"123\",\"verdict\":\"fail\",\"runs\":0,\"parse\":null,\"solve\":null,\"memory\":null}\n\
+             ]\n"
+        );
+        assert_eq!(Report::default().render(Format::Json), "[\n]\n");
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(
+            report().render(Format::Csv),
+            "day,part,input,status,answer,error,expected,verdict,runs,\
+             parse_min_ns,parse_median_ns,parse_mean_ns,parse_stddev_ns,\
+             solve_min_ns,solve_median_ns,solve_mean_ns,solve_stddev_ns,peak_bytes,allocations\n\
+             1,silver,sample,ok,24,,,unknown,1,5000,5000,5000,0,15000,15000,15000,0,1536,3\n\
+             11,gold,real,error,,\"parse error at line 1, column 2: expected \"\"x\"\", found `y`\",123,fail,0,,,,,,,,,,\n"
+        );
+    }
+
+    #[test]
+    fn test_timeout() {
+        let mut record = Record::new(
+            19,
+            Part::Gold,
+            InputKind::Real,
+            Outcome::Timeout(Duration::from_secs(5)),
+        );
+        record.expected = Some("10962".into());
+        assert_eq!(record.verdict(), None);
+        assert!(record.is_failure());
+
+        let report = Report {
+            records: vec![record],
+        };
+        assert_eq!(
+            report.render(Format::Pretty),
+            "Day 19, gold: TIMEOUT after 5s\n"
+        );
+        assert!(report
+            .render(Format::Csv)
+            .ends_with("\n19,gold,real,timeout,,,10962,,0,,,,,,,,,,\n"));
+    }
+
+    #[test]
+    fn test_is_success() {
+        let mut report = report();
+        assert!(!report.is_success());
+        report.records.pop();
+        assert!(report.is_success());
+        assert!(!report.is_verified());
+        report.records[0].expected = Some("24".into());
+        assert_eq!(report.records[0].verdict(), Some(Verdict::Pass));
+        assert!(report.is_verified());
+    }
+}
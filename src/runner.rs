@@ -0,0 +1,394 @@
+use std::{
+    borrow::Cow,
+    collections::BTreeMap,
+    sync::mpsc::{self, RecvTimeoutError},
+    time::Duration,
+};
+
+use rayon::prelude::*;
+
+use crate::{
+    answers::Answers,
+    cancel,
+    memory::{self, Usage},
+    registry::DayEntry,
+    report::{Format, Outcome, Printer, Record, Report},
+    timing::{self, PhaseStats, Repetitions},
+};
+
+use super::*;
+
+/// The days, parts and inputs that should be run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    pub days: Vec<u32>,
+    pub parts: Vec<Part>,
+    pub kinds: Vec<InputKind>,
+}
+
+impl Selection {
+    /// Selects both parts of every day, for both inputs.
+    pub fn all() -> Self {
+        Self {
+            days: registry::days().iter().map(|entry| entry.day).collect(),
+            parts: Part::ALL.to_vec(),
+            kinds: InputKind::ALL.to_vec(),
+        }
+    }
+
+    /// The selected combinations of day, part and input, in day order. Days that are not in the
+    /// registry are skipped.
+    fn jobs(&self) -> Vec<(&'static DayEntry, Part, InputKind)> {
+        let mut jobs = vec![];
+        for entry in self.days.iter().filter_map(|&day| registry::get(day)) {
+            for &part in &self.parts {
+                for &kind in &self.kinds {
+                    jobs.push((entry, part, kind));
+                }
+            }
+        }
+        jobs
+    }
+
+    /// Calls `fun` for every selected combination of day, part and input, and prints the
+    /// resulting records in `format` in day order. Parts that are not implemented and inputs or
+    /// parameters that could not be loaded are recorded without calling `fun`. Every record is
+    /// compared against `answers`.
+    ///
+    /// In parallel, the combinations run concurrently on the rayon pool. Records are still printed
+    /// in day order, as soon as all records before them are done, followed by the total time and
+    /// the CPU time that all threads used in that time.
+    fn run_each(
+        &self,
+        inputs: &InputProvider,
+        answers: &Answers,
+        format: Format,
+        execution: Execution,
+        fun: impl Fn(&mut Record, &'static DayEntry, &str, &Params) + Sync,
+    ) -> Report {
+        let run_job = |&(entry, part, kind): &(&'static DayEntry, Part, InputKind)| {
+            let mut record = Record::new(entry.day, part, kind, Outcome::NotImplemented);
+            if entry.part(part).is_some() {
+                record.expected = answers.get(entry.day, part, kind).map(str::to_string);
+                match load(entry, kind, inputs) {
+                    Ok((input, params)) => fun(&mut record, entry, &input, &params),
+                    Err(e) => record.outcome = Outcome::Error(e),
+                }
+            }
+            record
+        };
+
+        let mut output = Output::new(format);
+        let jobs = self.jobs();
+
+        match execution {
+            Execution::Sequential => {
+                for job in &jobs {
+                    output.push(run_job(job));
+                }
+            }
+            Execution::Parallel => {
+                let cpu_start = timing::process_cpu_time();
+                let ((), wall_time) = run_timed(|| {
+                    let (sender, receiver) = mpsc::channel();
+                    std::thread::scope(|scope| {
+                        scope.spawn(|| {
+                            jobs.par_iter()
+                                .enumerate()
+                                .for_each_with(sender, |sender, (index, job)| {
+                                    sender.send((index, run_job(job))).unwrap()
+                                });
+                        });
+
+                        // print the records in order, keeping the ones that finished early
+                        let mut pending = BTreeMap::new();
+                        for (index, record) in receiver {
+                            pending.insert(index, record);
+                            while let Some(record) = pending.remove(&output.report.records.len()) {
+                                output.push(record);
+                            }
+                        }
+                    });
+                });
+
+                // parts that use rayon themselves or run on threads of their own are included, and
+                // so are parts that ran out of their budget but keep running in the background
+                let cpu_time = cpu_start
+                    .zip(timing::process_cpu_time())
+                    .map(|(start, end)| end.saturating_sub(start));
+                let summary = match cpu_time {
+                    Some(cpu_time) => format!(
+                        "Ran {} parts in {wall_time:?}, using {cpu_time:?} of CPU time",
+                        jobs.len()
+                    ),
+                    None => format!("Ran {} parts in {wall_time:?}", jobs.len()),
+                };
+                match format {
+                    Format::Pretty => println!("{summary}"),
+                    // keep the output machine readable
+                    Format::Json | Format::Csv => eprintln!("{summary}"),
+                }
+            }
+        }
+
+        output.finish()
+    }
+}
+
+/// How the selected days and parts are scheduled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Execution {
+    /// One part at a time, in day order.
+    #[default]
+    Sequential,
+    /// All parts at once on the rayon pool. Parts slow each other down, so timings are less
+    /// reliable and heap usage is not measured.
+    Parallel,
+}
+
+/// Prints records as they are added and collects them into a [`Report`].
+struct Output {
+    printer: Printer,
+    report: Report,
+}
+
+impl Output {
+    fn new(format: Format) -> Self {
+        let printer = Printer::new(format);
+        if let Some(begin) = printer.begin() {
+            print!("{begin}");
+        }
+
+        Self {
+            printer,
+            report: Report::default(),
+        }
+    }
+
+    fn push(&mut self, record: Record) {
+        print!("{}", self.printer.record(&record));
+        self.report.records.push(record);
+    }
+
+    fn finish(self) -> Report {
+        if let Some(end) = self.printer.end() {
+            print!("{end}");
+        }
+        self.report
+    }
+}
+
+/// Runs the selection and prints the answers in `format`, together with how they compare to
+/// `answers` and how long they took. Parsing and solving are timed separately, both according to
+/// `repetitions`. When memory tracking is [enabled](memory::ENABLED), every part is run once more
+/// to measure its heap usage. Failures are reported and do not stop the remaining days.
+///
+/// With a `budget`, the parts run on a thread pool that is shared by the whole run, and a part is
+/// reported as a timeout if all of its runs together take longer than that. See
+/// [`run_with_budget`].
+pub fn run(
+    selection: &Selection,
+    inputs: &InputProvider,
+    answers: &Answers,
+    repetitions: Repetitions,
+    format: Format,
+    execution: Execution,
+    budget: Option<Duration>,
+) -> Report {
+    let measure_memory = memory::ENABLED && execution == Execution::Sequential;
+    let pool = budget.map(|_| {
+        rayon::ThreadPoolBuilder::new()
+            // the sender is dropped, which is reported as an error
+            .panic_handler(|_| ())
+            .build()
+            .expect("should be able to start the threads for the parts")
+    });
+    selection.run_each(
+        inputs,
+        answers,
+        format,
+        execution,
+        |record, entry, input, params| {
+            let part = record.part;
+            let result = match budget {
+                None => Ok(run_part(
+                    entry,
+                    part,
+                    input,
+                    params,
+                    repetitions,
+                    measure_memory,
+                )),
+                Some(budget) => {
+                    let pool = pool.as_ref().expect("a budget should come with a pool");
+                    let (input, params) = (input.to_string(), params.clone());
+                    run_with_budget(pool, budget, move || {
+                        run_part(entry, part, &input, &params, repetitions, measure_memory)
+                    })
+                }
+            };
+
+            match result {
+                Ok(Ok((answer, stats, usage))) => {
+                    record.outcome = Outcome::Answer(answer);
+                    record.timing = Some(stats);
+                    record.memory = usage;
+                }
+                Ok(Err(e)) => record.outcome = Outcome::Error(e.to_string()),
+                Err(RecvTimeoutError::Timeout) => {
+                    record.outcome = Outcome::Timeout(budget.unwrap_or_default());
+                }
+                Err(RecvTimeoutError::Disconnected) => {
+                    record.outcome = Outcome::Error("the part stopped without a result".into());
+                }
+            }
+
+            #[cfg(feature = "profile")]
+            if record.part == Part::Gold && record.kind == InputKind::Real {
+                for _ in 0..100 {
+                    _ = entry.solve(record.part, input, params);
+                }
+            }
+        },
+    )
+}
+
+/// Measures the phases of `part`, see [`measure_phases`], and then measures its heap usage if
+/// `measure_memory` is set.
+fn run_part(
+    entry: &DayEntry,
+    part: Part,
+    input: &str,
+    params: &Params,
+    repetitions: Repetitions,
+    measure_memory: bool,
+) -> Result<(String, PhaseStats, Option<Usage>), SolveError> {
+    let (answer, stats) = measure_phases(entry, part, input, params, repetitions)?;
+    let usage = if measure_memory {
+        memory::measure(|| entry.solve(part, input, params)).1
+    } else {
+        None
+    };
+
+    Ok((answer, stats, usage))
+}
+
+/// Runs `fun` as a job on `pool` and waits at most `budget` for it to finish, counting from when
+/// the job starts. The job gets a [token](cancel::Token) of its own, which is cancelled when the
+/// budget runs out so the part stops at its next [checkpoint](cancel::checkpoint), and this returns
+/// without waiting for it.
+///
+/// Using a pool apart from the global one means that parts that use rayon themselves still make
+/// progress while every thread of the global pool is waiting for a part in parallel mode. A part
+/// without checkpoints keeps a thread of `pool` busy until it finishes, even after it timed out, so
+/// the parts after it can wait for a thread. That wait does not count against their budget.
+fn run_with_budget<T: Send + 'static>(
+    pool: &rayon::ThreadPool,
+    budget: Duration,
+    fun: impl FnOnce() -> T + Send + 'static,
+) -> Result<T, RecvTimeoutError> {
+    let token = cancel::Token::new();
+    let job_token = token.clone();
+    let (started_sender, started) = mpsc::channel();
+    let (sender, receiver) = mpsc::channel();
+    pool.spawn(move || {
+        _ = started_sender.send(());
+        let result = cancel::with_token(Some(job_token), fun);
+        // the runner stops listening after a timeout
+        _ = sender.send(result);
+    });
+
+    started.recv().map_err(|_| RecvTimeoutError::Disconnected)?;
+    let result = receiver.recv_timeout(budget);
+    if result.is_err() {
+        token.cancel();
+    }
+    result
+}
+
+/// Measures parsing `input` on its own, then measures solving `part` for a single parsed input.
+/// Returns the answer of the last run.
+fn measure_phases(
+    entry: &DayEntry,
+    part: Part,
+    input: &str,
+    params: &Params,
+    repetitions: Repetitions,
+) -> Result<(String, PhaseStats), SolveError> {
+    let with_parsed = entry.with_parsed(part).expect("part should be implemented");
+
+    let ((), parse) = timing::measure(repetitions, || (entry.parse)(input, params))?;
+
+    let mut solved = None;
+    with_parsed(input, params, &mut |solve| {
+        solved = Some(timing::measure(repetitions, solve));
+    })?;
+    let (answer, solve) = solved.expect("input was parsed, so the part should be solved")?;
+
+    Ok((answer, PhaseStats { parse, solve }))
+}
+
+/// Loads the input of `kind` together with its parameters, and normalizes the input to the format
+/// the day expects. Errors are formatted for the report.
+fn load<'a>(
+    entry: &DayEntry,
+    kind: InputKind,
+    inputs: &'a InputProvider,
+) -> Result<(Cow<'a, str>, Params), String> {
+    let input = inputs
+        .load(entry.day, kind, entry.input(kind))
+        .map_err(|e| e.to_string())?;
+    let input = entry.format.apply(input);
+    let params = inputs
+        .load_params(entry.day, kind, entry.params(kind))
+        .map_err(|e| e.to_string())?;
+    let params = Params::parse(&params).map_err(|e| format!("invalid parameters: {e}"))?;
+
+    Ok((input, params))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_run_with_budget() {
+        // a single thread, so every job runs on the thread of the one before it
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .panic_handler(|_| ())
+            .build()
+            .unwrap();
+        let budget = Duration::from_secs(10);
+        assert_eq!(run_with_budget(&pool, budget, || 5), Ok(5));
+        assert_eq!(
+            run_with_budget(&pool, budget, || -> usize { panic!("oh no") }),
+            Err(RecvTimeoutError::Disconnected)
+        );
+
+        // a part that never finishes is stopped at its next checkpoint
+        let (sender, receiver) = mpsc::channel();
+        let result = run_with_budget(&pool, Duration::from_millis(50), move || {
+            let result = error::catch_panic(|| loop {
+                cancel::checkpoint();
+            });
+            sender.send(result).unwrap();
+        });
+        assert_eq!(result, Err(RecvTimeoutError::Timeout));
+        assert_eq!(receiver.recv(), Ok(Err(SolveError::Cancelled)));
+
+        // the next part gets a token of its own
+        let result = run_with_budget(&pool, budget, || error::catch_panic(cancel::checkpoint));
+        assert_eq!(result, Ok(Ok(())));
+
+        // a part without checkpoints keeps the thread after its timeout, but the part that waits
+        // for the thread only spends its budget once it runs
+        let slow = Duration::from_millis(500);
+        let result = run_with_budget(&pool, Duration::from_millis(50), move || {
+            std::thread::sleep(slow);
+        });
+        assert_eq!(result, Err(RecvTimeoutError::Timeout));
+        let result = run_with_budget(&pool, Duration::from_millis(200), || 5);
+        assert_eq!(result, Ok(5));
+    }
+}
@@ -0,0 +1,335 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, VecDeque},
+    hash::Hash,
+    ops::Add,
+};
+
+use ahash::AHashMap;
+
+use crate::cancel;
+
+/// The goal that a search reached, with the cost to reach it.
+///
+/// The nodes that were visited are kept so the path to the goal can be reconstructed with
+/// [`Found::path`].
+#[derive(Debug, Clone)]
+pub struct Found<N, C> {
+    pub goal: N,
+    pub cost: C,
+    /// The node each visited node was reached from. Start nodes have no parent.
+    parents: AHashMap<N, Option<N>>,
+}
+
+impl<N: Clone + Eq + Hash, C> Found<N, C> {
+    /// The path from the start node that was used to the goal, including both.
+    pub fn path(&self) -> Vec<N> {
+        let mut path = vec![self.goal.clone()];
+        while let Some(Some(parent)) = self.parents.get(path.last().unwrap()) {
+            path.push(parent.clone());
+        }
+
+        path.reverse();
+        path
+    }
+}
+
+/// Searches breadth-first from all `starts` at once until a node is found for which `is_goal`
+/// returns true. The cost is the number of steps from the closest start.
+pub fn bfs<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Found<N, usize>>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut parents = AHashMap::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if parents.insert(start.clone(), None).is_none() {
+            queue.push_back((start, 0));
+        }
+    }
+
+    while let Some((node, cost)) = queue.pop_front() {
+        cancel::checkpoint();
+        if is_goal(&node) {
+            return Some(Found {
+                goal: node,
+                cost,
+                parents,
+            });
+        }
+
+        for next in neighbours(&node) {
+            if !parents.contains_key(&next) {
+                parents.insert(next.clone(), Some(node.clone()));
+                queue.push_back((next, cost + 1));
+            }
+        }
+    }
+
+    None
+}
+
+/// Searches breadth-first from all `starts` and returns the number of steps to every node that can
+/// be reached.
+pub fn bfs_distances<N, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+) -> AHashMap<N, usize>
+where
+    N: Clone + Eq + Hash,
+    I: IntoIterator<Item = N>,
+{
+    let mut distances = AHashMap::new();
+    let mut queue = VecDeque::new();
+    for start in starts {
+        if distances.insert(start.clone(), 0).is_none() {
+            queue.push_back(start);
+        }
+    }
+
+    while let Some(node) = queue.pop_front() {
+        let cost = distances[&node] + 1;
+        for next in neighbours(&node) {
+            if !distances.contains_key(&next) {
+                distances.insert(next.clone(), cost);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    distances
+}
+
+/// Finds the cheapest way from any of the `starts` to a node for which `is_goal` returns true.
+/// `neighbours` returns the nodes that can be reached from a node, with the cost of that step.
+pub fn dijkstra<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    neighbours: impl FnMut(&N) -> I,
+    is_goal: impl FnMut(&N) -> bool,
+) -> Option<Found<N, C>>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (N, C)>,
+{
+    astar(starts, neighbours, |_| C::default(), is_goal)
+}
+
+/// Like [`dijkstra`], but explores the nodes that `heuristic` estimates to be closest to a goal
+/// first. The heuristic must never overestimate the remaining cost, or a more expensive path may be
+/// found.
+pub fn astar<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+    mut heuristic: impl FnMut(&N) -> C,
+    mut is_goal: impl FnMut(&N) -> bool,
+) -> Option<Found<N, C>>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (N, C)>,
+{
+    // the cheapest known cost of every node, and the node it was reached from
+    let mut best: AHashMap<N, (C, Option<N>)> = AHashMap::new();
+    let mut queue = BinaryHeap::new();
+    for start in starts {
+        best.insert(start.clone(), (C::default(), None));
+        queue.push(Entry {
+            priority: heuristic(&start),
+            cost: C::default(),
+            node: start,
+        });
+    }
+
+    while let Some(Entry { cost, node, .. }) = queue.pop() {
+        cancel::checkpoint();
+
+        // a cheaper way to this node was found after it was queued
+        if best[&node].0 < cost {
+            continue;
+        }
+
+        if is_goal(&node) {
+            let parents = best.into_iter().map(|(n, (_, p))| (n, p)).collect();
+            return Some(Found {
+                goal: node,
+                cost,
+                parents,
+            });
+        }
+
+        for (next, step) in neighbours(&node) {
+            let next_cost = cost + step;
+            if !matches!(best.get(&next), Some(&(c, _)) if c <= next_cost) {
+                best.insert(next.clone(), (next_cost, Some(node.clone())));
+                queue.push(Entry {
+                    priority: next_cost + heuristic(&next),
+                    cost: next_cost,
+                    node: next,
+                });
+            }
+        }
+    }
+
+    None
+}
+
+/// Finds the cheapest cost from any of the `starts` to every node that can be reached.
+pub fn dijkstra_distances<N, C, I>(
+    starts: impl IntoIterator<Item = N>,
+    mut neighbours: impl FnMut(&N) -> I,
+) -> AHashMap<N, C>
+where
+    N: Clone + Eq + Hash,
+    C: Copy + Ord + Add<Output = C> + Default,
+    I: IntoIterator<Item = (N, C)>,
+{
+    let mut distances = AHashMap::new();
+    let mut queue = BinaryHeap::new();
+    for start in starts {
+        distances.insert(start.clone(), C::default());
+        queue.push(Entry {
+            priority: C::default(),
+            cost: C::default(),
+            node: start,
+        });
+    }
+
+    while let Some(Entry { cost, node, .. }) = queue.pop() {
+        if distances[&node] < cost {
+            continue;
+        }
+
+        for (next, step) in neighbours(&node) {
+            let next_cost = cost + step;
+            if !matches!(distances.get(&next), Some(&c) if c <= next_cost) {
+                distances.insert(next.clone(), next_cost);
+                queue.push(Entry {
+                    priority: next_cost,
+                    cost: next_cost,
+                    node: next,
+                });
+            }
+        }
+    }
+
+    distances
+}
+
+/// A node in the priority queue. Entries are ordered so the lowest priority is popped first.
+struct Entry<N, C> {
+    priority: C,
+    cost: C,
+    node: N,
+}
+
+impl<N, C: Ord> PartialEq for Entry<N, C> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<N, C: Ord> Eq for Entry<N, C> {}
+
+impl<N, C: Ord> PartialOrd for Entry<N, C> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<N, C: Ord> Ord for Entry<N, C> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.priority.cmp(&self.priority)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 5x5 grid where `#` cannot be entered.
+    const MAZE: [&[u8; 5]; 5] = [b"...#.", b"##.##", b"#....", b"#.###", b"#...."];
+
+    fn maze_neighbours(&(x, y): &(usize, usize)) -> Vec<(usize, usize)> {
+        [(1, 0), (0, 1), (-1, 0), (0, -1)]
+            .into_iter()
+            .filter_map(|(dx, dy)| Some((x.checked_add_signed(dx)?, y.checked_add_signed(dy)?)))
+            .filter(|&(x, y)| x < 5 && y < 5 && MAZE[y][x] == b'.')
+            .collect()
+    }
+
+    /// A small weighted graph where the direct edge is more expensive than the detour.
+    fn weighted_neighbours(node: &char) -> Vec<(char, u32)> {
+        match node {
+            'a' => vec![('b', 1), ('d', 10)],
+            'b' => vec![('c', 2)],
+            'c' => vec![('d', 3)],
+            _ => vec![],
+        }
+    }
+
+    #[test]
+    fn test_bfs() {
+        let found = bfs([(0, 0)], maze_neighbours, |&n| n == (4, 4)).unwrap();
+        assert_eq!(found.cost, 10);
+        let path = found.path();
+        assert_eq!(path.len(), 11);
+        assert_eq!((path[0], path[10]), ((0, 0), (4, 4)));
+        assert!(path
+            .array_windows()
+            .all(|[a, b]| a.0.abs_diff(b.0) + a.1.abs_diff(b.1) == 1));
+
+        assert!(bfs([(0, 0)], maze_neighbours, |&n| n == (4, 0)).is_none());
+    }
+
+    #[test]
+    fn test_bfs_multiple_starts() {
+        let found = bfs([(0, 0), (4, 3)], maze_neighbours, |&n| n == (4, 4)).unwrap();
+        assert_eq!(found.cost, 1);
+        assert_eq!(found.path(), [(4, 3), (4, 4)]);
+
+        let found = bfs([(2, 2)], maze_neighbours, |&n| n == (2, 2)).unwrap();
+        assert_eq!((found.cost, found.path()), (0, vec![(2, 2)]));
+    }
+
+    #[test]
+    fn test_bfs_distances() {
+        let distances = bfs_distances([(0, 0)], maze_neighbours);
+        assert_eq!(distances[&(0, 0)], 0);
+        assert_eq!(distances[&(2, 1)], 3);
+        assert_eq!(distances[&(4, 2)], 6);
+        assert_eq!(distances[&(4, 4)], 10);
+        assert!(!distances.contains_key(&(4, 0)));
+    }
+
+    #[test]
+    fn test_dijkstra() {
+        let found = dijkstra(['a'], weighted_neighbours, |&n| n == 'd').unwrap();
+        assert_eq!(found.cost, 6);
+        assert_eq!(found.path(), ['a', 'b', 'c', 'd']);
+
+        let distances = dijkstra_distances(['a'], weighted_neighbours);
+        assert_eq!(distances[&'c'], 3);
+        assert_eq!(distances[&'d'], 6);
+        assert!(dijkstra(['d'], weighted_neighbours, |&n| n == 'a').is_none());
+    }
+
+    #[test]
+    fn test_astar_matches_bfs() {
+        let heuristic = |&(x, y): &(usize, usize)| (4 - x) + (4 - y);
+        let neighbours = |n: &(usize, usize)| maze_neighbours(n).into_iter().map(|n| (n, 1));
+        for start in [(0, 0), (2, 2), (4, 0), (0, 4)] {
+            let expected = bfs([start], maze_neighbours, |&n| n == (4, 4)).map(|f| f.cost);
+            let found = astar([start], neighbours, heuristic, |&n| n == (4, 4));
+            assert_eq!(found.as_ref().map(|f| f.cost), expected);
+            if let Some(found) = found {
+                assert_eq!(found.path().len(), found.cost + 1);
+            }
+        }
+    }
+}
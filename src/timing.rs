@@ -0,0 +1,184 @@
+use std::time::Duration;
+
+use super::*;
+
+/// How often a part is run when it is timed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Repetitions {
+    /// Runs that are not measured, to warm up caches and the branch predictor.
+    pub warmup: u32,
+    /// Runs that are measured. At least one run is always measured.
+    pub runs: u32,
+}
+
+impl Repetitions {
+    /// A single measured run without warm-up.
+    pub const ONCE: Repetitions = Repetitions { warmup: 0, runs: 1 };
+
+    pub const fn new(warmup: u32, runs: u32) -> Self {
+        Self { warmup, runs }
+    }
+}
+
+impl Default for Repetitions {
+    fn default() -> Self {
+        Self::ONCE
+    }
+}
+
+/// Statistics over the measured runs of a part.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stats {
+    pub runs: u32,
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    /// The sample standard deviation, which is zero for a single run.
+    pub stddev: Duration,
+}
+
+impl Stats {
+    /// Calculates the statistics of the given durations. Returns `None` if there are none.
+    pub fn from_samples(samples: &[Duration]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+
+        let mut sorted = samples.to_vec();
+        sorted.sort_unstable();
+
+        let len = sorted.len();
+        let median = if len % 2 == 0 {
+            (sorted[len / 2 - 1] + sorted[len / 2]) / 2
+        } else {
+            sorted[len / 2]
+        };
+
+        let total: Duration = sorted.iter().sum();
+        let mean = total / len as u32;
+
+        let stddev = if len > 1 {
+            let mean_nanos = mean.as_nanos() as f64;
+            let variance = sorted
+                .iter()
+                .map(|sample| (sample.as_nanos() as f64 - mean_nanos).powi(2))
+                .sum::<f64>()
+                / (len - 1) as f64;
+            Duration::from_nanos(variance.sqrt() as u64)
+        } else {
+            Duration::ZERO
+        };
+
+        Some(Self {
+            runs: len as u32,
+            min: sorted[0],
+            median,
+            mean,
+            stddev,
+        })
+    }
+}
+
+/// Statistics for the two phases of a part, which are measured separately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PhaseStats {
+    pub parse: Stats,
+    pub solve: Stats,
+}
+
+/// Calls `fun` according to `repetitions` and measures every run after the warm-up. Stops at the
+/// first error. Returns the output of the last run together with the statistics.
+pub fn measure<T, E>(
+    repetitions: Repetitions,
+    mut fun: impl FnMut() -> Result<T, E>,
+) -> Result<(T, Stats), E> {
+    for _ in 0..repetitions.warmup {
+        fun()?;
+    }
+
+    let runs = repetitions.runs.max(1);
+    let mut samples = Vec::with_capacity(runs as usize);
+    let mut output = None;
+    for _ in 0..runs {
+        let (result, time) = run_timed(&mut fun);
+        output = Some(result?);
+        samples.push(time);
+    }
+
+    let stats = Stats::from_samples(&samples).expect("at least one run is measured");
+    Ok((output.expect("at least one run is measured"), stats))
+}
+
+/// The CPU time that all threads of the process have used so far, or `None` on platforms where it
+/// cannot be measured.
+pub fn process_cpu_time() -> Option<Duration> {
+    #[cfg(unix)]
+    {
+        let mut time = libc::timespec {
+            tv_sec: 0,
+            tv_nsec: 0,
+        };
+        // SAFETY: `clock_gettime` only writes to `time`, which outlives the call
+        if unsafe { libc::clock_gettime(libc::CLOCK_PROCESS_CPUTIME_ID, &mut time) } == 0 {
+            return Some(Duration::new(time.tv_sec as u64, time.tv_nsec as u32));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn micros(values: &[u64]) -> Vec<Duration> {
+        values.iter().copied().map(Duration::from_micros).collect()
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::from_samples(&micros(&[4, 2, 8, 6])).unwrap();
+        assert_eq!(stats.runs, 4);
+        assert_eq!(stats.min, Duration::from_micros(2));
+        assert_eq!(stats.median, Duration::from_micros(5));
+        assert_eq!(stats.mean, Duration::from_micros(5));
+        // sqrt((1 + 9 + 9 + 1) / 3) = 2.582
+        assert_eq!(stats.stddev.as_nanos(), 2581);
+
+        let stats = Stats::from_samples(&micros(&[7, 1, 3])).unwrap();
+        assert_eq!(stats.median, Duration::from_micros(3));
+
+        assert!(Stats::from_samples(&[]).is_none());
+    }
+
+    #[test]
+    fn test_single_sample() {
+        let stats = Stats::from_samples(&micros(&[3])).unwrap();
+        assert_eq!(stats.min, stats.mean);
+        assert_eq!(stats.median, stats.mean);
+        assert_eq!(stats.stddev, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_measure() {
+        let mut calls = 0;
+        let (output, stats) = measure(Repetitions::new(2, 5), || {
+            calls += 1;
+            Ok::<_, ()>(calls)
+        })
+        .unwrap();
+        assert_eq!(output, 7);
+        assert_eq!(stats.runs, 5);
+
+        let mut calls = 0;
+        let result = measure(Repetitions::new(0, 5), || {
+            calls += 1;
+            if calls == 3 {
+                Err("failed")
+            } else {
+                Ok(())
+            }
+        });
+        assert_eq!(result, Err("failed"));
+        assert_eq!(calls, 3);
+    }
+}
@@ -1,33 +1,104 @@
 #![allow(unused)]
 
+use crate::SolveError;
+
 /// Parses an integer string
 ///
 /// This is faster than `str::parse` because it assumes the input consists of only digits.
 pub fn fast_parse_int(s: &str) -> usize {
-    debug_assert!(!s.is_empty());
-    debug_assert!(s.len() < usize::MAX.to_string().len()); // err on the side of caution
     debug_assert!(s.chars().all(|c| c.is_ascii_digit()));
 
-    s.bytes().fold(0, |a, c| a * 10 + (c & 0x0f) as usize)
+    fast_parse_int_from_bytes(s.as_bytes())
 }
 
-/// Parses an int string with a max length of 8
+/// Parses an int string
 ///
-/// This is faster than `str::parse` because it assumes the input consists of only digits and is up
-/// to 8 characters long.
+/// This is faster than `str::parse` because it assumes the input consists of only digits. Numbers
+/// of up to 16 digits are parsed 8 digits at a time, longer numbers fall back to
+/// [`parse_int_scalar`].
 pub fn fast_parse_int_from_bytes(s: &[u8]) -> usize {
     debug_assert!(!s.is_empty());
     debug_assert!(s.len() < usize::MAX.to_string().len()); // err on the side of caution
     debug_assert!(s.iter().all(u8::is_ascii_digit));
 
+    match s.len() {
+        // a couple of multiplications are cheaper than filling a word for short numbers
+        0..=3 => parse_int_scalar(s),
+        4..=8 => swar_parse_8(pad_digits(s)) as usize,
+        9..=16 => {
+            let (high, low) = s.split_at(s.len() - 8);
+            let low = swar_parse_8(low.try_into().unwrap());
+            (swar_parse_8(pad_digits(high)) * 100_000_000 + low) as usize
+        }
+        _ => parse_int_scalar(s),
+    }
+}
+
+/// Parses an int string one digit at a time. This is the reference implementation of
+/// [`fast_parse_int_from_bytes`], and is used for numbers that are too short or too long to parse
+/// in one word.
+pub fn parse_int_scalar(s: &[u8]) -> usize {
     s.iter().fold(0, |a, c| a * 10 + (c & 0x0f) as usize)
 }
 
+/// Left-pads up to 8 digits with zeroes.
+#[inline]
+fn pad_digits(s: &[u8]) -> [u8; 8] {
+    let mut digits = [b'0'; 8];
+    digits[8 - s.len()..].copy_from_slice(s);
+    digits
+}
+
+/// Parses exactly 8 digits using SWAR (SIMD within a register). Adjacent digits are combined into
+/// pairs, then into groups of 4, then into the full number, each step with a single multiplication
+/// that works on all lanes at once.
+#[inline]
+fn swar_parse_8(digits: [u8; 8]) -> u64 {
+    // the first digit is the lowest byte, so it ends up being multiplied the most
+    let value = u64::from_le_bytes(digits) & 0x0f0f_0f0f_0f0f_0f0f;
+    let value = (value * 10 + (value >> 8)) & 0x00ff_00ff_00ff_00ff;
+    let value = (value * 100 + (value >> 16)) & 0x0000_ffff_0000_ffff;
+    (value * 10000 + (value >> 32)) & 0xffff_ffff
+}
+
+/// Finds the index of the first occurrence of `needle`.
+///
+/// The haystack is searched 8 bytes at a time with SWAR. The remaining bytes are searched with
+/// [`find_byte_scalar`].
+#[inline]
+pub fn find_byte(haystack: &[u8], needle: u8) -> Option<usize> {
+    const LOW_BITS: u64 = 0x0101_0101_0101_0101;
+    const HIGH_BITS: u64 = 0x8080_8080_8080_8080;
+    let pattern = LOW_BITS * needle as u64;
+
+    let mut chunks = haystack.chunks_exact(8);
+    let mut offset = 0;
+    for chunk in &mut chunks {
+        // bytes that match the needle become 0. Subtracting 1 from each byte sets the high bit of
+        // zero bytes, and only bytes after the first zero byte can be false positives.
+        let word = u64::from_le_bytes(chunk.try_into().unwrap()) ^ pattern;
+        let zero_bytes = word.wrapping_sub(LOW_BITS) & !word & HIGH_BITS;
+        if zero_bytes != 0 {
+            return Some(offset + (zero_bytes.trailing_zeros() / 8) as usize);
+        }
+        offset += 8;
+    }
+
+    find_byte_scalar(chunks.remainder(), needle).map(|index| offset + index)
+}
+
+/// Finds the index of the first occurrence of `needle` one byte at a time. This is the reference
+/// implementation of [`find_byte`].
+#[inline]
+pub fn find_byte_scalar(haystack: &[u8], needle: u8) -> Option<usize> {
+    haystack.iter().position(|b| *b == needle)
+}
+
 /// Equivalent to `str`'s `split_once` function, but for byte slices.
 #[inline]
 pub fn split_once(haystack: &[u8], delimiter: u8) -> Option<(&[u8], &[u8])> {
-    let start = haystack.iter().position(|b| *b == delimiter)?;
-    // SAFETY: `position` is known to return valid indices.
+    let start = find_byte(haystack, delimiter)?;
+    // SAFETY: `find_byte` is known to return valid indices.
     unsafe {
         Some((
             haystack.get_unchecked(..start),
@@ -40,13 +111,8 @@ pub fn split_once(haystack: &[u8], delimiter: u8) -> Option<(&[u8], &[u8])> {
 /// the end of the string.
 #[inline]
 pub fn split_once_2(haystack: &[u8], delimiter: u8) -> (&[u8], &[u8]) {
-    // TODO: position instead?
-    // haystack.split_at(haystack.iter().take_while(|&&b| b != delimiter).count())
-    let start = haystack
-        .iter()
-        .position(|b| *b == delimiter)
-        .unwrap_or(haystack.len());
-    // SAFETY: `position` is known to return valid indices. `haystack.len()` would return an empty slice.
+    let start = find_byte(haystack, delimiter).unwrap_or(haystack.len());
+    // SAFETY: `find_byte` is known to return valid indices. `haystack.len()` would return an empty slice.
     unsafe {
         (
             haystack.get_unchecked(..start),
@@ -55,6 +121,212 @@ pub fn split_once_2(haystack: &[u8], delimiter: u8) -> (&[u8], &[u8]) {
     }
 }
 
+/// Parses a signed integer string, which may start with a `-`.
+///
+/// Like [`fast_parse_int_from_bytes`], this assumes the rest of the input consists of only digits.
+pub fn fast_parse_signed_from_bytes(s: &[u8]) -> isize {
+    match s.split_first() {
+        Some((b'-', digits)) => -(fast_parse_int_from_bytes(digits) as isize),
+        _ => fast_parse_int_from_bytes(s) as isize,
+    }
+}
+
+/// Why one of the checked integer parsers rejected its input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IntErrorKind {
+    Empty,
+    InvalidDigit,
+    Overflow,
+}
+
+/// An error from [`checked_parse_int`] or [`checked_parse_signed`], pointing at the offending byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IntError {
+    /// The byte offset in the parsed slice.
+    pub offset: usize,
+    pub kind: IntErrorKind,
+}
+
+impl IntError {
+    pub const fn message(self) -> &'static str {
+        match self.kind {
+            IntErrorKind::Empty => "expected a number",
+            IntErrorKind::InvalidDigit => "expected a digit",
+            IntErrorKind::Overflow => "number is too large",
+        }
+    }
+
+    /// Converts this into a [`SolveError`] for `number`, which must be a subslice of `input`.
+    pub fn at(self, input: impl AsRef<[u8]>, number: impl AsRef<[u8]>) -> SolveError {
+        let number = number.as_ref();
+        SolveError::at(
+            input,
+            &number[self.offset.min(number.len())..],
+            self.message(),
+        )
+    }
+}
+
+/// Parses an unsigned integer string, like [`fast_parse_int_from_bytes`], but rejects empty
+/// input, anything that is not a digit and numbers that do not fit in a `usize`.
+pub fn checked_parse_int(s: &[u8]) -> Result<usize, IntError> {
+    let error = |offset, kind| IntError { offset, kind };
+    if s.is_empty() {
+        return Err(error(0, IntErrorKind::Empty));
+    }
+
+    s.iter().enumerate().try_fold(0usize, |a, (i, &c)| {
+        if !c.is_ascii_digit() {
+            return Err(error(i, IntErrorKind::InvalidDigit));
+        }
+        a.checked_mul(10)
+            .and_then(|a| a.checked_add((c & 0x0f) as usize))
+            .ok_or(error(i, IntErrorKind::Overflow))
+    })
+}
+
+/// The signed counterpart of [`checked_parse_int`], which accepts a leading `-`.
+pub fn checked_parse_signed(s: &[u8]) -> Result<isize, IntError> {
+    let (negative, digits) = match s.split_first() {
+        Some((b'-', digits)) => (true, digits),
+        _ => (false, s),
+    };
+    let offset = s.len() - digits.len();
+    let magnitude = checked_parse_int(digits).map_err(|e| IntError {
+        offset: e.offset + offset,
+        ..e
+    })?;
+
+    let value = if negative {
+        0isize.checked_sub_unsigned(magnitude)
+    } else {
+        isize::try_from(magnitude).ok()
+    };
+    value.ok_or(IntError {
+        offset,
+        kind: IntErrorKind::Overflow,
+    })
+}
+
+/// Reads through a byte input from front to back. Every method that can fail returns a
+/// [`SolveError`] pointing at the current position, so parsers get line and column information for
+/// free.
+#[derive(Debug, Clone, Copy)]
+pub struct Cursor<'a> {
+    input: &'a [u8],
+    rest: &'a [u8],
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(input: &'a (impl AsRef<[u8]> + ?Sized)) -> Self {
+        let input = input.as_ref();
+        Self { input, rest: input }
+    }
+
+    /// The part of the input that has not been read yet.
+    pub const fn rest(&self) -> &'a [u8] {
+        self.rest
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.rest.is_empty()
+    }
+
+    /// The byte offset of the current position in the input.
+    pub const fn offset(&self) -> usize {
+        self.input.len() - self.rest.len()
+    }
+
+    pub fn peek(&self) -> Option<u8> {
+        self.rest.first().copied()
+    }
+
+    /// Creates a parse error at the current position.
+    pub fn error(&self, message: impl Into<String>) -> SolveError {
+        SolveError::at(self.input, self.rest, message)
+    }
+
+    /// Skips `literal` if the input continues with it, and returns whether it did.
+    pub fn eat(&mut self, literal: &[u8]) -> bool {
+        match self.rest.strip_prefix(literal) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Skips `literal`, or fails if the input does not continue with it.
+    pub fn expect(&mut self, literal: &[u8]) -> Result<(), SolveError> {
+        if self.eat(literal) {
+            Ok(())
+        } else {
+            Err(self.error(format!("expected `{}`", literal.escape_ascii())))
+        }
+    }
+
+    /// Fails unless the whole input has been read.
+    pub fn expect_end(&self) -> Result<(), SolveError> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(self.error("expected the end of the input"))
+        }
+    }
+
+    /// Reads up to the next `delimiter` and skips it, like [`split_once`]. Fails if there is no
+    /// `delimiter` left.
+    pub fn until(&mut self, delimiter: u8) -> Result<&'a [u8], SolveError> {
+        let Some((before, rest)) = split_once(self.rest, delimiter) else {
+            let end = Self {
+                rest: &self.rest[self.rest.len()..],
+                ..*self
+            };
+            return Err(end.error(format!("expected `{}`", delimiter.escape_ascii())));
+        };
+        self.rest = rest;
+        Ok(before)
+    }
+
+    /// Reads up to the next `delimiter` or the end of the input, like [`split_once_2`], and skips
+    /// the delimiter if there is one.
+    pub fn until_or_end(&mut self, delimiter: u8) -> &'a [u8] {
+        let (before, rest) = split_once_2(self.rest, delimiter);
+        self.rest = rest.get(1..).unwrap_or(rest);
+        before
+    }
+
+    /// Reads the digits at the current position as an unsigned number.
+    pub fn uint(&mut self) -> Result<usize, SolveError> {
+        let len = self.digits_len(0);
+        let digits = &self.rest[..len];
+        let value = checked_parse_int(digits).map_err(|e| e.at(self.input, digits))?;
+        self.rest = &self.rest[len..];
+        Ok(value)
+    }
+
+    /// Reads an optional `-` followed by digits at the current position as a signed number.
+    pub fn int(&mut self) -> Result<isize, SolveError> {
+        let sign = usize::from(self.peek() == Some(b'-'));
+        let len = self.digits_len(sign);
+        let number = &self.rest[..len];
+        let value = checked_parse_signed(number).map_err(|e| e.at(self.input, number))?;
+        self.rest = &self.rest[len..];
+        Ok(value)
+    }
+
+    /// The length of the run of digits that starts `skip` bytes from the current position, plus
+    /// `skip`.
+    fn digits_len(&self, skip: usize) -> usize {
+        let digits = self.rest[skip.min(self.rest.len())..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count();
+        skip + digits
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -79,6 +351,176 @@ mod tests {
         assert_eq!(fast_parse_int_from_bytes(b"12345678"), 12345678);
     }
 
+    #[test]
+    fn test_fast_parse_signed_from_bytes() {
+        assert_eq!(fast_parse_signed_from_bytes(b"0"), 0);
+        assert_eq!(fast_parse_signed_from_bytes(b"1234"), 1234);
+        assert_eq!(fast_parse_signed_from_bytes(b"-1234"), -1234);
+        assert_eq!(fast_parse_signed_from_bytes(b"-0"), 0);
+    }
+
+    #[test]
+    fn test_checked_parse_int() {
+        assert_eq!(checked_parse_int(b"0"), Ok(0));
+        assert_eq!(checked_parse_int(b"0001"), Ok(1));
+        assert_eq!(checked_parse_int(b"12345678"), Ok(12345678));
+        assert_eq!(
+            checked_parse_int(usize::MAX.to_string().as_bytes()),
+            Ok(usize::MAX)
+        );
+
+        let error = |offset, kind| Err(IntError { offset, kind });
+        assert_eq!(checked_parse_int(b""), error(0, IntErrorKind::Empty));
+        assert_eq!(
+            checked_parse_int(b"12a4"),
+            error(2, IntErrorKind::InvalidDigit)
+        );
+        assert_eq!(
+            checked_parse_int(b"-1"),
+            error(0, IntErrorKind::InvalidDigit)
+        );
+        assert_eq!(
+            checked_parse_int(b"99999999999999999999"),
+            error(19, IntErrorKind::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_checked_parse_signed() {
+        assert_eq!(checked_parse_signed(b"12"), Ok(12));
+        assert_eq!(checked_parse_signed(b"-12"), Ok(-12));
+        assert_eq!(
+            checked_parse_signed(isize::MIN.to_string().as_bytes()),
+            Ok(isize::MIN)
+        );
+        assert_eq!(
+            checked_parse_signed(isize::MAX.to_string().as_bytes()),
+            Ok(isize::MAX)
+        );
+
+        let error = |offset, kind| Err(IntError { offset, kind });
+        assert_eq!(checked_parse_signed(b"-"), error(1, IntErrorKind::Empty));
+        assert_eq!(
+            checked_parse_signed(b"--1"),
+            error(1, IntErrorKind::InvalidDigit)
+        );
+        assert_eq!(
+            checked_parse_signed(b"-1x"),
+            error(2, IntErrorKind::InvalidDigit)
+        );
+        assert_eq!(
+            checked_parse_signed(b"9223372036854775808"),
+            error(0, IntErrorKind::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_int_error_at() {
+        let input = b"1\n2x";
+        let error = checked_parse_int(&input[2..]).unwrap_err();
+        assert_eq!(
+            error.at(input, &input[2..]),
+            SolveError::Parse {
+                line: 2,
+                column: 2,
+                message: "expected a digit".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_cursor() {
+        let mut cursor = Cursor::new("addx -12\nnoop\nmove 3 from 1");
+        assert!(!cursor.eat(b"noop"));
+        cursor.expect(b"addx ").unwrap();
+        assert_eq!(cursor.int(), Ok(-12));
+        cursor.expect(b"\n").unwrap();
+        assert_eq!(cursor.until(b'\n'), Ok(b"noop".as_ref()));
+        assert_eq!(cursor.until_or_end(b' '), b"move");
+        assert_eq!(cursor.uint(), Ok(3));
+        assert_eq!(cursor.offset(), 20);
+        assert_eq!(cursor.peek(), Some(b' '));
+        assert_eq!(cursor.until_or_end(b'\n'), b" from 1");
+        assert!(cursor.is_empty());
+        cursor.expect_end().unwrap();
+    }
+
+    #[test]
+    fn test_cursor_errors() {
+        let error = |line, column, message: &str| SolveError::Parse {
+            line,
+            column,
+            message: message.to_string(),
+        };
+
+        let mut cursor = Cursor::new("ab\ncd");
+        assert_eq!(cursor.expect(b"ax"), Err(error(1, 1, "expected `ax`")));
+        assert_eq!(cursor.uint(), Err(error(1, 1, "expected a number")));
+        assert_eq!(cursor.until(b'\n'), Ok(b"ab".as_ref()));
+        assert_eq!(cursor.until(b','), Err(error(2, 3, "expected `,`")));
+        assert_eq!(
+            cursor.expect_end(),
+            Err(error(2, 1, "expected the end of the input"))
+        );
+
+        let mut cursor = Cursor::new("x -\n-5");
+        cursor.expect(b"x ").unwrap();
+        assert_eq!(cursor.int(), Err(error(1, 4, "expected a number")));
+        assert_eq!(cursor.offset(), 2);
+    }
+
+    /// A small deterministic generator for the differential tests.
+    fn pseudo_random(seed: &mut u64) -> u64 {
+        *seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        *seed >> 16
+    }
+
+    #[test]
+    fn test_fast_parse_int_matches_scalar() {
+        let mut seed = 1;
+        for len in 1..=19 {
+            for _ in 0..200 {
+                let digits: Vec<u8> = (0..len)
+                    .map(|_| b'0' + (pseudo_random(&mut seed) % 10) as u8)
+                    .collect();
+                assert_eq!(
+                    fast_parse_int_from_bytes(&digits),
+                    parse_int_scalar(&digits),
+                    "{}",
+                    digits.escape_ascii()
+                );
+            }
+
+            let nines = vec![b'9'; len];
+            assert_eq!(fast_parse_int_from_bytes(&nines), parse_int_scalar(&nines));
+        }
+    }
+
+    #[test]
+    fn test_find_byte_matches_scalar() {
+        for len in 0..40 {
+            let haystack: Vec<u8> = (0..len).map(|i| b'a' + (i % 7) as u8).collect();
+            for needle in [b'a', b'c', b'g', b'z', b'\n', 0x80, 0xff, 0] {
+                assert_eq!(
+                    find_byte(&haystack, needle),
+                    find_byte_scalar(&haystack, needle)
+                );
+            }
+
+            // every position of a single delimiter, including high bytes around it
+            for position in 0..len {
+                let mut haystack = vec![0xffu8; len];
+                haystack[position] = b'\n';
+                assert_eq!(find_byte(&haystack, b'\n'), Some(position));
+                haystack[position] = 0x01;
+                assert_eq!(find_byte(&haystack, 0x01), Some(position));
+                assert_eq!(find_byte(&haystack, 0x00), None);
+            }
+        }
+    }
+
     #[test]
     fn test_split_once() {
         assert_eq!(